@@ -10,7 +10,7 @@ fn main() {
         let mut engine = TradingEngine::new();
         // Test with BTCUSDT symbol
         let symbols = vec!["btcusdt".to_string()];
-        match engine.start_with_market_router(symbols, None, None).await {
+        match engine.start_with_market_router(symbols).await {
             | Ok(_) => println!("E2E Binance stream test completed."),
             | Err(e) => eprintln!("E2E test failed: {}", e),
         }
@@ -0,0 +1,304 @@
+//! Batch backtests over a strategies × symbols × parameter sets matrix.
+//!
+//! The market data is loaded once, split by symbol and shared read-only; every cell of the
+//! matrix is backtested on its own thread with rayon. Results are ranked by an [`Objective`]
+//! into a [`Leaderboard`] that can be written as CSV (one summary row per cell) or JSON
+//! (including the full report of every cell).
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::fill_model::{FillModel, InstantFillModel};
+use super::optimizer::{Objective, Optimizer, ParamSpace, SearchMethod};
+use super::{BacktestReport, OrderSplit};
+use crate::risk::portfolio_risk::PreTradeRiskEngine;
+use crate::risk::position_sizer::PositionSizer;
+use crate::risk::{RiskRule, SignalLimits};
+use crate::strategies::StrategyConfig;
+use crate::utils::types::MarketData;
+use crate::{Error, Result};
+
+/// One backtest of the matrix
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BatchCell {
+    pub strategy: String,
+    pub symbol: String,
+    pub params: Value,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LeaderboardEntry {
+    /// 1 for the best score
+    pub rank: usize,
+    pub strategy: String,
+    pub symbol: String,
+    pub params: Value,
+    pub score: f64,
+    pub report: BacktestReport,
+}
+
+/// Ranked results of a batch run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Leaderboard {
+    pub objective: Objective,
+    /// Best first
+    pub entries: Vec<LeaderboardEntry>,
+    /// Cells that could not be backtested, with the reason
+    pub failures: Vec<(BatchCell, String)>,
+}
+
+impl Leaderboard {
+    /// One summary row per cell; params are written as JSON
+    pub fn write_csv<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let csv_err = |e: csv::Error| Error::DataError(format!("CSV write error: {e}"));
+        let mut wtr = csv::Writer::from_path(path).map_err(csv_err)?;
+        wtr.write_record([
+            "rank",
+            "strategy",
+            "symbol",
+            "score",
+            "total_return",
+            "sharpe",
+            "sortino",
+            "calmar",
+            "max_drawdown",
+            "profit_factor",
+            "trades",
+            "params",
+        ])
+        .map_err(csv_err)?;
+        for e in &self.entries {
+            let r = &e.report;
+            wtr.write_record(&[
+                e.rank.to_string(),
+                e.strategy.clone(),
+                e.symbol.clone(),
+                e.score.to_string(),
+                (r.ending_balance / r.starting_balance - 1.0).to_string(),
+                r.sharpe.to_string(),
+                r.sortino.to_string(),
+                r.calmar.to_string(),
+                r.max_drawdown.to_string(),
+                r.profit_factor.map(|pf| pf.to_string()).unwrap_or_default(),
+                r.total_trades.to_string(),
+                e.params.to_string(),
+            ])
+            .map_err(csv_err)?;
+        }
+        wtr.flush()?;
+        Ok(())
+    }
+
+    /// The whole leaderboard with every cell's full report
+    pub fn write_json<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        std::fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    pub fn print(&self, top: usize) {
+        println!("===== LEADERBOARD ({:?}) =====", self.objective);
+        for e in self.entries.iter().take(top) {
+            println!(
+                "{:>3}. {:<18} {:<12} {:>10.4}  DD {:>6.2}%  trades {:>4}  {}",
+                e.rank,
+                e.strategy,
+                e.symbol,
+                e.score,
+                e.report.max_drawdown * 100.0,
+                e.report.total_trades,
+                e.params
+            );
+        }
+        if !self.failures.is_empty() {
+            println!("{} cells failed", self.failures.len());
+        }
+    }
+}
+
+/// Runs every strategy, with every parameter set of its space, on every symbol
+pub struct BatchRunner {
+    /// Params may hold ranges and choices as in [`ParamSpace`]; each grid point is one set
+    pub strategies: Vec<StrategyConfig>,
+    /// Symbols to trade, each on its own bars only; every symbol in the data when empty
+    pub symbols: Vec<String>,
+    pub objective: Objective,
+    pub timeframe: String,
+    pub starting_balance: f64,
+    pub fee_bps: u16,
+    pub slippage_bps: u16,
+    pub fill_model: Box<dyn FillModel>,
+    pub risk_rules: Vec<Box<dyn RiskRule>>,
+    pub pre_trade_risk: Option<PreTradeRiskEngine>,
+    pub position_sizer: Option<Box<dyn PositionSizer>>,
+    pub limits: Option<SignalLimits>,
+    pub order_split: Option<OrderSplit>,
+}
+
+impl BatchRunner {
+    /// Runner with a 10k balance, no costs, risk rules, sizer, limits or splitting and instant
+    /// fills
+    pub fn new(strategies: Vec<StrategyConfig>, objective: Objective) -> Self {
+        Self {
+            strategies,
+            symbols: Vec::new(),
+            objective,
+            timeframe: "default".to_string(),
+            starting_balance: 10_000.0,
+            fee_bps: 0,
+            slippage_bps: 0,
+            fill_model: Box::new(InstantFillModel),
+            risk_rules: Vec::new(),
+            pre_trade_risk: None,
+            position_sizer: None,
+            limits: None,
+            order_split: None,
+        }
+    }
+
+    fn optimizer(&self, space: ParamSpace) -> Optimizer {
+        Optimizer {
+            timeframe: self.timeframe.clone(),
+            starting_balance: self.starting_balance,
+            fee_bps: self.fee_bps,
+            slippage_bps: self.slippage_bps,
+            fill_model: self.fill_model.clone(),
+            risk_rules: self.risk_rules.clone(),
+            pre_trade_risk: self.pre_trade_risk.clone(),
+            position_sizer: self.position_sizer.clone(),
+            limits: self.limits,
+            order_split: self.order_split,
+            ..Optimizer::new(space, self.objective, SearchMethod::Grid)
+        }
+    }
+
+    /// Backtest every cell in parallel and rank the results
+    pub fn run(&self, data: &[MarketData]) -> Result<Leaderboard> {
+        // grouped once; every cell of a symbol backtests the same borrowed bars
+        let mut by_symbol: BTreeMap<&str, Vec<MarketData>> = BTreeMap::new();
+        for md in data {
+            by_symbol.entry(md.symbol.as_str()).or_default().push(md.clone());
+        }
+        let symbols: Vec<&str> = if self.symbols.is_empty() {
+            by_symbol.keys().copied().collect()
+        } else {
+            self.symbols.iter().map(String::as_str).collect()
+        };
+
+        let mut cells = Vec::new();
+        for cfg in &self.strategies {
+            let optimizer = self.optimizer(ParamSpace::from_config(cfg)?);
+            for params in optimizer.space.grid() {
+                for symbol in &symbols {
+                    let mut params = params.clone();
+                    if let Some(p) = params.as_object_mut() {
+                        p.insert("symbol".into(), Value::from(*symbol));
+                    }
+                    cells.push((optimizer.space.clone(), *symbol, params));
+                }
+            }
+        }
+        log::info!("Batch backtesting {} cells", cells.len());
+
+        let results: Vec<(BatchCell, Result<BacktestReport>)> = cells
+            .into_par_iter()
+            .map(|(space, symbol, params)| {
+                let cell = BatchCell {
+                    strategy: space.strategy.clone(),
+                    symbol: symbol.to_string(),
+                    params,
+                };
+                let report = match by_symbol.get(symbol) {
+                    | Some(bars) => self.optimizer(space).evaluate(&cell.params, bars),
+                    | None => Err(Error::DataError(format!("no data for {symbol}"))),
+                };
+                (cell, report)
+            })
+            .collect();
+
+        let mut entries = Vec::new();
+        let mut failures = Vec::new();
+        for (cell, report) in results {
+            match report {
+                | Ok(report) => entries.push(LeaderboardEntry {
+                    rank: 0,
+                    score: self.objective.score(&report),
+                    strategy: cell.strategy,
+                    symbol: cell.symbol,
+                    params: cell.params,
+                    report,
+                }),
+                | Err(e) => {
+                    log::warn!("Batch cell {} on {} failed: {e}", cell.strategy, cell.symbol);
+                    failures.push((cell, e.to_string()));
+                }
+            }
+        }
+        // stable sort keeps matrix order for ties
+        entries.sort_by(|a, b| b.score.total_cmp(&a.score));
+        for (i, e) in entries.iter_mut().enumerate() {
+            e.rank = i + 1;
+        }
+        Ok(Leaderboard { objective: self.objective, entries, failures })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::types::TradingPair;
+    use serde_json::json;
+
+    fn bars(symbol: &str, phase: f64) -> Vec<MarketData> {
+        let (base, quote) = symbol.split_once('/').unwrap();
+        (0..200)
+            .map(|i| {
+                let px = 100.0 + 10.0 * (i as f64 / 8.0 + phase).sin();
+                MarketData {
+                    pair: TradingPair::new(base, quote),
+                    symbol: symbol.into(),
+                    timestamp: i * 3600,
+                    close: px,
+                    last_price: px,
+                    ..Default::default()
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn every_cell_is_ranked_once() {
+        let mut data = bars("SOL/USDC", 0.0);
+        data.extend(bars("BONK/USDC", 1.0));
+        data.sort_by_key(|d| d.timestamp);
+        let strategies = vec![
+            StrategyConfig {
+                name: "mean_reversion".into(),
+                enabled: true,
+                params: json!({ "lookback_period": [10, 20] }),
+                performance: None,
+            },
+            StrategyConfig {
+                name: "nope".into(),
+                enabled: true,
+                params: json!({}),
+                performance: None,
+            },
+        ];
+        let board = BatchRunner::new(strategies, Objective::TotalReturn).run(&data).unwrap();
+        assert_eq!(board.entries.len(), 4);
+        assert_eq!(board.failures.len(), 2);
+        assert!(board.entries.windows(2).all(|w| w[0].score >= w[1].score));
+        assert_eq!(board.entries.iter().map(|e| e.rank).collect::<Vec<_>>(), vec![1, 2, 3, 4]);
+        let symbols: Vec<&str> = board.entries.iter().map(|e| e.symbol.as_str()).collect();
+        assert_eq!(symbols.iter().filter(|s| **s == "SOL/USDC").count(), 2);
+
+        let dir = tempfile::tempdir().unwrap();
+        board.write_csv(dir.path().join("board.csv")).unwrap();
+        let csv = std::fs::read_to_string(dir.path().join("board.csv")).unwrap();
+        assert_eq!(csv.lines().count(), 5);
+    }
+}
@@ -1,36 +1,58 @@
 //! Backtest cache module
-//! Extends existing report caching to also persist raw market data blobs.
-//! Uses `sled` embedded database under $HOME/.algotrader/cache.
+//! Persists raw market data blobs and backtest reports in one `sled` database under
+//! $HOME/.algotrader/cache (trees `raw` and `reports`).
+//!
+//! sled locks its directory, so the database is opened once per process and shared.
 
+use crate::backtest::BacktestReport;
 use crate::{Error, Result};
-use sled::Db;
+use once_cell::sync::OnceCell;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
 
 const DB_SUBDIR: &str = ".algotrader/cache";
+const RAW_TREE: &str = "raw";
+const REPORTS_TREE: &str = "reports";
+
+static SHARED_DB: OnceCell<sled::Db> = OnceCell::new();
 
-fn open_db() -> Result<sled::Db> {
+/// Location of the shared cache database
+pub fn cache_dir() -> Result<PathBuf> {
     let mut dir =
         dirs::home_dir().ok_or_else(|| Error::DataError("cannot find home dir".into()))?;
     dir.push(DB_SUBDIR);
-    std::fs::create_dir_all(&dir).map_err(|e| Error::DataError(format!("mkdir error: {e}")))?;
-    sled::open(dir).map_err(|e| Error::DataError(format!("sled open error: {e}")))
+    Ok(dir)
+}
+
+fn shared_db() -> Result<&'static sled::Db> {
+    SHARED_DB.get_or_try_init(|| {
+        let dir = cache_dir()?;
+        std::fs::create_dir_all(&dir)
+            .map_err(|e| Error::DataError(format!("mkdir error: {e}")))?;
+        sled::open(dir).map_err(|e| Error::DataError(format!("sled open error: {e}")))
+    })
+}
+
+fn open_tree(db: &sled::Db, name: &str) -> Result<sled::Tree> {
+    db.open_tree(name).map_err(|e| Error::DataError(format!("sled open tree error: {e}")))
 }
 
 /// Store compressed blob (zstd) keyed by provided key
 pub fn put_raw(key: &str, bytes: &[u8]) -> Result<()> {
-    let db = open_db()?;
+    let tree = open_tree(shared_db()?, RAW_TREE)?;
     let compressed = zstd::encode_all(bytes, 1)
         .map_err(|e| Error::DataError(format!("zstd encode error: {e}")))?;
-    db.insert(key.as_bytes(), compressed)
+    tree.insert(key.as_bytes(), compressed)
         .map_err(|e| Error::DataError(format!("sled write error: {e}")))?;
-    db.flush()
+    tree.flush()
         .map_err(|e| Error::DataError(format!("sled flush error: {e}")))?;
     Ok(())
 }
 
 /// Retrieve compressed blob if exists
 pub fn get_raw(key: &str) -> Result<Option<Vec<u8>>> {
-    let db = open_db()?;
-    if let Some(val) = db
+    let tree = open_tree(shared_db()?, RAW_TREE)?;
+    if let Some(val) = tree
         .get(key.as_bytes())
         .map_err(|e| Error::DataError(format!("sled get error: {e}")))?
     {
@@ -42,57 +64,157 @@ pub fn get_raw(key: &str) -> Result<Option<Vec<u8>>> {
     }
 }
 
-/// ---------------------------------------------------------------------------
-/// Report cache for BacktestReport (was previously in cache.rs)
-/// ---------------------------------------------------------------------------
-use crate::backtest::BacktestReport;
+/// Raw-cache key for a data file. Size and modification time are part of the key, so an
+/// edited file is read again instead of being served from the cache.
+pub fn file_key(kind: &str, path: &Path) -> Result<String> {
+    let meta = std::fs::metadata(path)?;
+    let modified = meta
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+        .map_or(0, |d| d.as_nanos());
+    let path = path.to_string_lossy();
+    Ok(build_key(&[kind, path.as_ref(), &meta.len().to_string(), &modified.to_string()]))
+}
+
+// ---------------------------------------------------------------------------
+// Report cache for BacktestReport (was previously in cache.rs)
+// ---------------------------------------------------------------------------
+
+/// A cached report together with what produced it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CacheEntry {
+    /// Run fingerprint, see [`crate::backtest::Backtester::fingerprint`]
+    pub key: String,
+    /// Unix time the entry was written
+    pub created_at: i64,
+    /// Crate version that produced the report
+    pub version: String,
+    pub strategy: String,
+    pub symbols: String,
+    pub timeframe: String,
+    pub start_ts: i64,
+    pub end_ts: i64,
+    pub report: BacktestReport,
+}
+
+/// Which entries [`BacktestCache::prune`] removes. Entries that no longer decode are always
+/// removed.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PruneFilter {
+    /// Entries written before this unix time
+    pub older_than: Option<i64>,
+    /// Entries written by another crate version
+    pub other_versions: bool,
+    /// Everything
+    pub all: bool,
+}
+
+fn read_error(e: sled::Error) -> Error {
+    Error::DataError(format!("Cache read error: {e}"))
+}
 
 /// Lightweight embedded cache for backtest results.
 #[derive(Clone)]
 pub struct BacktestCache {
-    db: Db,
+    reports: sled::Tree,
 }
 
 impl BacktestCache {
+    /// Report cache in its own database at `path`
     pub fn open(path: &str) -> Result<Self> {
         let db =
             sled::open(path).map_err(|e| Error::DataError(format!("Cache open error: {e}")))?;
-        Ok(Self { db })
+        Ok(Self { reports: open_tree(&db, REPORTS_TREE)? })
     }
 
-    fn key(strategy: &str, symbol: &str, timeframe: &str, start_ts: i64, end_ts: i64) -> Vec<u8> {
-        format!("{strategy}:{symbol}:{timeframe}:{start_ts}:{end_ts}").into_bytes()
+    /// Report cache in the shared database under [`cache_dir`]
+    pub fn open_default() -> Result<Self> {
+        Ok(Self { reports: open_tree(shared_db()?, REPORTS_TREE)? })
     }
 
-    pub fn get(
-        &self, strategy: &str, symbol: &str, timeframe: &str, start_ts: i64, end_ts: i64,
-    ) -> Result<Option<BacktestReport>> {
-        let key = Self::key(strategy, symbol, timeframe, start_ts, end_ts);
+    pub fn get(&self, key: &str) -> Result<Option<CacheEntry>> {
         if let Some(ivec) = self
-            .db
+            .reports
             .get(key)
-            .map_err(|e| Error::DataError(format!("Cache read error: {e}")))?
+            .map_err(read_error)?
         {
-            let report: BacktestReport = bincode::deserialize(&ivec)
-                .map_err(|e| Error::DataError(format!("Cache deserialize error: {e}")))?;
-            Ok(Some(report))
+            // Entries written by an older report layout no longer decode; recompute them
+            match bincode::deserialize::<CacheEntry>(&ivec) {
+                | Ok(entry) => Ok(Some(entry)),
+                | Err(e) => {
+                    log::debug!("Ignoring stale cache entry: {e}");
+                    Ok(None)
+                }
+            }
         } else {
             Ok(None)
         }
     }
 
-    pub fn insert(
-        &self, strategy: &str, symbol: &str, timeframe: &str, start_ts: i64, end_ts: i64,
-        report: &BacktestReport,
-    ) -> Result<()> {
-        let key = Self::key(strategy, symbol, timeframe, start_ts, end_ts);
-        let bytes = bincode::serialize(report)
+    pub fn insert(&self, entry: &CacheEntry) -> Result<()> {
+        let bytes = bincode::serialize(entry)
             .map_err(|e| Error::DataError(format!("Cache serialize error: {e}")))?;
-        self.db
-            .insert(key, bytes)
+        self.reports
+            .insert(entry.key.as_bytes(), bytes)
             .map_err(|e| Error::DataError(format!("Cache write error: {e}")))?;
+        self.reports
+            .flush()
+            .map_err(|e| Error::DataError(format!("Cache flush error: {e}")))?;
         Ok(())
     }
+
+    /// Every readable entry, oldest first
+    pub fn entries(&self) -> Result<Vec<CacheEntry>> {
+        let mut out = Vec::new();
+        for item in self.reports.iter() {
+            let (_, value) = item.map_err(read_error)?;
+            if let Ok(entry) = bincode::deserialize::<CacheEntry>(&value) {
+                out.push(entry);
+            }
+        }
+        out.sort_by_key(|e| e.created_at);
+        Ok(out)
+    }
+
+    /// Entries whose key starts with `prefix`
+    pub fn find(&self, prefix: &str) -> Result<Vec<CacheEntry>> {
+        let mut out = Vec::new();
+        for item in self.reports.scan_prefix(prefix.as_bytes()) {
+            let (_, value) = item.map_err(read_error)?;
+            if let Ok(entry) = bincode::deserialize::<CacheEntry>(&value) {
+                out.push(entry);
+            }
+        }
+        Ok(out)
+    }
+
+    /// Remove the entries selected by `filter`, returning how many were removed
+    pub fn prune(&self, filter: PruneFilter) -> Result<usize> {
+        let version = env!("CARGO_PKG_VERSION");
+        let mut removed = 0;
+        for item in self.reports.iter() {
+            let (key, value) = item.map_err(read_error)?;
+            let drop = match bincode::deserialize::<CacheEntry>(&value) {
+                | Err(_) => true,
+                | Ok(entry) => {
+                    filter.all
+                        || filter.older_than.is_some_and(|t| entry.created_at < t)
+                        || (filter.other_versions && entry.version != version)
+                }
+            };
+            if drop {
+                self.reports
+                    .remove(key)
+                    .map_err(|e| Error::DataError(format!("Cache write error: {e}")))?;
+                removed += 1;
+            }
+        }
+        self.reports
+            .flush()
+            .map_err(|e| Error::DataError(format!("Cache flush error: {e}")))?;
+        Ok(removed)
+    }
 }
 
 /// Utility to build a cache key from arbitrary strings (source,symbol,tf,etc.)
@@ -103,3 +225,40 @@ pub fn build_key(parts: &[&str]) -> String {
     hasher.update(concat.as_bytes());
     format!("{:x}", hasher.finalize())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(key: &str, created_at: i64, version: &str) -> CacheEntry {
+        CacheEntry {
+            key: key.to_string(),
+            created_at,
+            version: version.to_string(),
+            strategy: "s".into(),
+            symbols: "SOL/USDC".into(),
+            timeframe: "1h".into(),
+            start_ts: 0,
+            end_ts: 3_600,
+            report: BacktestReport::from_ledger(1.0, 1.0, 0.0, vec![1.0], vec![0], Vec::new()),
+        }
+    }
+
+    #[test]
+    fn prune_by_age_and_version() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = BacktestCache::open(dir.path().to_str().unwrap()).unwrap();
+        cache.insert(&entry("aa", 100, env!("CARGO_PKG_VERSION"))).unwrap();
+        cache.insert(&entry("ab", 200, "0.0.0-old")).unwrap();
+        cache.insert(&entry("bb", 300, env!("CARGO_PKG_VERSION"))).unwrap();
+        assert_eq!(cache.find("a").unwrap().len(), 2);
+        assert_eq!(cache.get("bb").unwrap().unwrap().created_at, 300);
+
+        let old = PruneFilter { older_than: Some(150), ..Default::default() };
+        assert_eq!(cache.prune(old).unwrap(), 1);
+        let stale = PruneFilter { other_versions: true, ..Default::default() };
+        assert_eq!(cache.prune(stale).unwrap(), 1);
+        let keys: Vec<String> = cache.entries().unwrap().into_iter().map(|e| e.key).collect();
+        assert_eq!(keys, vec!["bb".to_string()]);
+    }
+}
@@ -1,42 +1,124 @@
 #![allow(clippy::large_enum_variant)]
-use std::collections::VecDeque;
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
 
 use super::SimulatedTrade;
 use crate::utils::types::MarketData;
 
-/// Events that drive the back-testing simulation.
+/// Events that drive the back-testing simulation. Market data is borrowed from the data the
+/// backtest runs over.
 #[derive(Debug, Clone)]
-pub enum BacktestEvent {
+pub enum BacktestEvent<'a> {
     /// Incoming market data (tick or aggregated candle)
-    Market(MarketData),
-    /// A trade that has been generated by a strategy and is ready to be applied to the portfolio
+    Market(&'a MarketData),
+    /// A fill that is ready to be applied to the portfolio
     Trade(SimulatedTrade),
+    /// Timer `id` of the strategy at index `strategy` fires at `timestamp`
+    Timer { timestamp: i64, strategy: usize, id: u64 },
 }
 
-/// Simple FIFO event queue for the simulator loop.
+impl BacktestEvent<'_> {
+    /// Simulated time at which the event happens
+    pub fn timestamp(&self) -> i64 {
+        match self {
+            | BacktestEvent::Market(md) => md.timestamp,
+            | BacktestEvent::Trade(trade) => trade.timestamp,
+            | BacktestEvent::Timer { timestamp, .. } => *timestamp,
+        }
+    }
+}
+
+struct Scheduled<'a> {
+    timestamp: i64,
+    seq: u64,
+    event: BacktestEvent<'a>,
+}
+
+impl PartialEq for Scheduled<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        (self.timestamp, self.seq) == (other.timestamp, other.seq)
+    }
+}
+
+impl Eq for Scheduled<'_> {}
+
+impl PartialOrd for Scheduled<'_> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Scheduled<'_> {
+    // reversed: BinaryHeap is a max-heap and the earliest event must pop first
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.timestamp.cmp(&self.timestamp).then_with(|| other.seq.cmp(&self.seq))
+    }
+}
+
+/// Event queue for the simulator loop, ordered by event time and then by insertion order.
+///
+/// A trade pushed while handling bar N therefore applies before bar N+1, while events that
+/// share a timestamp keep the order in which they were pushed.
 #[derive(Default)]
-pub struct EventQueue {
-    queue: VecDeque<BacktestEvent>,
+pub struct EventQueue<'a> {
+    heap: BinaryHeap<Scheduled<'a>>,
+    next_seq: u64,
 }
 
-impl EventQueue {
+impl<'a> EventQueue<'a> {
     /// Create an empty queue
     pub fn new() -> Self {
-        Self { queue: VecDeque::new() }
+        Self::default()
+    }
+
+    /// Schedule an event at its own timestamp
+    pub fn push(&mut self, evt: BacktestEvent<'a>) {
+        let seq = self.next_seq;
+        self.next_seq += 1;
+        self.heap.push(Scheduled { timestamp: evt.timestamp(), seq, event: evt });
+    }
+
+    /// Pop the earliest event, if any
+    pub fn pop(&mut self) -> Option<BacktestEvent<'a>> {
+        self.heap.pop().map(|s| s.event)
     }
 
-    /// Push a new event to the back of the queue
-    pub fn push(&mut self, evt: BacktestEvent) {
-        self.queue.push_back(evt);
+    /// Time of the next event, if any
+    pub fn peek_time(&self) -> Option<i64> {
+        self.heap.peek().map(|s| s.timestamp)
     }
 
-    /// Pop the next event, if any
-    pub fn pop(&mut self) -> Option<BacktestEvent> {
-        self.queue.pop_front()
+    pub fn len(&self) -> usize {
+        self.heap.len()
     }
 
     /// Whether queue is empty
     pub fn is_empty(&self) -> bool {
-        self.queue.is_empty()
+        self.heap.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn timer(timestamp: i64, id: u64) -> BacktestEvent<'static> {
+        BacktestEvent::Timer { timestamp, strategy: 0, id }
+    }
+
+    #[test]
+    fn pops_by_time_then_insertion_order() {
+        let mut q = EventQueue::new();
+        q.push(timer(20, 1));
+        q.push(timer(10, 2));
+        q.push(timer(20, 3));
+        q.push(timer(10, 4));
+        let order: Vec<u64> = std::iter::from_fn(|| q.pop())
+            .map(|e| match e {
+                | BacktestEvent::Timer { id, .. } => id,
+                | _ => unreachable!(),
+            })
+            .collect();
+        assert_eq!(order, vec![2, 4, 1, 3]);
     }
 }
@@ -0,0 +1,397 @@
+//! Fill models decide when and at what price simulated orders execute.
+//!
+//! The backtester turns every strategy signal into a [`SimOrder`] resting in a [`SimBook`].
+//! On each bar the book asks the configured [`FillModel`] whether (and how much of) each
+//! eligible order for that symbol fills.
+
+use super::SimulatedTrade;
+use crate::utils::types::{MarketData, OrderSide, OrderType};
+
+/// Default time in force of resting limit orders under [`RealisticFillModel`], in bars
+pub const DEFAULT_EXPIRY_BARS: usize = 24;
+
+/// Order resting in the simulated book
+#[derive(Debug, Clone)]
+pub struct SimOrder {
+    pub id: u64,
+    pub strategy: String,
+    pub symbol: String,
+    pub side: OrderSide,
+    pub order_type: OrderType,
+    /// Quantity still open
+    pub qty: f64,
+    pub limit_price: Option<f64>,
+    pub stop_price: Option<f64>,
+    /// Price the strategy saw when it emitted the signal
+    pub ref_price: f64,
+    /// Timestamp of the bar that produced the order
+    pub submitted_at: i64,
+    /// Per-symbol bar index from which the order may execute (latency)
+    pub eligible_from: usize,
+    /// Whether the stop of a stop / stop-limit order has been hit
+    pub triggered: bool,
+}
+
+impl SimOrder {
+    fn is_buy(&self) -> bool {
+        matches!(self.side, OrderSide::Buy)
+    }
+
+    /// Trade event for `fill`; PnL and fee are filled in when the portfolio applies it
+    pub fn to_trade(&self, fill: SimFill, timestamp: i64) -> SimulatedTrade {
+        SimulatedTrade {
+            timestamp,
+            strategy: self.strategy.clone(),
+            symbol: self.symbol.clone(),
+            side: self.side,
+            qty: fill.qty,
+            price: fill.price,
+            pnl: 0.0,
+            fee: 0.0,
+        }
+    }
+}
+
+/// A (possibly partial) execution produced by a fill model
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SimFill {
+    pub qty: f64,
+    pub price: f64,
+}
+
+/// Execution model used by the backtester
+pub trait FillModel: Send + Sync {
+    /// Bars (or ticks in tick mode) between a signal and the first chance to execute it
+    fn latency_bars(&self) -> usize {
+        0
+    }
+
+    /// Bars a resting limit or stop-limit order stays in the book before it is cancelled;
+    /// `None` keeps it until the end of the run
+    fn expiry_bars(&self) -> Option<usize> {
+        None
+    }
+
+    /// Try to execute `order` against `bar`. Implementations may flip `order.triggered`;
+    /// the caller reduces `order.qty` by the returned fill.
+    fn try_fill(
+        &self, order: &mut SimOrder, bar: &MarketData, slippage_bps: u16,
+    ) -> Option<SimFill>;
+
+    /// Model type and parameters (used in backtest fingerprints)
+    fn describe(&self) -> String {
+        std::any::type_name::<Self>().to_string()
+    }
+
+    fn box_clone(&self) -> Box<dyn FillModel>;
+}
+
+impl Clone for Box<dyn FillModel> {
+    fn clone(&self) -> Self {
+        self.box_clone()
+    }
+}
+
+fn slip(price: f64, is_buy: bool, frac: f64) -> f64 {
+    if is_buy {
+        price * (1.0 + frac)
+    } else {
+        price * (1.0 - frac)
+    }
+}
+
+/// Fills every order in full at the signal price adjusted by the flat slippage,
+/// ignoring order type. This is the historical backtester behaviour and the default.
+#[derive(Debug, Clone, Default)]
+pub struct InstantFillModel;
+
+impl FillModel for InstantFillModel {
+    fn try_fill(
+        &self, order: &mut SimOrder, _bar: &MarketData, slippage_bps: u16,
+    ) -> Option<SimFill> {
+        let price = slip(order.ref_price, order.is_buy(), slippage_bps as f64 / 10_000.0);
+        Some(SimFill { qty: order.qty, price })
+    }
+
+    fn box_clone(&self) -> Box<dyn FillModel> {
+        Box::new(self.clone())
+    }
+}
+
+/// Bar-driven execution with latency, trigger semantics, volume caps and market impact.
+///
+/// * Market orders fill at the signal price on the signal bar, otherwise at the bar open.
+/// * Limit orders rest until a bar trades through the limit (low for buys, high for sells) and
+///   fill at the better of the open and the limit.
+/// * Stops trigger on the bar high/low and then behave as market (stop) or limit (stop-limit).
+/// * Each fill is capped at `participation_rate` of the bar volume; the rest stays open.
+/// * Marketable fills pay `impact_coef * sqrt(qty / volume)` on top of the flat slippage.
+/// * Limit and stop-limit orders are cancelled after `expiry_bars` eligible bars.
+#[derive(Debug, Clone)]
+pub struct RealisticFillModel {
+    pub latency_bars: usize,
+    /// Time in force of resting limit orders in bars (`None`: good till the end of the run)
+    pub expiry_bars: Option<usize>,
+    /// Maximum fraction of a bar's volume a single order may take (0 disables the cap)
+    pub participation_rate: f64,
+    /// Square-root impact coefficient (fraction of price at 100 % participation)
+    pub impact_coef: f64,
+}
+
+impl Default for RealisticFillModel {
+    fn default() -> Self {
+        Self {
+            latency_bars: 1,
+            expiry_bars: Some(DEFAULT_EXPIRY_BARS),
+            participation_rate: 0.1,
+            impact_coef: 0.1,
+        }
+    }
+}
+
+impl RealisticFillModel {
+    fn capped_qty(&self, wanted: f64, bar: &MarketData) -> f64 {
+        match bar.volume {
+            | Some(v) if v > 0.0 && self.participation_rate > 0.0 => {
+                wanted.min(v * self.participation_rate)
+            }
+            | _ => wanted,
+        }
+    }
+
+    fn impact(&self, qty: f64, bar: &MarketData) -> f64 {
+        match bar.volume {
+            | Some(v) if v > 0.0 => self.impact_coef * (qty / v).sqrt(),
+            | _ => 0.0,
+        }
+    }
+
+    /// Marketable execution: slippage and impact on top of `base`
+    fn take(&self, order: &SimOrder, base: f64, bar: &MarketData, slippage_bps: u16) -> SimFill {
+        let qty = self.capped_qty(order.qty, bar);
+        let frac = slippage_bps as f64 / 10_000.0 + self.impact(qty, bar);
+        SimFill { qty, price: slip(base, order.is_buy(), frac) }
+    }
+
+    /// Passive execution at `limit` once the bar trades through it
+    fn rest(&self, order: &SimOrder, limit: f64, bar: &MarketData) -> Option<SimFill> {
+        let open = bar.open.unwrap_or(bar.close);
+        let (crossed, price) = if order.is_buy() {
+            (bar.low.unwrap_or(bar.close) <= limit, open.min(limit))
+        } else {
+            (bar.high.unwrap_or(bar.close) >= limit, open.max(limit))
+        };
+        crossed.then(|| SimFill { qty: self.capped_qty(order.qty, bar), price })
+    }
+}
+
+impl FillModel for RealisticFillModel {
+    fn latency_bars(&self) -> usize {
+        self.latency_bars
+    }
+
+    fn expiry_bars(&self) -> Option<usize> {
+        self.expiry_bars
+    }
+
+    fn try_fill(
+        &self, order: &mut SimOrder, bar: &MarketData, slippage_bps: u16,
+    ) -> Option<SimFill> {
+        let open = bar.open.unwrap_or(bar.close);
+        // Orders executing on the bar that produced them only know the signal price
+        let base = if bar.timestamp == order.submitted_at { order.ref_price } else { open };
+
+        if matches!(order.order_type, OrderType::Stop | OrderType::StopLimit) && !order.triggered {
+            let stop = order.stop_price?;
+            let hit = if order.is_buy() {
+                bar.high.unwrap_or(bar.close) >= stop
+            } else {
+                bar.low.unwrap_or(bar.close) <= stop
+            };
+            if !hit {
+                return None;
+            }
+            order.triggered = true;
+            if order.order_type == OrderType::Stop {
+                // Gaps through the stop fill at the open, not at the stop
+                let trigger_px = if order.is_buy() { open.max(stop) } else { open.min(stop) };
+                return Some(self.take(order, trigger_px, bar, slippage_bps));
+            }
+        }
+
+        match order.order_type {
+            | OrderType::Market | OrderType::Stop => {
+                Some(self.take(order, base, bar, slippage_bps))
+            }
+            | OrderType::Limit => self.rest(order, order.limit_price?, bar),
+            | OrderType::StopLimit => {
+                let limit = order.limit_price.or(order.stop_price)?;
+                self.rest(order, limit, bar)
+            }
+        }
+    }
+
+    fn describe(&self) -> String {
+        format!("{self:?}")
+    }
+
+    fn box_clone(&self) -> Box<dyn FillModel> {
+        Box::new(self.clone())
+    }
+}
+
+/// Orders waiting for execution during a backtest
+#[derive(Debug, Default)]
+pub struct SimBook {
+    orders: Vec<SimOrder>,
+    next_id: u64,
+    expired: usize,
+}
+
+impl SimBook {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add an order and return its id
+    pub fn submit(&mut self, mut order: SimOrder) -> u64 {
+        self.next_id += 1;
+        order.id = self.next_id;
+        self.orders.push(order);
+        self.next_id
+    }
+
+    /// Run every eligible order for `bar`'s symbol through `model`. Returns the fills with a
+    /// snapshot of the order they belong to; fully filled orders leave the book, as do
+    /// resting limit orders whose time in force ran out on this bar.
+    pub fn match_bar(
+        &mut self, bar: &MarketData, bar_index: usize, model: &dyn FillModel, slippage_bps: u16,
+    ) -> Vec<(SimOrder, SimFill)> {
+        let symbol = bar.pair.to_string();
+        let expiry = model.expiry_bars();
+        let mut fills = Vec::new();
+        for order in self.orders.iter_mut() {
+            if order.symbol != symbol || order.eligible_from > bar_index {
+                continue;
+            }
+            if let Some(fill) = model.try_fill(order, bar, slippage_bps) {
+                if fill.qty > 0.0 {
+                    order.qty -= fill.qty;
+                    fills.push((order.clone(), fill));
+                }
+            }
+            let resting = matches!(order.order_type, OrderType::Limit | OrderType::StopLimit);
+            let lived = bar_index + 1 - order.eligible_from;
+            if resting && order.qty > 1e-12 && expiry.is_some_and(|n| lived >= n) {
+                log::debug!("{} order {} expired unfilled", order.symbol, order.id);
+                order.qty = 0.0;
+                self.expired += 1;
+            }
+        }
+        self.orders.retain(|o| o.qty > 1e-12);
+        fills
+    }
+
+    /// Orders cancelled because their time in force ran out
+    pub fn expired(&self) -> usize {
+        self.expired
+    }
+
+    pub fn len(&self) -> usize {
+        self.orders.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.orders.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::types::TradingPair;
+
+    fn bar(ts: i64, open: f64, high: f64, low: f64, close: f64, volume: f64) -> MarketData {
+        MarketData {
+            pair: TradingPair::new("SOL", "USDC"),
+            symbol: "SOL/USDC".into(),
+            timestamp: ts,
+            open: Some(open),
+            high: Some(high),
+            low: Some(low),
+            close,
+            last_price: close,
+            volume: Some(volume),
+            ..Default::default()
+        }
+    }
+
+    fn order(side: OrderSide, order_type: OrderType, qty: f64) -> SimOrder {
+        SimOrder {
+            id: 0,
+            strategy: "test".into(),
+            symbol: "SOL/USDC".into(),
+            side,
+            order_type,
+            qty,
+            limit_price: None,
+            stop_price: None,
+            ref_price: 100.0,
+            submitted_at: 0,
+            eligible_from: 0,
+            triggered: false,
+        }
+    }
+
+    #[test]
+    fn limit_buy_waits_for_low_to_cross() {
+        let model = RealisticFillModel { participation_rate: 0.0, ..Default::default() };
+        let mut o = order(OrderSide::Buy, OrderType::Limit, 1.0);
+        o.limit_price = Some(95.0);
+        assert!(model.try_fill(&mut o, &bar(1, 100.0, 101.0, 96.0, 99.0, 1e6), 0).is_none());
+        let fill = model.try_fill(&mut o, &bar(2, 99.0, 99.0, 94.0, 95.0, 1e6), 0).unwrap();
+        assert_eq!(fill.price, 95.0);
+    }
+
+    #[test]
+    fn stop_gap_fills_at_open() {
+        let model = RealisticFillModel { impact_coef: 0.0, ..Default::default() };
+        let mut o = order(OrderSide::Sell, OrderType::Stop, 1.0);
+        o.stop_price = Some(90.0);
+        let fill = model.try_fill(&mut o, &bar(1, 85.0, 86.0, 80.0, 82.0, 1e6), 0).unwrap();
+        assert!(o.triggered);
+        assert_eq!(fill.price, 85.0);
+    }
+
+    #[test]
+    fn volume_cap_leaves_remainder_resting() {
+        let model = RealisticFillModel { latency_bars: 0, ..Default::default() };
+        let mut book = SimBook::new();
+        book.submit(order(OrderSide::Buy, OrderType::Market, 15.0));
+        let first = book.match_bar(&bar(1, 100.0, 100.0, 100.0, 100.0, 100.0), 0, &model, 0);
+        assert_eq!(first[0].1.qty, 10.0);
+        assert!(first[0].1.price > 100.0, "impact pushes buys up");
+        assert_eq!(book.len(), 1);
+        let second = book.match_bar(&bar(2, 100.0, 100.0, 100.0, 100.0, 100.0), 1, &model, 0);
+        assert!((second[0].1.qty - 5.0).abs() < 1e-12);
+        assert!(book.is_empty());
+    }
+
+    #[test]
+    fn untouched_limit_expires() {
+        let model =
+            RealisticFillModel { latency_bars: 0, expiry_bars: Some(2), ..Default::default() };
+        let mut book = SimBook::new();
+        let mut o = order(OrderSide::Buy, OrderType::Limit, 1.0);
+        o.limit_price = Some(90.0);
+        book.submit(o);
+        let quiet = |ts| bar(ts, 100.0, 101.0, 99.0, 100.0, 1e6);
+        assert!(book.match_bar(&quiet(1), 0, &model, 0).is_empty());
+        assert_eq!(book.len(), 1);
+        assert!(book.match_bar(&quiet(2), 1, &model, 0).is_empty());
+        assert!(book.is_empty());
+        assert_eq!(book.expired(), 1);
+        // days later the price comes back, but the order is gone
+        assert!(book.match_bar(&bar(3, 90.0, 90.0, 85.0, 88.0, 1e6), 2, &model, 0).is_empty());
+    }
+}
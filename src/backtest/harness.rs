@@ -1,104 +1,399 @@
-//! Walk-forward optimisation harness
-//! Iteratively runs backtests on rolling train/test windows and aggregates reports.
-
-use crate::backtest::{
-    providers::CSVHistoricalDataProvider, tick_provider::CSVTicksProvider, Backtester,
-    HistoricalDataProvider, SimMode,
-};
-use crate::persistence;
-use crate::strategies::{MeanReversionStrategy, TimeFrame, TradingStrategy};
-use crate::utils::types::MarketData;
-use crate::Result;
-use std::io::Write;
-use std::path::{Path, PathBuf};
-use std::sync::Arc;
-use tempfile::NamedTempFile;
+//! Walk-forward and cross-validation harness
+//! Splits one dataset into train/test windows (rolling or anchored walk-forward, purged k-fold,
+//! combinatorial purged CV) and backtests every window in memory on the full `MarketData`.
+
+use std::ops::Range;
+use std::path::Path;
+
+use crate::backtest::optimizer::Optimizer;
+use crate::backtest::report::sharpe;
+use crate::backtest::{csv_provider, default_backtester, BacktestReport, SimMode};
+use crate::utils::types::{MarketData, TradingPair};
+use crate::{Error, Result};
+
+/// How the training window moves between walk-forward steps
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum WindowMode {
+    /// Fixed-length training window sliding forward by `step_days`
+    #[default]
+    Rolling,
+    /// Training always starts at the first bar and grows by `step_days` (expanding window)
+    Anchored,
+}
 
 /// Simple config expressed in days for training & testing spans
+#[derive(Debug, Clone, Copy)]
 pub struct WalkForwardConfig {
     pub train_days: i64,
     pub test_days: i64,
     pub step_days: i64, // slide step between windows
+    pub mode: WindowMode,
 }
 
-/// Run walk-forward analysis on a single data CSV file.
+impl Default for WalkForwardConfig {
+    fn default() -> Self {
+        Self { train_days: 90, test_days: 30, step_days: 30, mode: WindowMode::Rolling }
+    }
+}
+
+/// Training covers `[train_start, train_end)`, testing `[train_end, test_end)`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WindowBounds {
+    pub train_start: i64,
+    pub train_end: i64,
+    pub test_end: i64,
+}
+
+impl WalkForwardConfig {
+    /// Windows that fit inside data spanning `start_ts..=end_ts`
+    pub fn windows(&self, start_ts: i64, end_ts: i64) -> Result<Vec<WindowBounds>> {
+        if self.train_days <= 0 || self.test_days <= 0 || self.step_days <= 0 {
+            return Err(Error::InvalidArgument("walk-forward spans must be positive".into()));
+        }
+        let train_secs = self.train_days * 86_400;
+        let test_secs = self.test_days * 86_400;
+        let step_secs = self.step_days * 86_400;
+        let mut windows = Vec::new();
+        let mut window_start = start_ts;
+        while window_start + train_secs + test_secs <= end_ts {
+            let train_end = window_start + train_secs;
+            let train_start = match self.mode {
+                | WindowMode::Rolling => window_start,
+                | WindowMode::Anchored => start_ts,
+            };
+            windows.push(WindowBounds { train_start, train_end, test_end: train_end + test_secs });
+            window_start += step_secs;
+        }
+        Ok(windows)
+    }
+}
+
+/// Bars in `[from, to)` of time-sorted `data`
+fn slice(data: &[MarketData], from: i64, to: i64) -> &[MarketData] {
+    let start = data.partition_point(|d| d.timestamp < from);
+    let end = data.partition_point(|d| d.timestamp < to).max(start);
+    &data[start..end]
+}
+
+fn span(data: &[MarketData]) -> Result<(i64, i64)> {
+    match (data.first(), data.last()) {
+        | (Some(first), Some(last)) => Ok((first.timestamp, last.timestamp)),
+        | _ => Err(Error::DataError("empty dataset".into())),
+    }
+}
+
+/// Run walk-forward analysis on a single data CSV file with the default strategy setup.
+/// `pair` labels a file without a symbol column whose name does not give the pair.
 /// Returns backtest reports for each test window.
-/// NOTE: This uses a temporary filtered CSV per window for simplicity.
-/// Run walk-forward analysis on a single data CSV file.
-/// `data_path` is borrowed as a `Path` (no needless `PathBuf`).
 pub async fn run_walk_forward(
-    data_path: &Path, timeframe: &str, sim_mode: SimMode, cfg: WalkForwardConfig,
-) -> Result<Vec<crate::backtest::BacktestReport>> {
-    // Load full dataset once (bar or tick provider just for timestamps)
-    let provider: Box<dyn HistoricalDataProvider> = match sim_mode {
-        | SimMode::Bar => Box::new(CSVHistoricalDataProvider::new()),
-        | SimMode::Tick => Box::new(CSVTicksProvider::new()),
-    };
+    data_path: &Path, pair: Option<&TradingPair>, timeframe: &str, sim_mode: SimMode,
+    cfg: WalkForwardConfig,
+) -> Result<Vec<BacktestReport>> {
+    // Load full dataset once
+    let provider = csv_provider(sim_mode, pair);
     let all_data = provider.load(data_path)?;
-    if all_data.is_empty() {
-        return Err(crate::Error::DataError("empty dataset".into()));
-    }
-
-    let mut reports: Vec<crate::backtest::BacktestReport> = Vec::new();
-    let start_ts = all_data.first().unwrap().timestamp;
-    let end_ts = all_data.last().unwrap().timestamp;
-    let mut window_start = start_ts;
-    let train_secs = cfg.train_days * 86_400;
-    let test_secs = cfg.test_days * 86_400;
-    let step_secs = cfg.step_days * 86_400;
-
-    while window_start + train_secs + test_secs <= end_ts {
-        let train_end = window_start + train_secs;
-        let test_end = train_end + test_secs;
-        // Extract test slice
-        let test_slice: Vec<&MarketData> = all_data
-            .iter()
-            .filter(|d| d.timestamp >= train_end && d.timestamp < test_end)
-            .collect();
-        if test_slice.is_empty() {
-            window_start += step_secs;
+    let (start_ts, end_ts) = span(&all_data)?;
+
+    let mut reports = Vec::new();
+    for w in cfg.windows(start_ts, end_ts)? {
+        let test = slice(&all_data, w.train_end, w.test_end);
+        if test.is_empty() {
             continue;
         }
-        // Write temp CSV for provider reuse
-        let mut tmp = NamedTempFile::new()?;
-        // assume MarketData close column exists
-        writeln!(tmp, "timestamp,close")?;
-        for d in &test_slice {
-            writeln!(tmp, "{},{}", d.timestamp, d.close)?;
+        let mut bt = default_backtester(provider.clone(), timeframe, sim_mode);
+        reports.push(bt.run_with_data(test).await?);
+    }
+    Ok(reports)
+}
+
+/// Outcome of one optimised walk-forward window
+#[derive(Debug, Clone)]
+pub struct WalkForwardWindow {
+    pub train_start: i64,
+    pub train_end: i64,
+    pub test_end: i64,
+    /// Parameters chosen on the training slice
+    pub params: serde_json::Value,
+    pub in_sample_score: f64,
+    /// Out-of-sample report of `params` on the test slice
+    pub report: crate::backtest::BacktestReport,
+}
+
+/// Walk-forward with re-optimisation: every window searches `optimizer`'s space on the
+/// training slice and backtests the winner on the following test slice.
+pub fn run_walk_forward_optimized(
+    data: &[MarketData], cfg: &WalkForwardConfig, optimizer: &Optimizer,
+) -> Result<Vec<WalkForwardWindow>> {
+    let (start_ts, end_ts) = span(data)?;
+    let mut windows = Vec::new();
+    for w in cfg.windows(start_ts, end_ts)? {
+        let train = slice(data, w.train_start, w.train_end);
+        let test = slice(data, w.train_end, w.test_end);
+        if train.is_empty() || test.is_empty() {
+            continue;
         }
-        let tmp_path = tmp.path().to_path_buf();
+        let best = optimizer.optimize(train)?.best;
+        let report = optimizer.evaluate(&best.params, test)?;
+        windows.push(WalkForwardWindow {
+            train_start: w.train_start,
+            train_end: w.train_end,
+            test_end: w.test_end,
+            params: best.params,
+            in_sample_score: best.score,
+            report,
+        });
+    }
+    Ok(windows)
+}
 
-        // Build a fresh backtester similar to simple_backtest but capture the report
-        let provider: Box<dyn HistoricalDataProvider> = match sim_mode {
-            | SimMode::Bar => Box::new(CSVHistoricalDataProvider::new()),
-            | SimMode::Tick => Box::new(CSVTicksProvider::new()),
-        };
-        let strategies: Vec<Box<dyn TradingStrategy>> = vec![Box::new(MeanReversionStrategy::new(
-            "UNK/UNK",
-            TimeFrame::OneHour,
-            20,
-            2.0,
-            2.0,
-            1.0,
-        ))];
-        let mut bt = Backtester {
-            risk_rules: vec![
-                Box::new(crate::risk::StopLossRule::new(0.05)),
-                Box::new(crate::risk::TakeProfitRule::new(0.10)),
-            ],
-            data_provider: provider,
-            timeframe: timeframe.to_string(),
-            starting_balance: 10_000.0,
-            strategies,
-            cache: None,
-            sim_mode,
-            slippage_bps: 0,
-            fee_bps: 8,
-            persistence: Some(Arc::new(persistence::NullPersistence)),
+/// Purged, embargoed k-fold cross-validation. Test folds are contiguous blocks with an equal
+/// number of bars. Training uses every other bar except those within `purge_secs` before a
+/// test fold (their outcomes overlap it) and within `embargo_secs` after it (serial
+/// correlation would leak the test period back into training).
+#[derive(Debug, Clone, Copy)]
+pub struct PurgedKFoldConfig {
+    pub folds: usize,
+    pub purge_secs: i64,
+    pub embargo_secs: i64,
+}
+
+/// One held-out block and the out-of-sample report of the parameters chosen without it
+#[derive(Debug, Clone)]
+pub struct FoldResult {
+    /// Timestamp of the first bar in the block
+    pub test_start: i64,
+    /// Timestamp of the last bar in the block
+    pub test_end: i64,
+    pub params: serde_json::Value,
+    pub in_sample_score: f64,
+    pub report: BacktestReport,
+}
+
+/// `n` contiguous index ranges of (nearly) equal length covering `0..len`
+fn group_ranges(len: usize, n: usize) -> Vec<Range<usize>> {
+    (0..n).map(|i| (i * len / n)..((i + 1) * len / n)).collect()
+}
+
+/// Bars outside every `test` range, minus the purge and embargo zones around them
+fn purged_train(
+    data: &[MarketData], test: &[Range<usize>], purge_secs: i64, embargo_secs: i64,
+) -> Vec<MarketData> {
+    let bounds: Vec<(i64, i64)> =
+        test.iter().map(|r| (data[r.start].timestamp, data[r.end - 1].timestamp)).collect();
+    data.iter()
+        .filter(|d| {
+            bounds
+                .iter()
+                .all(|&(s, e)| d.timestamp < s - purge_secs || d.timestamp > e + embargo_secs)
+        })
+        .cloned()
+        .collect()
+}
+
+fn check_groups(data: &[MarketData], groups: usize) -> Result<()> {
+    if groups < 2 {
+        return Err(Error::InvalidArgument("cross-validation needs at least 2 folds".into()));
+    }
+    if data.len() < groups {
+        return Err(Error::DataError(format!("{} bars cannot form {groups} folds", data.len())));
+    }
+    Ok(())
+}
+
+/// Purged k-fold: for every fold, optimise on the purged remainder and backtest the winner on
+/// the fold
+pub fn run_purged_kfold(
+    data: &[MarketData], cfg: &PurgedKFoldConfig, optimizer: &Optimizer,
+) -> Result<Vec<FoldResult>> {
+    check_groups(data, cfg.folds)?;
+    let mut results = Vec::new();
+    for fold in group_ranges(data.len(), cfg.folds) {
+        let test = std::slice::from_ref(&fold);
+        let train = purged_train(data, test, cfg.purge_secs, cfg.embargo_secs);
+        if train.is_empty() {
+            continue;
+        }
+        let best = optimizer.optimize(&train)?.best;
+        let report = optimizer.evaluate(&best.params, &data[fold.clone()])?;
+        results.push(FoldResult {
+            test_start: data[fold.start].timestamp,
+            test_end: data[fold.end - 1].timestamp,
+            params: best.params,
+            in_sample_score: best.score,
+            report,
+        });
+    }
+    Ok(results)
+}
+
+/// Combinatorial purged cross-validation: the data is cut into `groups` blocks and every
+/// combination of `test_groups` blocks is held out once, which yields
+/// `C(groups, test_groups) * test_groups / groups` complete out-of-sample paths.
+#[derive(Debug, Clone, Copy)]
+pub struct CpcvConfig {
+    pub groups: usize,
+    pub test_groups: usize,
+    pub purge_secs: i64,
+    pub embargo_secs: i64,
+}
+
+/// One train/test split of CPCV
+#[derive(Debug, Clone)]
+pub struct CpcvSplit {
+    /// Held-out block indices, ascending
+    pub test_groups: Vec<usize>,
+    pub params: serde_json::Value,
+    pub in_sample_score: f64,
+    /// Out-of-sample report per held-out block, in `test_groups` order
+    pub reports: Vec<BacktestReport>,
+}
+
+/// Out-of-sample path stitched together from one test result per block
+#[derive(Debug, Clone)]
+pub struct CpcvPath {
+    /// Bar returns of every block, in time order
+    pub returns: Vec<f64>,
+    pub total_return: f64,
+    pub sharpe: f64,
+}
+
+#[derive(Debug, Clone)]
+pub struct CpcvResult {
+    pub splits: Vec<CpcvSplit>,
+    pub paths: Vec<CpcvPath>,
+}
+
+/// All `k`-element subsets of `0..n` in lexicographic order
+pub(crate) fn combinations(n: usize, k: usize) -> Vec<Vec<usize>> {
+    let mut out = Vec::new();
+    let mut combo: Vec<usize> = (0..k).collect();
+    loop {
+        out.push(combo.clone());
+        // rightmost position that can still move up
+        let Some(i) = (0..k).rev().find(|&i| combo[i] < n - k + i) else {
+            return out;
         };
-        let rpt = bt.run(tmp_path.as_path()).await?;
-        reports.push(rpt);
-        window_start += step_secs;
+        combo[i] += 1;
+        for j in i + 1..k {
+            combo[j] = combo[j - 1] + 1;
+        }
+    }
+}
+
+/// Combinatorial purged CV: optimise on every purged training set, backtest the winner on each
+/// held-out block and assemble the distribution of out-of-sample paths.
+pub fn run_cpcv(
+    data: &[MarketData], cfg: &CpcvConfig, optimizer: &Optimizer,
+) -> Result<CpcvResult> {
+    check_groups(data, cfg.groups)?;
+    if cfg.test_groups == 0 || cfg.test_groups >= cfg.groups {
+        return Err(Error::InvalidArgument("test_groups must be in 1..groups".into()));
+    }
+    let ranges = group_ranges(data.len(), cfg.groups);
+    let mut splits = Vec::new();
+    for test_groups in combinations(cfg.groups, cfg.test_groups) {
+        let test: Vec<Range<usize>> = test_groups.iter().map(|&g| ranges[g].clone()).collect();
+        let train = purged_train(data, &test, cfg.purge_secs, cfg.embargo_secs);
+        if train.is_empty() {
+            let msg = format!("split {test_groups:?} leaves no training data");
+            return Err(Error::DataError(msg));
+        }
+        let best = optimizer.optimize(&train)?.best;
+        let reports = test
+            .iter()
+            .map(|r| optimizer.evaluate(&best.params, &data[r.clone()]))
+            .collect::<Result<Vec<_>>>()?;
+        splits.push(CpcvSplit {
+            test_groups,
+            params: best.params,
+            in_sample_score: best.score,
+            reports,
+        });
+    }
+    let paths = assemble_paths(&splits, cfg.groups);
+    Ok(CpcvResult { splits, paths })
+}
+
+/// Path `p` takes, for every block, the report of the `p`-th split that held the block out
+fn assemble_paths(splits: &[CpcvSplit], groups: usize) -> Vec<CpcvPath> {
+    let mut by_group: Vec<Vec<&BacktestReport>> = vec![Vec::new(); groups];
+    for split in splits {
+        for (g, report) in split.test_groups.iter().zip(&split.reports) {
+            by_group[*g].push(report);
+        }
+    }
+    let n_paths = by_group.iter().map(Vec::len).min().unwrap_or(0);
+    (0..n_paths)
+        .map(|p| {
+            let returns: Vec<f64> =
+                by_group.iter().flat_map(|reports| reports[p].returns.iter().copied()).collect();
+            let total_return = returns.iter().fold(1.0, |acc, r| acc * (1.0 + r)) - 1.0;
+            CpcvPath { sharpe: sharpe(&returns), total_return, returns }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::types::TradingPair;
+
+    fn bars(n: i64) -> Vec<MarketData> {
+        (0..n)
+            .map(|i| MarketData {
+                symbol: "SOL/USDC".into(),
+                pair: TradingPair::new("SOL", "USDC"),
+                timestamp: i * 3_600,
+                close: 100.0,
+                last_price: 100.0,
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn anchored_windows_keep_their_start() {
+        let day = 86_400;
+        let cfg =
+            WalkForwardConfig { train_days: 2, test_days: 1, step_days: 1, ..Default::default() };
+        let rolling = cfg.windows(0, 5 * day).unwrap();
+        let anchored =
+            WalkForwardConfig { mode: WindowMode::Anchored, ..cfg }.windows(0, 5 * day).unwrap();
+        assert_eq!(rolling.len(), 3);
+        assert_eq!(anchored.len(), 3);
+        assert_eq!(rolling[2].train_start, 2 * day);
+        assert!(anchored.iter().all(|w| w.train_start == 0));
+        let test_ends: Vec<i64> = rolling.iter().map(|w| w.test_end).collect();
+        assert_eq!(test_ends, vec![3 * day, 4 * day, 5 * day]);
+    }
+
+    #[test]
+    fn purge_and_embargo_drop_neighbouring_bars() {
+        let data = bars(10);
+        // test on bars 4..6 (t = 4h, 5h), purge 2h before and embargo 1h after
+        let train = purged_train(&data, std::slice::from_ref(&(4..6)), 2 * 3_600, 3_600);
+        let kept: Vec<i64> = train.iter().map(|d| d.timestamp / 3_600).collect();
+        assert_eq!(kept, vec![0, 1, 7, 8, 9]);
+    }
+
+    #[test]
+    fn cpcv_path_count() {
+        assert_eq!(combinations(6, 2).len(), 15);
+        assert_eq!(combinations(4, 1), vec![vec![0], vec![1], vec![2], vec![3]]);
+        let report =
+            BacktestReport::from_ledger(1.0, 1.1, 0.1, vec![1.0, 1.1], vec![0, 1], Vec::new());
+        let splits: Vec<CpcvSplit> = combinations(6, 2)
+            .into_iter()
+            .map(|test_groups| CpcvSplit {
+                test_groups,
+                params: serde_json::Value::Null,
+                in_sample_score: 0.0,
+                reports: vec![report.clone(), report.clone()],
+            })
+            .collect();
+        let paths = assemble_paths(&splits, 6);
+        // C(6,2) * 2 / 6 = 5 paths of one return per block
+        assert_eq!(paths.len(), 5);
+        assert!(paths.iter().all(|p| p.returns.len() == 6));
     }
-    Ok(reports)
 }
@@ -4,33 +4,35 @@
 use crate::backtest::remote_provider::{
     BirdeyeProvider, CryptoCompareProvider, RemoteHistoricalDataProvider,
 };
+use crate::utils::types::MarketData;
 use crate::Result;
 
-/// Download candles for `symbol=BASE/QUOTE` and save to `output_csv`.
-///
-/// * `timeframe` – Accepted values map to CryptoCompare endpoints: "1d", "1h", otherwise minutes.
-/// * `limit` – Number of candles to request (CryptoCompare max 2000).
-pub async fn download_to_csv(
-    base: &str, quote: &str, timeframe: &str, limit: usize, output_csv: &std::path::Path,
-) -> Result<()> {
-    // Try Birdeye first if API key present, fallback to CryptoCompare
-    let mut candles = Vec::new();
+/// Fetch the last `limit` candles of `BASE/QUOTE`, from Birdeye when `BIRDEYE_API_KEY` is set
+/// and from CryptoCompare otherwise or when Birdeye fails.
+pub async fn fetch_candles(
+    base: &str, quote: &str, timeframe: &str, limit: usize,
+) -> Result<Vec<MarketData>> {
     if std::env::var("BIRDEYE_API_KEY").is_ok() {
         let birdeye = BirdeyeProvider::new();
         match birdeye.fetch(base, quote, timeframe, limit).await {
-            | Ok(c) if !c.is_empty() => {
-                candles = c;
-            }
+            | Ok(c) if !c.is_empty() => return Ok(c),
             | Err(e) => {
                 log::warn!("Birdeye fetch failed: {} – falling back to CryptoCompare", e);
             }
             | _ => {}
         }
     }
-    if candles.is_empty() {
-        let cc = CryptoCompareProvider::new();
-        candles = cc.fetch(base, quote, timeframe, limit).await?;
-    }
+    CryptoCompareProvider::new().fetch(base, quote, timeframe, limit).await
+}
+
+/// Download candles for `symbol=BASE/QUOTE` and save to `output_csv`.
+///
+/// * `timeframe` – Accepted values map to CryptoCompare endpoints: "1d", "1h", otherwise minutes.
+/// * `limit` – Number of candles to request (CryptoCompare max 2000).
+pub async fn download_to_csv(
+    base: &str, quote: &str, timeframe: &str, limit: usize, output_csv: &std::path::Path,
+) -> Result<()> {
+    let candles = fetch_candles(base, quote, timeframe, limit).await?;
 
     // Ensure parent dir exists
     if let Some(p) = output_csv.parent() {
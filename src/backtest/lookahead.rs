@@ -0,0 +1,167 @@
+//! Lookahead-bias detection.
+//!
+//! A strategy is replayed twice over the same bars with two fresh instances. In the first pass
+//! every bar carries the complete candle history of its symbol, future candles included; in the
+//! second the candles are truncated at the current bar. A strategy that only looks at the past
+//! emits the same signals in both passes, so every bar where they differ is reported.
+
+use std::collections::HashMap;
+
+use serde_json::{json, Value};
+
+use crate::strategies::{StrategyConfig, StrategyFactory, TradingStrategy};
+use crate::trading::{MarketData, Signal};
+use crate::utils::types::Candle;
+use crate::{Error, Result};
+
+/// Signals of one bar that differ between the two passes
+#[derive(Debug, Clone)]
+pub struct LookaheadMismatch {
+    pub timestamp: i64,
+    pub with_future: Vec<Signal>,
+    pub without_future: Vec<Signal>,
+}
+
+#[derive(Debug, Clone)]
+pub struct LookaheadReport {
+    pub strategy: String,
+    pub bars: usize,
+    /// Signals emitted over the truncated pass
+    pub signals: usize,
+    pub mismatches: Vec<LookaheadMismatch>,
+}
+
+impl LookaheadReport {
+    pub fn is_clean(&self) -> bool {
+        self.mismatches.is_empty()
+    }
+}
+
+fn close_enough(a: f64, b: f64) -> bool {
+    (a - b).abs() <= 1e-9 * a.abs().max(b.abs()).max(1.0)
+}
+
+/// Signal timestamps and metadata may come from the wall clock, so they are not compared
+fn same_signals(a: &[Signal], b: &[Signal]) -> bool {
+    a.len() == b.len()
+        && a.iter().zip(b).all(|(x, y)| {
+            x.symbol == y.symbol
+                && x.signal_type == y.signal_type
+                && x.order_type == y.order_type
+                && close_enough(x.price, y.price)
+                && close_enough(x.size, y.size)
+                && close_enough(x.confidence, y.confidence)
+        })
+}
+
+/// Replay `data` (sorted by time) through both instances of the same strategy
+pub async fn detect(
+    mut with_future: Box<dyn TradingStrategy>, mut without_future: Box<dyn TradingStrategy>,
+    data: &[MarketData],
+) -> LookaheadReport {
+    let mut history: HashMap<&str, Vec<Candle>> = HashMap::new();
+    for md in data {
+        history.entry(md.symbol.as_str()).or_default().push(md.candle());
+    }
+    let mut seen: HashMap<&str, usize> = HashMap::new();
+    let mut report = LookaheadReport {
+        strategy: with_future.name().to_string(),
+        bars: data.len(),
+        signals: 0,
+        mismatches: Vec::new(),
+    };
+    for md in data {
+        let all = &history[md.symbol.as_str()];
+        let n = seen.entry(md.symbol.as_str()).or_default();
+        *n += 1;
+
+        let full = MarketData { candles: all.clone(), ..md.clone() };
+        let truncated = MarketData { candles: all[..*n].to_vec(), ..md.clone() };
+        let a = with_future.generate_signals(&full).await;
+        let b = without_future.generate_signals(&truncated).await;
+        report.signals += b.len();
+        if !same_signals(&a, &b) {
+            report.mismatches.push(LookaheadMismatch {
+                timestamp: md.timestamp,
+                with_future: a,
+                without_future: b,
+            });
+        }
+    }
+    report
+}
+
+/// Strategy names [`check_all`] runs through the factory
+pub fn strategy_names() -> Vec<&'static str> {
+    StrategyFactory::available_strategies()
+        .iter()
+        .copied()
+        .filter(|n| *n != "meta") // alias of "ensemble"
+        .collect()
+}
+
+/// Factory params for `name` trading `symbol`, merged over `overrides`. Composite strategies
+/// are built from leaf strategies that trade `symbol` with their default params.
+pub fn default_params(name: &str, symbol: &str, overrides: &Value) -> Value {
+    let leaf = |n: &str| json!({ "name": n, "params": { "symbol": symbol } });
+    match name {
+        | "ensemble" | "meta" => {
+            json!([leaf("mean_reversion"), leaf("momentum"), leaf("trend_following")])
+        }
+        | "allocation" => json!({
+            "subs": [leaf("mean_reversion"), leaf("momentum")],
+            "weights": [0.5, 0.5],
+        }),
+        | _ => {
+            let mut params = json!({ "symbol": symbol });
+            if let (Some(p), Some(o)) = (params.as_object_mut(), overrides.as_object()) {
+                p.extend(o.clone());
+            }
+            params
+        }
+    }
+}
+
+fn build(name: &str, params: &Value) -> Result<Box<dyn TradingStrategy>> {
+    let cfg = StrategyConfig {
+        name: name.to_string(),
+        enabled: true,
+        params: params.clone(),
+        performance: None,
+    };
+    StrategyFactory::create_strategy(name, &cfg)
+        .map_err(|e| Error::StrategyError(format!("{name}: {e}")))
+}
+
+/// Check one factory strategy built from `params`
+pub async fn check(name: &str, params: &Value, data: &[MarketData]) -> Result<LookaheadReport> {
+    Ok(detect(build(name, params)?, build(name, params)?, data).await)
+}
+
+/// Check every factory strategy on `data`, trading the symbol of its first bar
+pub async fn check_all(data: &[MarketData]) -> Result<Vec<LookaheadReport>> {
+    let symbol = data
+        .first()
+        .map(|md| md.symbol.clone())
+        .ok_or_else(|| Error::DataError("empty dataset".into()))?;
+    let mut reports = Vec::new();
+    for name in strategy_names() {
+        reports.push(check(name, &default_params(name, &symbol, &Value::Null), data).await?);
+    }
+    Ok(reports)
+}
+
+/// Test helper: panics with the first differing bar when `name` peeks at future candles
+pub async fn assert_no_lookahead(name: &str, params: &Value, data: &[MarketData]) {
+    let report = check(name, params, data).await.expect("strategy builds");
+    if let Some(m) = report.mismatches.first() {
+        panic!(
+            "{name} uses future data: {} of {} bars differ, first at {} ({:?} vs {:?})",
+            report.mismatches.len(),
+            report.bars,
+            m.timestamp,
+            m.with_future,
+            m.without_future
+        );
+    }
+}
@@ -2,7 +2,7 @@
 
 use clap::ValueEnum;
 use serde::{Deserialize, Serialize}; // SimulatedTrade and BacktestReport
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 
 #[derive(Debug, Clone, Copy, ValueEnum)]
 pub enum SimMode {
@@ -10,16 +10,25 @@ pub enum SimMode {
     Tick,
 }
 
+/// Ledger label for positions closed by risk rules
+pub const RISK_EXIT: &str = "risk";
+
 // Backtester struct with portfolio simulation and basic reporting
 /// Result of a single simulated trade
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SimulatedTrade {
     pub timestamp: i64,
+    /// Strategy that originated the order ("risk" for risk-rule exits)
+    #[serde(default)]
+    pub strategy: String,
     pub symbol: String,
     pub side: crate::utils::types::OrderSide,
     pub qty: f64,
     pub price: f64,
     pub pnl: f64,
+    /// Trading fee paid in quote currency
+    #[serde(default)]
+    pub fee: f64,
 }
 
 /// Simple portfolio representation used during backtest
@@ -54,6 +63,7 @@ impl Portfolio {
     }
 
     pub fn update_on_sell(&mut self, symbol: &str, qty: f64, price: f64) -> f64 {
+        let qty = qty.min(self.held(symbol));
         let pnl = if let Some(pos) = self.positions.get_mut(symbol) {
             pos.update_on_sell(qty, price)
         } else {
@@ -64,6 +74,16 @@ impl Portfolio {
         pnl
     }
 
+    /// Long quantity held in `symbol`
+    pub fn held(&self, symbol: &str) -> f64 {
+        self.positions.get(symbol).map_or(0.0, |p| p.size.max(0.0))
+    }
+
+    /// Snapshot for portfolio-level pre-trade risk checks
+    pub fn risk_view<'a>(&'a self, prices: &'a HashMap<String, f64>) -> PortfolioView<'a> {
+        PortfolioView::new(self.cash, &self.positions, prices)
+    }
+
     pub fn equity(&self, prices: &HashMap<String, f64>) -> f64 {
         let mut eq = self.cash;
         for (sym, pos) in &self.positions {
@@ -90,6 +110,43 @@ pub struct BacktestReport {
     pub returns: Vec<f64>,
     /// Annualised Sharpe ratio (using simple return series)
     pub sharpe: f64,
+    /// Sortino ratio, same scaling as `sharpe`
+    #[serde(default)]
+    pub sortino: f64,
+    /// Annualised return divided by max drawdown
+    #[serde(default)]
+    pub calmar: f64,
+    /// Gross profit / gross loss of closing trades, net of entry and exit fees; `None` means
+    /// no losing trades
+    #[serde(default)]
+    pub profit_factor: Option<f64>,
+    /// Fraction of bars with an open position
+    #[serde(default)]
+    pub exposure_time: f64,
+    /// Mean seconds between opening and flattening a position
+    #[serde(default)]
+    pub avg_holding_secs: f64,
+    /// Bar timestamps matching `equity_curve`
+    #[serde(default)]
+    pub timestamps: Vec<i64>,
+    /// Drawdown from the running peak after each bar
+    #[serde(default)]
+    pub drawdown_series: Vec<f64>,
+    /// Bars spent below the running peak after each bar
+    #[serde(default)]
+    pub drawdown_duration: Vec<usize>,
+    #[serde(default)]
+    pub max_drawdown_duration: usize,
+    /// Every executed trade in order of execution
+    #[serde(default)]
+    pub trades: Vec<SimulatedTrade>,
+    #[serde(default)]
+    pub by_strategy: BTreeMap<String, report::Attribution>,
+    #[serde(default)]
+    pub by_symbol: BTreeMap<String, report::Attribution>,
+    /// Signals refused by the signal limits or the pre-trade risk engine
+    #[serde(default)]
+    pub rejected_signals: usize,
 }
 
 impl BacktestReport {
@@ -122,7 +179,6 @@ impl BacktestReport {
         Ok(())
     }
 
-    /// Export equity curve and summary metrics to CSV
     /// Export equity curve and summary metrics to CSV
     pub fn to_csv<P: AsRef<std::path::Path>>(&self, path: P) -> std::io::Result<()> {
         let mut wtr = csv::Writer::from_path(path)?;
@@ -147,6 +203,18 @@ impl BacktestReport {
             self.winning_trades as f64 / self.total_trades.max(1) as f64 * 100.0
         );
         println!("Sharpe Ratio  : {:.2}", self.sharpe);
+        println!("Sortino Ratio : {:.2}", self.sortino);
+        println!("Calmar Ratio  : {:.2}", self.calmar);
+        match self.profit_factor {
+            | Some(pf) => println!("Profit Factor : {:.2}", pf),
+            | None => println!("Profit Factor : {}", report::UNBOUNDED_PROFIT_FACTOR),
+        }
+        println!("Exposure      : {:.2}%", self.exposure_time * 100.0);
+        println!("Avg Holding   : {:.1}h", self.avg_holding_secs / 3600.0);
+        println!("Rejected Sigs : {}", self.rejected_signals);
+        for (name, a) in &self.by_strategy {
+            println!("  {:<20} trades {:>4}  net PnL {:>12.2}", name, a.trades, a.net_pnl());
+        }
         println!("===========================");
     }
 }
@@ -166,23 +234,182 @@ pub struct Backtester {
     pub persistence: Option<std::sync::Arc<dyn crate::persistence::Persistence + Send + Sync>>,
     /// Risk rules (stop-loss, take-profit, etc.)
     pub risk_rules: Vec<Box<dyn crate::risk::RiskRule>>,
+    /// Portfolio-level checks applied to every order before it reaches the book
+    pub pre_trade_risk: Option<PreTradeRiskEngine>,
     /// Simulation mode (bar vs tick)
     pub sim_mode: SimMode,
     /// Per-trade slippage expressed in basis points (100 bps = 1 %)
     pub slippage_bps: u16,
     /// Trading fee expressed in basis points (paid on notional)
     pub fee_bps: u16,
+    /// Decides when and at what price orders execute (default: instant fills)
+    pub fill_model: Box<dyn FillModel>,
+    /// Sizes every order like the live engine does; the strategy's `sig.size` is used when unset
+    pub position_sizer: Option<Box<dyn PositionSizer>>,
+    /// Splits large orders into child orders (off by default)
+    pub order_split: Option<OrderSplit>,
+    /// Order cap, open-trade and daily-loss limits checked before sizing (off by default)
+    pub limits: Option<SignalLimits>,
+}
+
+/// Live order splitting: orders above `threshold` units go out in pieces of at most `chunk`
+#[derive(Debug, Clone, Copy)]
+pub struct OrderSplit {
+    pub threshold: f64,
+    pub chunk: f64,
+}
+
+impl OrderSplit {
+    /// Child order sizes for an order of `qty` units
+    pub fn pieces(&self, qty: f64) -> Vec<f64> {
+        if qty <= self.threshold || self.chunk <= 0.0 {
+            return vec![qty];
+        }
+        let mut pieces = Vec::new();
+        let mut remaining = qty;
+        while remaining > 1e-12 {
+            let piece = remaining.min(self.chunk);
+            pieces.push(piece);
+            remaining -= piece;
+        }
+        pieces
+    }
+}
+
+/// Arbitrage legs are never split, matching the live engine
+fn is_arbitrage(strategy: &str) -> bool {
+    strategy.to_lowercase().contains("arbitrage")
 }
 
 impl Backtester {
+    /// Backtester with instant fills, no trading costs, risk rules, cache or persistence
+    pub fn new(
+        data_provider: Box<dyn HistoricalDataProvider>, timeframe: &str, starting_balance: f64,
+        strategies: Vec<Box<dyn crate::strategies::TradingStrategy>>,
+    ) -> Self {
+        Self {
+            data_provider,
+            timeframe: timeframe.to_string(),
+            starting_balance,
+            strategies,
+            cache: None,
+            persistence: None,
+            risk_rules: Vec::new(),
+            pre_trade_risk: None,
+            sim_mode: SimMode::Bar,
+            slippage_bps: 0,
+            fee_bps: 0,
+            fill_model: Box::new(InstantFillModel),
+            position_sizer: None,
+            order_split: None,
+            limits: None,
+        }
+    }
+
+    /// Backtester that trades like `TradingEngine` would with `config`: its enabled strategies,
+    /// starting balance, position sizer, signal limits, exit rules, portfolio limits, slippage,
+    /// paper-trading fee and order splitting, filled by the `[backtest.fill_model]` model.
+    pub fn from_config(
+        config: &crate::config::Config, data_provider: Box<dyn HistoricalDataProvider>,
+        timeframe: &str,
+    ) -> Result<Self> {
+        let mut strategies = Vec::new();
+        for scfg in config.trading.strategies.iter().filter(|s| s.enabled) {
+            let strategy = crate::strategies::StrategyFactory::create_strategy(&scfg.name, scfg)
+                .map_err(|e| {
+                    crate::Error::StrategyError(format!("failed to init {}: {e}", scfg.name))
+                })?;
+            strategies.push(strategy);
+        }
+        if strategies.is_empty() {
+            return Err(crate::Error::ConfigError("no enabled strategies in config".into()));
+        }
+        let trading = &config.trading;
+        Ok(Self {
+            risk_rules: config.risk.build_rules(),
+            pre_trade_risk: config.risk.portfolio.clone().map(PreTradeRiskEngine::new),
+            slippage_bps: trading.slippage_bps,
+            fee_bps: trading.paper.fee_bps,
+            fill_model: config.backtest.fill_model.build(),
+            position_sizer: Some(config.risk.build_position_sizer()),
+            limits: Some(config.signal_limits()),
+            order_split: Some(OrderSplit {
+                threshold: trading.split_threshold_sol,
+                chunk: trading.split_chunk_sol,
+            }),
+            ..Self::new(data_provider, timeframe, trading.starting_balance_usd, strategies)
+        })
+    }
+
+    /// Report cache key: SHA-256 over the crate version, the market data and every setting
+    /// that can change the outcome (strategies and their params, costs, fill model, sizing,
+    /// limits and risk rules).
+    pub fn fingerprint(&self, market_data: &[MarketData]) -> String {
+        use sha2::{Digest, Sha256};
+        let strategies: Vec<serde_json::Value> = self
+            .strategies
+            .iter()
+            .map(|s| {
+                serde_json::json!({
+                    "name": s.name(),
+                    "timeframe": s.timeframe(),
+                    "symbols": s.symbols(),
+                    "params": s.params(),
+                })
+            })
+            .collect();
+        let settings = serde_json::json!({
+            "version": env!("CARGO_PKG_VERSION"),
+            "timeframe": self.timeframe,
+            "starting_balance": self.starting_balance,
+            "sim_mode": format!("{:?}", self.sim_mode),
+            "slippage_bps": self.slippage_bps,
+            "fee_bps": self.fee_bps,
+            "fill_model": self.fill_model.describe(),
+            "position_sizer": self.position_sizer.as_ref().map(|s| s.describe()),
+            "order_split": self.order_split.map(|s| format!("{s:?}")),
+            "limits": self.limits.map(|l| format!("{l:?}")),
+            "pre_trade_risk": self.pre_trade_risk.as_ref().map(|e| format!("{:?}", e.config())),
+            "risk_rules": self.risk_rules.iter().map(|r| r.describe()).collect::<Vec<_>>(),
+            "strategies": strategies,
+        });
+        let mut hasher = Sha256::new();
+        hasher.update(settings.to_string().as_bytes());
+        for md in market_data {
+            match bincode::serialize(md) {
+                | Ok(bytes) => hasher.update(&bytes),
+                | Err(_) => hasher.update(format!("{md:?}").as_bytes()),
+            }
+        }
+        format!("{:x}", hasher.finalize())
+    }
+
     pub async fn run(&mut self, data_file: &std::path::Path) -> Result<BacktestReport> {
+        self.run_many(&[data_file.to_path_buf()]).await
+    }
+
+    /// Backtest over several files (typically one per pair), replaying their events
+    /// interleaved by timestamp against a single portfolio.
+    pub async fn run_many(&mut self, data_files: &[PathBuf]) -> Result<BacktestReport> {
+        let market_data = self.data_provider.load_many(data_files)?;
+        self.run_with_data(&market_data).await
+    }
+
+    /// Backtest over already loaded data, which must be sorted by timestamp.
+    /// The data provider is not consulted.
+    pub async fn run_with_data(&mut self, market_data: &[MarketData]) -> Result<BacktestReport> {
         // determine date range for caching
-        let market_data = self.data_provider.load(data_file)?;
         if market_data.is_empty() {
             return Err(crate::Error::DataError("No market data loaded".to_string()));
         }
         let start_ts = market_data.first().unwrap().timestamp;
         let end_ts = market_data.last().unwrap().timestamp;
+        let fingerprint = self.cache.as_ref().map(|_| self.fingerprint(market_data));
+
+        // every pair present in the data, used for routing and the cache key
+        let known_symbols: std::collections::BTreeSet<String> =
+            market_data.iter().map(|d| d.pair.to_string()).collect();
+        let symbol_key = known_symbols.iter().cloned().collect::<Vec<_>>().join(",");
 
         // strategy name concat if single strategy else "multi"
         let strat_key = if self.strategies.len() == 1 {
@@ -190,187 +417,206 @@ impl Backtester {
         } else {
             "multi".to_string()
         };
-        if let Some(cache) = &self.cache {
-            if let Some(cached) = cache.get(&strat_key, "UNK", &self.timeframe, start_ts, end_ts)? {
-                return Ok(cached);
+        if let (Some(cache), Some(key)) = (&self.cache, &fingerprint) {
+            if let Some(entry) = cache.get(key)? {
+                return Ok(entry.report);
             }
         }
 
-        // use loaded data for simulation
-        let market_data = self.data_provider.load(data_file)?;
-        if market_data.is_empty() {
-            return Err(crate::Error::DataError("No market data loaded".to_string()));
-        }
-
         // Prepare portfolio and event queue
-        let mut portfolio = Portfolio::new(self.starting_balance);
-        let mut prices: HashMap<String, f64> = HashMap::new();
-        let mut queue = crate::backtest::event::EventQueue::new();
-        use crate::backtest::event::BacktestEvent;
+        let mut sim = SimState {
+            portfolio: Portfolio::new(self.starting_balance),
+            prices: HashMap::new(),
+            queue: EventQueue::new(),
+            book: SimBook::new(),
+            bar_counts: HashMap::new(),
+            known_symbols,
+            rejected_signals: 0,
+            day_open_equity: self.starting_balance,
+            end_ts,
+        };
+        let mut timers = TimerBook::default();
 
         // prime queue with historical market data
         for dp in market_data {
-            queue.push(BacktestEvent::Market(dp));
+            sim.queue.push(BacktestEvent::Market(dp));
+        }
+        for (idx, strategy) in self.strategies.iter_mut().enumerate() {
+            let mut ctx = StrategyContext::new(start_ts);
+            strategy.on_start(&mut ctx);
+            sim.schedule(&mut timers, idx, &mut ctx);
         }
 
         // metrics
-        let mut total_trades = 0usize;
-        let mut winning_trades = 0usize;
-        let mut peak_equity = self.starting_balance;
-        let mut max_drawdown = 0.0;
         let mut equity_curve: Vec<f64> = Vec::new();
+        let mut timestamps: Vec<i64> = Vec::new();
+        let mut ledger: Vec<SimulatedTrade> = Vec::new();
+        // daily loss is measured from the equity at the first bar of each UTC day
+        let mut day = None;
 
-        while let Some(evt) = queue.pop() {
+        while let Some(evt) = sim.queue.pop() {
             match evt {
                 | BacktestEvent::Market(data_point) => {
                     // update last price
-                    prices.insert(data_point.pair.to_string(), data_point.last_price);
+                    sim.prices.insert(data_point.pair.to_string(), data_point.last_price);
+
+                    let data_symbol = data_point.pair.to_string();
+                    let today = data_point.timestamp.div_euclid(86_400);
+                    if day != Some(today) {
+                        day = Some(today);
+                        sim.day_open_equity = sim.portfolio.equity(&sim.prices);
+                    }
+                    // Bars share the data's clock; ticks would give returns over unequal spans
+                    if let (Some(engine), SimMode::Bar) =
+                        (self.pre_trade_risk.as_mut(), self.sim_mode)
+                    {
+                        engine.observe_price(&data_symbol, data_point.last_price);
+                    }
+                    let bar_index = {
+                        let seen = sim.bar_counts.entry(data_symbol.clone()).or_insert(0);
+                        *seen += 1;
+                        *seen - 1
+                    };
+
+                    // resting orders get the first look at the new bar
+                    let model = self.fill_model.as_ref();
+                    for (order, fill) in
+                        sim.book.match_bar(data_point, bar_index, model, self.slippage_bps)
+                    {
+                        let trade = order.to_trade(fill, data_point.timestamp);
+                        sim.queue.push(BacktestEvent::Trade(trade));
+                    }
 
                     // generate signals
-                    for strategy in &mut self.strategies {
-                        let signals =
-                            futures::executor::block_on(strategy.generate_signals(&data_point));
-                        for sig in signals {
-                            // convert to trade later via slippage/fee; push Trade event
-                            let qty = sig.size;
-                            let raw_price = sig.price;
-                            let exec_price = match sig.signal_type {
-                                | crate::trading::SignalType::Buy => {
-                                    raw_price * (1.0 + (self.slippage_bps as f64) / 10_000.0)
-                                }
-                                | crate::trading::SignalType::Sell => {
-                                    raw_price * (1.0 - (self.slippage_bps as f64) / 10_000.0)
-                                }
-                                | _ => raw_price,
-                            };
-                            use crate::trading::SignalType;
-                            let side = match sig.signal_type {
-                                | SignalType::Buy => crate::utils::types::OrderSide::Buy,
-                                | SignalType::Sell => crate::utils::types::OrderSide::Sell,
-                                | _ => continue,
-                            };
-                            let trade_evt = BacktestEvent::Trade(SimulatedTrade {
-                                timestamp: sig.timestamp,
-                                symbol: sig.symbol.clone(),
-                                side,
-                                qty,
-                                price: exec_price,
-                                pnl: 0.0,
-                            });
-                            queue.push(trade_evt);
+                    let pairs_in_run = sim.known_symbols.len();
+                    for idx in 0..self.strategies.len() {
+                        let strategy = &mut self.strategies[idx];
+                        if !routes_to(&strategy.symbols(), &data_symbol, pairs_in_run) {
+                            continue;
                         }
+                        let signals = strategy.generate_signals(data_point).await;
+                        let name = strategy.name().to_string();
+                        let now = data_point.timestamp;
+                        self.submit_signals(&mut sim, &name, signals, now, Some(data_point)).await;
                     }
 
                     // ---------- Risk rule evaluation ----------
-                    let positions_snapshot: std::collections::HashMap<
-                        String,
-                        crate::portfolio::Position,
-                    > = portfolio.positions.clone();
-                    for (sym, pos) in positions_snapshot {
-                        if pos.size <= 0.0 {
-                            continue;
-                        }
-                        if let Some(price) = prices.get(&sym) {
-                            for rule in &self.risk_rules {
-                                if let Some(RiskAction::ClosePosition) =
-                                    rule.evaluate(&sym, &pos, *price)
-                                {
-                                    // push a trade event to close the position
-                                    let close_evt = BacktestEvent::Trade(SimulatedTrade {
-                                        timestamp: data_point.timestamp,
-                                        symbol: sym.clone(),
-                                        side: crate::utils::types::OrderSide::Sell,
-                                        qty: pos.size,
-                                        price: *price,
-                                        pnl: 0.0,
-                                    });
-                                    queue.push(close_evt);
-                                    break;
-                                }
+                    // only the symbol that just printed has a new price; evaluating the others
+                    // again would queue duplicate exits for the same instant
+                    let open = sim.portfolio.positions.get(&data_symbol).filter(|p| p.size > 0.0);
+                    if let Some(pos) = open.cloned() {
+                        let (price, now) = (data_point.last_price, data_point.timestamp);
+                        for rule in self.risk_rules.iter_mut() {
+                            let action = rule.evaluate(&data_symbol, &pos, price, now);
+                            if let Some(qty) = action.and_then(|a| a.exit_qty(pos.size)) {
+                                // close (part of) the position at this bar's price
+                                sim.queue.push(BacktestEvent::Trade(SimulatedTrade {
+                                    timestamp: now,
+                                    strategy: RISK_EXIT.to_string(),
+                                    symbol: data_symbol.clone(),
+                                    side: crate::utils::types::OrderSide::Sell,
+                                    qty,
+                                    price,
+                                    pnl: 0.0,
+                                    fee: 0.0,
+                                }));
+                                break;
                             }
                         }
                     }
 
-                    // update equity/drawdown after processing bar
-                    let equity = portfolio.equity(&prices);
-                    if equity > peak_equity {
-                        peak_equity = equity;
-                    }
-                    let drawdown = (peak_equity - equity) / peak_equity;
-                    if drawdown > max_drawdown {
-                        max_drawdown = drawdown;
-                    }
-                    equity_curve.push(equity);
+                    // record equity after processing bar
+                    equity_curve.push(sim.portfolio.equity(&sim.prices));
+                    timestamps.push(data_point.timestamp);
                 }
                 | BacktestEvent::Trade(mut trade) => {
+                    // a strategy exit and a risk exit on the same bar are both sized from the
+                    // pre-bar position; only what is still held can be sold
+                    if trade.side == crate::utils::types::OrderSide::Sell {
+                        trade.qty = trade.qty.min(sim.portfolio.held(&trade.symbol));
+                        if trade.qty <= 1e-12 {
+                            continue;
+                        }
+                    }
                     let notional = trade.qty * trade.price;
                     let fee = notional * (self.fee_bps as f64) / 10_000.0;
-                    let before_pnl = portfolio.realized_pnl;
-                    portfolio.apply_trade(&trade);
-                    portfolio.cash -= fee;
-                    let realized = portfolio.realized_pnl - before_pnl;
-                    trade.pnl = realized;
-                    if realized > 0.0 {
-                        winning_trades += 1;
+                    let before_pnl = sim.portfolio.realized_pnl;
+                    sim.portfolio.apply_trade(&trade);
+                    sim.portfolio.cash -= fee;
+                    trade.pnl = sim.portfolio.realized_pnl - before_pnl;
+                    trade.fee = fee;
+                    let position =
+                        sim.portfolio.positions.get(&trade.symbol).cloned().unwrap_or_default();
+                    if position.size <= 1e-12 {
+                        for rule in self.risk_rules.iter_mut() {
+                            rule.on_position_closed(&trade.symbol);
+                        }
                     }
-                    total_trades += 1;
-                }
-            }
-        }
-
-        // ---------- Risk rule evaluation (after full run) ----------
-        for (sym, pos) in portfolio.positions.clone() {
-            if pos.size <= 0.0 {
-                continue;
-            }
-            if let Some(price) = prices.get(&sym) {
-                for rule in &self.risk_rules {
-                    if let Some(RiskAction::ClosePosition) = rule.evaluate(&sym, &pos, *price) {
-                        let pnl = portfolio.update_on_sell(&sym, pos.size, *price);
-                        total_trades += 1;
-                        if pnl > 0.0 {
-                            winning_trades += 1;
+                    let pairs_in_run = sim.known_symbols.len();
+                    for (idx, strategy) in self.strategies.iter_mut().enumerate() {
+                        if routes_to(&strategy.symbols(), &trade.symbol, pairs_in_run) {
+                            let mut ctx = StrategyContext::new(trade.timestamp);
+                            strategy.on_position_update(&trade.symbol, &position, &mut ctx);
+                            sim.schedule(&mut timers, idx, &mut ctx);
                         }
-                        break;
                     }
+                    ledger.push(trade);
+                }
+                | BacktestEvent::Timer { timestamp, strategy, id } => {
+                    // cancelled or re-set timers leave stale entries behind
+                    let Some(next) = timers.fire(strategy, id, timestamp) else {
+                        continue;
+                    };
+                    if let Some(at) = next.filter(|at| *at <= end_ts) {
+                        sim.queue.push(BacktestEvent::Timer { timestamp: at, strategy, id });
+                    }
+                    let mut ctx = StrategyContext::new(timestamp);
+                    let signals = self.strategies[strategy].on_timer(id, &mut ctx);
+                    sim.schedule(&mut timers, strategy, &mut ctx);
+                    let name = self.strategies[strategy].name().to_string();
+                    self.submit_signals(&mut sim, &name, signals, timestamp, None).await;
                 }
             }
         }
 
-        // compute returns & sharpe
-        let mut returns: Vec<f64> = Vec::new();
-        for w in equity_curve.windows(2) {
-            let prev = w[0];
-            let curr = w[1];
-            if prev > 0.0 {
-                returns.push((curr - prev) / prev);
-            }
+        for strategy in self.strategies.iter_mut() {
+            strategy.on_stop(&mut StrategyContext::new(end_ts));
+        }
+        let SimState { portfolio, prices, book, rejected_signals, .. } = sim;
+
+        if !book.is_empty() {
+            log::debug!("{} orders left unfilled at the end of the backtest", book.len());
+        }
+        if book.expired() > 0 {
+            log::debug!("{} limit orders expired unfilled", book.expired());
         }
-        let mean_ret = returns.iter().copied().sum::<f64>() / returns.len().max(1) as f64;
-        let var = returns.iter().map(|r| (r - mean_ret).powi(2)).sum::<f64>()
-            / returns.len().max(1) as f64;
-        let sd = var.sqrt();
-        let sharpe = if sd > 0.0 {
-            mean_ret / sd * (returns.len() as f64).sqrt()
-        } else {
-            0.0
-        };
 
         let ending_balance = portfolio.equity(&prices);
-        let report = BacktestReport {
-            starting_balance: self.starting_balance,
+        let mut report = BacktestReport::from_ledger(
+            self.starting_balance,
             ending_balance,
-            realized_pnl: portfolio.realized_pnl,
-            max_drawdown,
-            total_trades,
-            winning_trades,
+            portfolio.realized_pnl,
             equity_curve,
-            returns,
-            sharpe,
-        };
+            timestamps,
+            ledger,
+        );
+        report.rejected_signals = rejected_signals;
         // store in cache
-        if let Some(cache) = &self.cache {
-            let _ = cache.insert(&strat_key, "UNK", &self.timeframe, start_ts, end_ts, &report);
+        if let (Some(cache), Some(key)) = (&self.cache, fingerprint) {
+            let entry = crate::backtest::cache::CacheEntry {
+                key,
+                created_at: chrono::Utc::now().timestamp(),
+                version: env!("CARGO_PKG_VERSION").to_string(),
+                strategy: strat_key.clone(),
+                symbols: symbol_key.clone(),
+                timeframe: self.timeframe.clone(),
+                start_ts,
+                end_ts,
+                report: report.clone(),
+            };
+            if let Err(e) = cache.insert(&entry) {
+                log::warn!("Failed to cache backtest report: {e}");
+            }
         }
         // Persist summary if configured
         if let Some(p) = &self.persistence {
@@ -380,17 +626,150 @@ impl Backtester {
                 timeframe: self.timeframe.clone(),
                 start_balance: self.starting_balance,
                 end_balance: ending_balance,
-                sharpe,
-                max_drawdown,
+                sharpe: report.sharpe,
+                max_drawdown: report.max_drawdown,
             };
             let _ = p.save_backtest(&summary).await;
         }
         Ok(report)
     }
+
+    /// Turn the signals `strategy` emitted at the current simulated time into orders, applying
+    /// the signal limits, sizer, pre-trade risk and order splitting. `bar` is the bar that
+    /// produced them; signals from timers have none and never fill on the spot.
+    async fn submit_signals(
+        &self, sim: &mut SimState<'_>, strategy: &str, signals: Vec<Signal>, now: i64,
+        bar: Option<&MarketData>,
+    ) {
+        let latency = self.fill_model.latency_bars();
+        for sig in signals {
+            use crate::trading::SignalType;
+            let side = match sig.signal_type {
+                | SignalType::Buy => crate::utils::types::OrderSide::Buy,
+                | SignalType::Sell => crate::utils::types::OrderSide::Sell,
+                | _ => continue,
+            };
+            if let Some(limits) = &self.limits {
+                let open = sim.portfolio.positions.values().filter(|p| p.size > 1e-12).count();
+                let loss = (sim.day_open_equity - sim.portfolio.equity(&sim.prices)).max(0.0);
+                let refusal = limits.check(side, open, loss, sim.day_open_equity);
+                if let Some(reason) = refusal {
+                    log::debug!("{} signal refused: {:?}", strategy, reason);
+                    sim.rejected_signals += 1;
+                    continue;
+                }
+            }
+            // symbol-agnostic strategies trade whatever pair fed them
+            let symbol = match bar {
+                | Some(bar) if !sim.known_symbols.contains(&sig.symbol) => bar.pair.to_string(),
+                | _ => sig.symbol.clone(),
+            };
+            let mut qty = match &self.position_sizer {
+                // the live engine sizes from cash and the base token
+                | Some(sizer) => {
+                    let base = symbol.split('/').next().unwrap_or(&symbol);
+                    sizer.size(sim.portfolio.cash, base).await
+                }
+                | None => sig.size,
+            };
+            if let Some(limits) = &self.limits {
+                qty = limits.cap(qty);
+            }
+            if qty <= 0.0 {
+                continue;
+            }
+            if let Some(engine) = &self.pre_trade_risk {
+                let price = sim.prices.get(&symbol).copied().unwrap_or(sig.price);
+                let intent = OrderIntent { symbol: &symbol, side, qty, price };
+                let (allowed, decision) =
+                    engine.approve(&intent, &sim.portfolio.risk_view(&sim.prices));
+                if let Some(d) = decision {
+                    log::debug!(
+                        "{} {:?} {qty} {symbol}: {:?} ({:?})",
+                        strategy,
+                        sig.signal_type,
+                        d.action,
+                        d.limit
+                    );
+                }
+                qty = allowed;
+                if qty <= 0.0 {
+                    sim.rejected_signals += 1;
+                    continue;
+                }
+            }
+            let seen = sim.bar_counts.get(&symbol).copied().unwrap_or(0);
+            let pieces = match self.order_split {
+                | Some(split) if !is_arbitrage(strategy) => split.pieces(qty),
+                | _ => vec![qty],
+            };
+            for piece in pieces {
+                let mut order = SimOrder {
+                    id: 0,
+                    strategy: strategy.to_string(),
+                    symbol: symbol.clone(),
+                    side,
+                    order_type: sig.order_type,
+                    qty: piece,
+                    limit_price: sig.limit_price,
+                    stop_price: sig.stop_price,
+                    ref_price: sig.price,
+                    submitted_at: now,
+                    // `seen` is already the index of the symbol's next bar
+                    eligible_from: seen + latency.saturating_sub(1),
+                    triggered: false,
+                };
+                // without latency an order may execute on its own bar
+                if let Some(bar) = bar.filter(|b| latency == 0 && b.pair.to_string() == symbol) {
+                    if let Some(fill) = self.fill_model.try_fill(&mut order, bar, self.slippage_bps)
+                    {
+                        order.qty -= fill.qty;
+                        let trade = order.to_trade(fill, now);
+                        sim.queue.push(BacktestEvent::Trade(trade));
+                    }
+                }
+                if order.qty > 1e-12 {
+                    sim.book.submit(order);
+                }
+            }
+        }
+    }
 }
 
-use crate::risk::{RiskAction, RiskRule};
+/// Mutable state of one simulation run over data borrowed for `'a`
+struct SimState<'a> {
+    portfolio: Portfolio,
+    prices: HashMap<String, f64>,
+    queue: EventQueue<'a>,
+    book: SimBook,
+    /// Bars seen per symbol; order latency is counted in that symbol's bars
+    bar_counts: HashMap<String, usize>,
+    /// Every pair present in the data
+    known_symbols: BTreeSet<String>,
+    rejected_signals: usize,
+    day_open_equity: f64,
+    end_ts: i64,
+}
+
+impl SimState<'_> {
+    /// Queue the timers `strategy` requested through `ctx`; timers after the last bar never fire
+    fn schedule(&mut self, timers: &mut TimerBook, strategy: usize, ctx: &mut StrategyContext) {
+        for (at, id) in timers.apply(strategy, ctx) {
+            if at <= self.end_ts {
+                self.queue.push(BacktestEvent::Timer { timestamp: at, strategy, id });
+            }
+        }
+    }
+}
+
+use crate::risk::portfolio_risk::{OrderIntent, PortfolioView, PreTradeRiskEngine};
+use crate::risk::position_sizer::PositionSizer;
+use crate::risk::{RiskRule, SignalLimits};
 use crate::Result;
+use crate::strategies::{StrategyContext, TimerBook};
+use crate::trading::Signal;
+use event::{BacktestEvent, EventQueue};
+use fill_model::{FillModel, InstantFillModel, SimBook, SimOrder};
 
 use crate::utils::types::MarketData;
 
@@ -399,60 +778,95 @@ use std::path::PathBuf;
 /// Trait for historical data providers
 pub trait HistoricalDataProvider: Send + Sync {
     fn load(&self, data_file: &std::path::Path) -> Result<Vec<MarketData>>;
+
+    /// Load several files and merge them into one stream ordered by timestamp.
+    /// Ties keep file order, so events at the same instant replay deterministically.
+    fn load_many(&self, data_files: &[PathBuf]) -> Result<Vec<MarketData>> {
+        let mut out = Vec::new();
+        for file in data_files {
+            out.extend(self.load(file)?);
+        }
+        out.sort_by_key(|d| d.timestamp);
+        Ok(out)
+    }
+
     fn box_clone(&self) -> Box<dyn HistoricalDataProvider>;
 }
 
+/// Whether a data point for `data_symbol` should be fed to a strategy trading `symbols`.
+/// Strategies without a concrete pair (`UNK/UNK` or none) see everything, as does every
+/// strategy when the run only contains one pair.
+fn routes_to(symbols: &[String], data_symbol: &str, pairs_in_run: usize) -> bool {
+    let wildcard = |s: &String| s == providers::UNKNOWN_SYMBOL;
+    pairs_in_run <= 1
+        || data_symbol == providers::UNKNOWN_SYMBOL
+        || symbols.is_empty()
+        || symbols.iter().any(|s| s.eq_ignore_ascii_case(data_symbol) || wildcard(s))
+}
+
 impl Clone for Box<dyn HistoricalDataProvider> {
     fn clone(&self) -> Self {
         self.box_clone()
     }
 }
 
+pub mod batch;
 pub mod cache;
 pub mod event;
+pub mod fill_model;
 pub mod harness;
 pub mod importer;
+pub mod lookahead;
+pub mod optimizer;
+pub mod overfitting;
 pub mod providers;
 pub mod remote_provider;
+pub mod report;
+pub mod robustness;
 pub mod tick_provider;
 
 /// Convenience helper used by CLI until full engine integration is ready
 use std::path::Path;
 
+/// CSV provider for `sim_mode`; rows without a symbol column get `pair` when given, otherwise
+/// the pair in the file name
+pub(crate) fn csv_provider(
+    sim_mode: SimMode, pair: Option<&crate::utils::types::TradingPair>,
+) -> Box<dyn HistoricalDataProvider> {
+    match (sim_mode, pair.cloned()) {
+        | (SimMode::Bar, None) => Box::new(providers::CSVHistoricalDataProvider::new()),
+        | (SimMode::Bar, Some(p)) => {
+            Box::new(providers::CSVHistoricalDataProvider::new().with_pair(p))
+        }
+        | (SimMode::Tick, None) => Box::new(tick_provider::CSVTicksProvider::new()),
+        | (SimMode::Tick, Some(p)) => Box::new(tick_provider::CSVTicksProvider::new().with_pair(p)),
+    }
+}
+
+/// Strategies, costs and risk settings come from `config` when given (see
+/// [`Backtester::from_config`]), otherwise a default mean-reversion setup is used. `pair` labels
+/// files without a symbol column whose name does not give the pair.
 pub async fn simple_backtest(
-    data_path: &Path, timeframe: &str, sim_mode: SimMode, output: Option<&Path>,
+    data_paths: &[PathBuf], pair: Option<&crate::utils::types::TradingPair>, timeframe: &str,
+    sim_mode: SimMode, config: Option<&crate::config::Config>, output: Option<&Path>,
 ) -> Result<()> {
-    use crate::strategies::{MeanReversionStrategy, TimeFrame, TradingStrategy};
     // 1. Provider
-    let provider: Box<dyn HistoricalDataProvider> = match sim_mode {
-        | SimMode::Bar => Box::new(crate::backtest::providers::CSVHistoricalDataProvider::new()),
-        | SimMode::Tick => Box::new(crate::backtest::tick_provider::CSVTicksProvider::new()),
-    };
+    let provider = csv_provider(sim_mode, pair);
     // 2. Build backtester
-    let strategies: Vec<Box<dyn TradingStrategy>> = vec![Box::new(MeanReversionStrategy::new(
-        "UNK/UNK",
-        TimeFrame::OneHour,
-        20,
-        2.0,
-        2.0,
-        1.0,
-    ))];
-    let mut bt = Backtester {
-        risk_rules: vec![
-            Box::new(crate::risk::StopLossRule::new(0.05)),
-            Box::new(crate::risk::TakeProfitRule::new(0.10)),
-        ],
-        data_provider: provider,
-        timeframe: timeframe.to_string(),
-        starting_balance: 10_000.0,
-        strategies,
-        cache: None,
-        persistence: Some(std::sync::Arc::new(crate::persistence::NullPersistence)),
-        sim_mode,
-        slippage_bps: 0,
-        fee_bps: 8, // 0.03 %
+    let mut bt = match config {
+        | Some(cfg) => Backtester {
+            persistence: Some(std::sync::Arc::new(crate::persistence::NullPersistence)),
+            sim_mode,
+            ..Backtester::from_config(cfg, provider, timeframe)?
+        },
+        | None => default_backtester(provider, timeframe, sim_mode),
     };
-    let rpt = bt.run(data_path).await?;
+    if std::env::var("BACKTEST_NO_CACHE").is_err() {
+        bt.cache = cache::BacktestCache::open_default()
+            .map_err(|e| log::warn!("Report cache unavailable: {e}"))
+            .ok();
+    }
+    let rpt = bt.run_many(data_paths).await?;
     if let Some(path) = output {
         if let Err(e) = rpt.to_csv(path) {
             log::error!("Failed to write CSV report: {e}");
@@ -466,9 +880,43 @@ pub async fn simple_backtest(
             } else {
                 log::info!("PNG chart written to {}", png_path.display());
             }
+            let json_path = png_path.with_extension("json");
+            match rpt.to_json(&json_path) {
+                | Ok(()) => log::info!("JSON report written to {}", json_path.display()),
+                | Err(e) => log::error!("Failed to write JSON report: {e}"),
+            }
+            let html_path = png_path.with_extension("html");
+            match rpt.to_html(&html_path) {
+                | Ok(()) => log::info!("HTML tearsheet written to {}", html_path.display()),
+                | Err(e) => log::error!("Failed to write HTML tearsheet: {e}"),
+            }
         }
     }
     rpt.print();
     Ok(())
 }
+/// Mean reversion on any pair with a 5% stop, 10% take-profit and 8 bps fees
+pub(crate) fn default_backtester(
+    provider: Box<dyn HistoricalDataProvider>, timeframe: &str, sim_mode: SimMode,
+) -> Backtester {
+    use crate::strategies::{MeanReversionStrategy, TimeFrame, TradingStrategy};
+    let strategies: Vec<Box<dyn TradingStrategy>> = vec![Box::new(MeanReversionStrategy::new(
+        "UNK/UNK",
+        TimeFrame::OneHour,
+        20,
+        2.0,
+        2.0,
+        1.0,
+    ))];
+    Backtester {
+        risk_rules: vec![
+            Box::new(crate::risk::StopLossRule::new(0.05)),
+            Box::new(crate::risk::TakeProfitRule::new(0.10)),
+        ],
+        persistence: Some(std::sync::Arc::new(crate::persistence::NullPersistence)),
+        sim_mode,
+        fee_bps: 8, // 0.03 %
+        ..Backtester::new(provider, timeframe, 10_000.0, strategies)
+    }
+}
 // TODO: Add result reporting and export utilities
@@ -0,0 +1,229 @@
+//! Selection-bias diagnostics for parameter sweeps and strategy comparisons.
+//!
+//! Picking the best of many backtests inflates its Sharpe ratio. The probabilistic Sharpe
+//! ratio (PSR) measures how likely a Sharpe is to beat a benchmark given the track length and
+//! the skew/kurtosis of the returns; the deflated Sharpe ratio (DSR) uses the Sharpe expected
+//! from the best of the trials as that benchmark. The probability of backtest overfitting (PBO)
+//! is estimated with combinatorially symmetric cross-validation (CSCV) on the trial returns.
+//!
+//! All Sharpe ratios here are per bar, i.e. not scaled by `sqrt(n)` like
+//! [`super::report::sharpe`].
+
+use serde::{Deserialize, Serialize};
+use statrs::distribution::{ContinuousCDF, Normal};
+
+use super::harness::combinations;
+use super::report::sharpe;
+use crate::{Error, Result};
+
+/// Euler–Mascheroni constant
+const EULER_GAMMA: f64 = 0.577_215_664_901_532_9;
+
+/// Default number of CSCV blocks
+pub const DEFAULT_CSCV_GROUPS: usize = 8;
+
+fn std_normal() -> Normal {
+    Normal::new(0.0, 1.0).expect("unit normal")
+}
+
+/// Mean, standard deviation, skewness and (non-excess) kurtosis
+fn moments(xs: &[f64]) -> (f64, f64, f64, f64) {
+    let n = xs.len() as f64;
+    let m = xs.iter().sum::<f64>() / n;
+    let central = |p: i32| xs.iter().map(|x| (x - m).powi(p)).sum::<f64>() / n;
+    let var = central(2);
+    let sd = var.sqrt();
+    if sd == 0.0 {
+        return (m, 0.0, 0.0, 3.0);
+    }
+    (m, sd, central(3) / sd.powi(3), central(4) / var.powi(2))
+}
+
+/// Per-bar Sharpe ratio
+pub fn bar_sharpe(returns: &[f64]) -> f64 {
+    if returns.is_empty() {
+        return 0.0;
+    }
+    sharpe(returns) / (returns.len() as f64).sqrt()
+}
+
+/// Probability that the true per-bar Sharpe of `returns` exceeds `benchmark`
+pub fn probabilistic_sharpe(returns: &[f64], benchmark: f64) -> f64 {
+    if returns.len() < 2 {
+        return 0.0;
+    }
+    let (_, sd, skew, kurt) = moments(returns);
+    if sd == 0.0 {
+        return 0.0;
+    }
+    let sr = bar_sharpe(returns);
+    let denom = 1.0 - skew * sr + (kurt - 1.0) / 4.0 * sr * sr;
+    if denom <= 0.0 {
+        return 0.0;
+    }
+    let z = (sr - benchmark) * ((returns.len() - 1) as f64).sqrt() / denom.sqrt();
+    std_normal().cdf(z)
+}
+
+/// Sharpe the best of `trial_sharpes` is expected to reach by luck alone
+pub fn expected_max_sharpe(trial_sharpes: &[f64]) -> f64 {
+    let n = trial_sharpes.len();
+    if n < 2 {
+        return 0.0;
+    }
+    let m = trial_sharpes.iter().sum::<f64>() / n as f64;
+    let var = trial_sharpes.iter().map(|s| (s - m).powi(2)).sum::<f64>() / (n - 1) as f64;
+    let normal = std_normal();
+    let n = n as f64;
+    var.sqrt()
+        * ((1.0 - EULER_GAMMA) * normal.inverse_cdf(1.0 - 1.0 / n)
+            + EULER_GAMMA * normal.inverse_cdf(1.0 - 1.0 / (n * std::f64::consts::E)))
+}
+
+/// PSR of `returns` against the expected maximum Sharpe of all `trial_sharpes` (per bar)
+pub fn deflated_sharpe(returns: &[f64], trial_sharpes: &[f64]) -> f64 {
+    probabilistic_sharpe(returns, expected_max_sharpe(trial_sharpes))
+}
+
+/// Outcome of CSCV
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PboEstimate {
+    /// Share of splits whose in-sample winner ranks in the bottom half out of sample
+    pub pbo: f64,
+    /// Logit of the out-of-sample relative rank of the in-sample winner, one per split
+    pub logits: Vec<f64>,
+}
+
+/// Probability of backtest overfitting. `trials` holds one return series per configuration,
+/// all aligned on the same bars; they are cut into `groups` blocks and every half of the
+/// blocks serves once as the in-sample set.
+pub fn probability_of_overfitting(trials: &[Vec<f64>], groups: usize) -> Result<PboEstimate> {
+    if trials.len() < 2 {
+        return Err(Error::InvalidArgument("PBO needs at least 2 trials".into()));
+    }
+    if groups < 2 || !groups.is_multiple_of(2) {
+        return Err(Error::InvalidArgument("CSCV needs an even number of groups".into()));
+    }
+    let len = trials[0].len();
+    if trials.iter().any(|t| t.len() != len) {
+        return Err(Error::InvalidArgument("trial return series differ in length".into()));
+    }
+    if len < groups {
+        return Err(Error::DataError(format!("{len} returns cannot form {groups} groups")));
+    }
+    let blocks: Vec<(usize, usize)> =
+        (0..groups).map(|g| (g * len / groups, (g + 1) * len / groups)).collect();
+    let pick = |t: &[f64], chosen: &[usize]| -> Vec<f64> {
+        chosen.iter().flat_map(|&g| t[blocks[g].0..blocks[g].1].iter().copied()).collect()
+    };
+
+    let n = trials.len() as f64;
+    let mut logits = Vec::new();
+    for in_sample in combinations(groups, groups / 2) {
+        let out_of_sample: Vec<usize> = (0..groups).filter(|g| !in_sample.contains(g)).collect();
+        let is: Vec<f64> = trials.iter().map(|t| sharpe(&pick(t, &in_sample))).collect();
+        let oos: Vec<f64> = trials.iter().map(|t| sharpe(&pick(t, &out_of_sample))).collect();
+        let best = (0..is.len()).max_by(|&a, &b| is[a].total_cmp(&is[b])).unwrap_or(0);
+        // relative rank in (0, 1); ties share the rank
+        let below = oos.iter().filter(|s| **s < oos[best]).count() as f64;
+        let ties = oos.iter().filter(|s| **s == oos[best]).count() as f64;
+        let omega = (below + (ties + 1.0) / 2.0) / (n + 1.0);
+        logits.push((omega / (1.0 - omega)).ln());
+    }
+    let pbo = logits.iter().filter(|l| **l <= 0.0).count() as f64 / logits.len() as f64;
+    Ok(PboEstimate { pbo, logits })
+}
+
+/// Selection-bias summary for the trial picked out of a set of trials
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OverfittingReport {
+    pub trials: usize,
+    /// Index of the picked trial
+    pub picked: usize,
+    /// Per-bar Sharpe of the picked trial
+    pub picked_sharpe: f64,
+    /// Per-bar Sharpe the best trial is expected to reach by luck
+    pub expected_max_sharpe: f64,
+    /// PSR of the picked trial against a zero Sharpe
+    pub probabilistic_sharpe: f64,
+    /// PSR of the picked trial against `expected_max_sharpe`
+    pub deflated_sharpe: f64,
+    /// `None` when the trials are too few or too short for CSCV
+    pub pbo: Option<f64>,
+}
+
+impl OverfittingReport {
+    /// Diagnose the matrix of trial returns (one aligned series per trial) for the trial at
+    /// index `picked`, which need not be the one with the highest Sharpe
+    pub fn from_trials(trials: &[Vec<f64>], picked: usize, groups: usize) -> Result<Self> {
+        if picked >= trials.len() {
+            return Err(Error::InvalidArgument(format!(
+                "picked trial {picked} out of {} trials",
+                trials.len()
+            )));
+        }
+        let sharpes: Vec<f64> = trials.iter().map(|t| bar_sharpe(t)).collect();
+        let pbo = match probability_of_overfitting(trials, groups) {
+            | Ok(est) => Some(est.pbo),
+            | Err(e) => {
+                log::debug!("PBO not computed: {e}");
+                None
+            }
+        };
+        Ok(Self {
+            trials: trials.len(),
+            picked,
+            picked_sharpe: sharpes[picked],
+            expected_max_sharpe: expected_max_sharpe(&sharpes),
+            probabilistic_sharpe: probabilistic_sharpe(&trials[picked], 0.0),
+            deflated_sharpe: deflated_sharpe(&trials[picked], &sharpes),
+            pbo,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    fn noise(seed: u64, n: usize, drift: f64) -> Vec<f64> {
+        let mut rng = StdRng::seed_from_u64(seed);
+        (0..n).map(|_| drift + rng.gen_range(-0.01..0.01)).collect()
+    }
+
+    #[test]
+    fn deflation_grows_with_trial_count() {
+        let returns = noise(1, 500, 0.001);
+        let psr = probabilistic_sharpe(&returns, 0.0);
+        assert!(psr > 0.95);
+        let few: Vec<f64> = (0..2).map(|i| bar_sharpe(&noise(i, 500, 0.0))).collect();
+        let many: Vec<f64> = (0..200).map(|i| bar_sharpe(&noise(i, 500, 0.0))).collect();
+        assert!(expected_max_sharpe(&many) > expected_max_sharpe(&few));
+        assert!(deflated_sharpe(&returns, &many) < psr);
+    }
+
+    #[test]
+    fn pbo_separates_skill_from_noise() {
+        let pure_noise: Vec<Vec<f64>> = (0..20).map(|i| noise(i, 400, 0.0)).collect();
+        let noisy = probability_of_overfitting(&pure_noise, 8).unwrap();
+        assert_eq!(noisy.logits.len(), 70);
+        assert!(noisy.pbo > 0.2);
+
+        let mut skilled = pure_noise.clone();
+        skilled[0] = noise(99, 400, 0.005);
+        assert_eq!(probability_of_overfitting(&skilled, 8).unwrap().pbo, 0.0);
+        assert!(probability_of_overfitting(&skilled, 3).is_err());
+    }
+
+    #[test]
+    fn report_describes_the_picked_trial() {
+        let trials = vec![noise(1, 400, 0.0), noise(2, 400, 0.004)];
+        let report = OverfittingReport::from_trials(&trials, 0, 8).unwrap();
+        assert_eq!(report.picked, 0);
+        assert_eq!(report.picked_sharpe, bar_sharpe(&trials[0]));
+        assert_eq!(report.probabilistic_sharpe, probabilistic_sharpe(&trials[0], 0.0));
+        assert!(OverfittingReport::from_trials(&trials, 2, 8).is_err());
+    }
+}
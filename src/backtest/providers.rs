@@ -6,13 +6,18 @@ use csv::ReaderBuilder;
 use serde::Deserialize;
 use std::fs;
 use std::io::Cursor;
-
 use std::path::Path;
 
+/// Placeholder pair for data whose symbol cannot be determined
+pub const UNKNOWN_SYMBOL: &str = "UNK/UNK";
+
 /// Simple CSV row matching the extended MarketData struct
 #[derive(Debug, Deserialize)]
 struct CsvRow {
     timestamp: i64,
+    /// Optional pair column for multi-symbol files ("SOL/USDC", "SOL-USDC" or "SOL_USDC")
+    #[serde(default)]
+    symbol: Option<String>,
     open: Option<f64>,
     high: Option<f64>,
     low: Option<f64>,
@@ -20,47 +25,109 @@ struct CsvRow {
     volume: Option<f64>,
 }
 
-/// CSV provider that reads OHLCV rows into `MarketData` records
-#[derive(Clone)]
-pub struct CSVHistoricalDataProvider;
+/// Parse a pair written as `BASE/QUOTE`, `BASE-QUOTE` or `BASE_QUOTE`
+pub fn parse_pair(s: &str) -> Option<TradingPair> {
+    let mut parts = s.trim().split(['/', '-', '_']);
+    let (base, quote) = (parts.next()?, parts.next()?);
+    let valid = |p: &str| !p.is_empty() && p.chars().all(|c| c.is_ascii_alphanumeric());
+    if parts.next().is_some() || !valid(base) || !valid(quote) {
+        return None;
+    }
+    Some(TradingPair::new(base, quote))
+}
+
+/// Quote assets a pair can be inferred with from a file name
+const QUOTE_ASSETS: &[&str] = &["USDC", "USDT", "USD", "SOL", "BTC", "ETH"];
+
+/// Infer the pair from importer-style file names (`SOL_USDC_1h.csv`, `SOL_USDC.csv`). The
+/// quote must be a known quote asset, so `sample_data.csv` or `btc_1h.csv` infer nothing.
+pub fn pair_from_path(path: &Path) -> Option<TradingPair> {
+    let stem = path.file_stem()?.to_str()?;
+    let parts: Vec<&str> = stem.split('_').collect();
+    let (base, quote) = match parts.as_slice() {
+        | [base, quote] | [base, quote, _] => (base, quote),
+        | _ => return None,
+    };
+    if !QUOTE_ASSETS.iter().any(|q| q.eq_ignore_ascii_case(quote)) {
+        return None;
+    }
+    parse_pair(&format!("{base}/{quote}"))
+}
+
+/// Pair for a row: the row's own symbol column wins, then the provider/file default. Rows
+/// with neither are an error rather than being filed under a made-up pair.
+pub(crate) fn resolve_pair(
+    row_symbol: Option<&str>, default: Option<&TradingPair>, data_file: &Path,
+) -> Result<TradingPair> {
+    match row_symbol.map(str::trim).filter(|s| !s.is_empty()) {
+        | Some(sym) => parse_pair(sym)
+            .ok_or_else(|| crate::Error::DataError(format!("invalid symbol '{sym}' in CSV"))),
+        | None => default.cloned().ok_or_else(|| {
+            crate::Error::DataError(format!(
+                "cannot tell the pair of {}: add a symbol column, name the file \
+                 BASE_QUOTE[_tf].csv or set the pair explicitly",
+                data_file.display()
+            ))
+        }),
+    }
+}
+
+/// CSV provider that reads OHLCV rows into `MarketData` records.
+///
+/// The pair of each row comes from an optional `symbol` column, falling back to the pair set
+/// with [`with_pair`](Self::with_pair), then to the file name; rows with no pair fail to load.
+#[derive(Clone, Default)]
+pub struct CSVHistoricalDataProvider {
+    pair: Option<TradingPair>,
+}
 
 impl CSVHistoricalDataProvider {
     pub fn new() -> Self {
-        Self
+        Self { pair: None }
+    }
+
+    /// Assign every row without a symbol column to `pair`
+    pub fn with_pair(mut self, pair: TradingPair) -> Self {
+        self.pair = Some(pair);
+        self
     }
-}
 
-impl Default for CSVHistoricalDataProvider {
-    fn default() -> Self {
-        Self::new()
+    fn default_pair(&self, data_file: &Path) -> Option<TradingPair> {
+        self.pair.clone().or_else(|| pair_from_path(data_file))
     }
 }
 
 impl HistoricalDataProvider for CSVHistoricalDataProvider {
-    fn load(&self, data_file: &std::path::Path) -> Result<Vec<MarketData>> {
+    fn load(&self, data_file: &Path) -> Result<Vec<MarketData>> {
         let no_cache = std::env::var("BACKTEST_NO_CACHE").is_ok();
-        let key = cache::build_key(&["csv", data_file.to_string_lossy().as_ref()]);
-        let raw_bytes = if !no_cache {
-            if let Some(b) = cache::get_raw(&key)? {
-                b
-            } else {
-                fs::read(data_file)?
-            }
+        let key = cache::file_key("csv", data_file)?;
+        // the cache is an optimisation; a locked or broken cache falls back to the file
+        let cached = if no_cache {
+            None
         } else {
-            fs::read(data_file)?
+            cache::get_raw(&key).ok().flatten()
+        };
+        let raw_bytes = match cached {
+            | Some(b) => b,
+            | None => {
+                let bytes = fs::read(data_file)?;
+                if !no_cache {
+                    cache::put_raw(&key, &bytes).ok();
+                }
+                bytes
+            }
         };
-        if !no_cache {
-            cache::put_raw(&key, &raw_bytes).ok();
-        }
         let mut rdr = ReaderBuilder::new()
             .has_headers(true)
             .from_reader(Cursor::new(raw_bytes));
+        let default_pair = self.default_pair(data_file);
         let mut out = Vec::new();
         for rec in rdr.deserialize::<CsvRow>() {
             let row = rec.map_err(|e| crate::Error::DataError(format!("CSV parse error: {e}")))?;
+            let pair = resolve_pair(row.symbol.as_deref(), default_pair.as_ref(), data_file)?;
             out.push(MarketData {
-                pair: TradingPair::new("UNK", "UNK"),
-                symbol: "UNK/UNK".to_string(),
+                symbol: pair.to_string(),
+                pair,
                 candles: Vec::new(),
                 last_price: row.close.unwrap_or(0.0),
                 volume_24h: 0.0,
@@ -75,6 +142,8 @@ impl HistoricalDataProvider for CSVHistoricalDataProvider {
                 dex_prices: None,
             });
         }
+        // Multi-symbol files are not necessarily grouped by time
+        out.sort_by_key(|d| d.timestamp);
         Ok(out)
     }
 
@@ -277,7 +277,13 @@ impl RemoteHistoricalDataProvider for CryptoCompareProvider {
         // Determine endpoint and aggregation based on timeframe string
         let (endpoint, aggregate) = match timeframe.to_lowercase().as_str() {
             | "1d" | "1day" | "day" | "d" => ("histoday", 1),
+            | "1w" | "1week" | "week" | "w" => ("histoday", 7),
             | "1h" | "hour" | "h" => ("histohour", 1),
+            | tf if tf.ends_with('h') => {
+                // parse "4h" etc.
+                let num = tf.trim_end_matches('h').parse::<u32>().unwrap_or(1).max(1);
+                ("histohour", num)
+            }
             | tf if tf.ends_with('m') => {
                 // parse "5m", "15m" etc.
                 let num = tf.trim_end_matches('m').parse::<u32>().unwrap_or(1).max(1);
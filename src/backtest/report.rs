@@ -0,0 +1,428 @@
+//! Report analytics: PnL attribution, risk-adjusted ratios, drawdown statistics and the
+//! JSON / HTML tearsheet exports of [`BacktestReport`].
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write as _;
+
+use serde::{Deserialize, Serialize};
+
+use super::{BacktestReport, SimulatedTrade};
+use crate::utils::types::OrderSide;
+
+const SECS_PER_YEAR: f64 = 365.25 * 86_400.0;
+/// How reports show a `None` profit factor
+pub(crate) const UNBOUNDED_PROFIT_FACTOR: &str = "∞ (no losing trades)";
+
+/// PnL contribution of one strategy or symbol
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Attribution {
+    pub trades: usize,
+    pub winning_trades: usize,
+    /// Realised PnL before fees
+    pub realized_pnl: f64,
+    pub fees: f64,
+    /// Traded notional
+    pub volume: f64,
+}
+
+impl Attribution {
+    /// Realised PnL after fees
+    pub fn net_pnl(&self) -> f64 {
+        self.realized_pnl - self.fees
+    }
+
+    fn add(&mut self, trade: &SimulatedTrade) {
+        self.trades += 1;
+        if trade.pnl > 0.0 {
+            self.winning_trades += 1;
+        }
+        self.realized_pnl += trade.pnl;
+        self.fees += trade.fee;
+        self.volume += trade.qty * trade.price;
+    }
+}
+
+/// Group the ledger by `key` (strategy, symbol, ...)
+pub fn attribute_by<'a>(
+    trades: &'a [SimulatedTrade], key: impl Fn(&'a SimulatedTrade) -> &'a str,
+) -> BTreeMap<String, Attribution> {
+    let mut out: BTreeMap<String, Attribution> = BTreeMap::new();
+    for t in trades {
+        out.entry(key(t).to_string()).or_default().add(t);
+    }
+    out
+}
+
+/// Bar-to-bar simple returns of an equity curve
+pub fn simple_returns(equity: &[f64]) -> Vec<f64> {
+    equity
+        .windows(2)
+        .filter(|w| w[0] > 0.0)
+        .map(|w| (w[1] - w[0]) / w[0])
+        .collect()
+}
+
+fn mean(xs: &[f64]) -> f64 {
+    xs.iter().sum::<f64>() / xs.len().max(1) as f64
+}
+
+/// Sharpe ratio scaled by `sqrt(n)`, matching the historical report definition
+pub fn sharpe(returns: &[f64]) -> f64 {
+    let m = mean(returns);
+    let sd = (returns.iter().map(|r| (r - m).powi(2)).sum::<f64>()
+        / returns.len().max(1) as f64)
+        .sqrt();
+    if sd > 0.0 {
+        m / sd * (returns.len() as f64).sqrt()
+    } else {
+        0.0
+    }
+}
+
+/// Like [`sharpe`] but only penalises downside deviation (target return 0)
+pub fn sortino(returns: &[f64]) -> f64 {
+    let downside =
+        (returns.iter().map(|r| r.min(0.0).powi(2)).sum::<f64>() / returns.len().max(1) as f64)
+            .sqrt();
+    if downside > 0.0 {
+        mean(returns) / downside * (returns.len() as f64).sqrt()
+    } else {
+        0.0
+    }
+}
+
+/// Drawdown from the running peak at every point and the number of bars spent below that
+/// peak. The peak starts at `starting_balance`.
+pub fn drawdowns(equity: &[f64], starting_balance: f64) -> (Vec<f64>, Vec<usize>) {
+    let mut peak = starting_balance;
+    let mut underwater = 0usize;
+    let mut series = Vec::with_capacity(equity.len());
+    let mut durations = Vec::with_capacity(equity.len());
+    for &eq in equity {
+        if eq >= peak {
+            peak = eq;
+            underwater = 0;
+        } else {
+            underwater += 1;
+        }
+        series.push(if peak > 0.0 { (peak - eq) / peak } else { 0.0 });
+        durations.push(underwater);
+    }
+    (series, durations)
+}
+
+/// Gross profit over gross loss of closing trades, each net of its own fee and the entry fees of
+/// the quantity it closes (so every fee is counted once, as in `robustness`); `None` when there
+/// are gains but no losing trades, so the ratio is unbounded
+pub fn profit_factor(trades: &[SimulatedTrade]) -> Option<f64> {
+    // per symbol: signed position and the entry fees not yet charged to a closing trade
+    let mut open: HashMap<&str, (f64, f64)> = HashMap::new();
+    let (mut gains, mut losses) = (0.0, 0.0);
+    for t in trades {
+        let (pos, entry_fees) = open.entry(t.symbol.as_str()).or_insert((0.0, 0.0));
+        let signed = match t.side {
+            | OrderSide::Buy => t.qty,
+            | OrderSide::Sell => -t.qty,
+        };
+        if *pos * signed < 0.0 {
+            let share = (t.qty / pos.abs()).min(1.0);
+            let entry_fee = *entry_fees * share;
+            *entry_fees -= entry_fee;
+            let net = t.pnl - t.fee - entry_fee;
+            if net > 0.0 {
+                gains += net;
+            } else {
+                losses -= net;
+            }
+        } else {
+            *entry_fees += t.fee;
+        }
+        *pos += signed;
+        if pos.abs() <= 1e-12 {
+            *entry_fees = 0.0;
+        }
+    }
+    match (gains > 0.0, losses > 0.0) {
+        | (_, true) => Some(gains / losses),
+        | (true, false) => None,
+        | (false, false) => Some(0.0),
+    }
+}
+
+/// Replays the ledger and returns the `(opened, flattened)` timestamps of every position.
+/// Positions still open at the end count as held until `end`.
+fn position_spans(trades: &[SimulatedTrade], end: i64) -> Vec<(i64, i64)> {
+    let mut open: HashMap<&str, (f64, i64)> = HashMap::new();
+    let mut spans = Vec::new();
+    for t in trades {
+        let entry = open.entry(t.symbol.as_str()).or_insert((0.0, t.timestamp));
+        let before = entry.0;
+        entry.0 += match t.side {
+            | OrderSide::Buy => t.qty,
+            | OrderSide::Sell => -t.qty,
+        };
+        if before.abs() <= 1e-12 {
+            entry.1 = t.timestamp;
+        } else if entry.0.abs() <= 1e-12 {
+            spans.push((entry.1, t.timestamp));
+        }
+    }
+    spans.extend(open.values().filter(|(q, _)| q.abs() > 1e-12).map(|(_, ts)| (*ts, end)));
+    spans
+}
+
+/// Average time (seconds) between opening and flattening a position
+pub fn avg_holding_secs(trades: &[SimulatedTrade]) -> f64 {
+    let last = trades.last().map(|t| t.timestamp).unwrap_or(0);
+    let spans = position_spans(trades, last);
+    if spans.is_empty() {
+        return 0.0;
+    }
+    spans.iter().map(|(a, b)| (b - a) as f64).sum::<f64>() / spans.len() as f64
+}
+
+/// Fraction of bars during which at least one position was open
+pub fn exposure_time(trades: &[SimulatedTrade], timestamps: &[i64]) -> f64 {
+    if timestamps.is_empty() {
+        return 0.0;
+    }
+    // the final bar counts as exposed if a position is still open
+    let end = timestamps.iter().copied().max().unwrap_or(0) + 1;
+    let spans = position_spans(trades, end);
+    let exposed = timestamps
+        .iter()
+        .filter(|ts| spans.iter().any(|(a, b)| **ts >= *a && **ts < *b))
+        .count();
+    exposed as f64 / timestamps.len() as f64
+}
+
+/// Annualised return over max drawdown
+pub fn calmar(start: f64, end: f64, timestamps: &[i64], max_drawdown: f64) -> f64 {
+    let span = match (timestamps.first(), timestamps.last()) {
+        | (Some(a), Some(b)) if b > a => (b - a) as f64,
+        | _ => return 0.0,
+    };
+    if start <= 0.0 || end <= 0.0 || max_drawdown <= 0.0 {
+        return 0.0;
+    }
+    let cagr = (end / start).powf(SECS_PER_YEAR / span) - 1.0;
+    cagr / max_drawdown
+}
+
+impl BacktestReport {
+    /// Assemble a report from the equity curve (one point per bar) and the trade ledger
+    pub fn from_ledger(
+        starting_balance: f64, ending_balance: f64, realized_pnl: f64, equity_curve: Vec<f64>,
+        timestamps: Vec<i64>, trades: Vec<SimulatedTrade>,
+    ) -> Self {
+        let returns = simple_returns(&equity_curve);
+        let (drawdown_series, drawdown_duration) = drawdowns(&equity_curve, starting_balance);
+        let max_drawdown = drawdown_series.iter().copied().fold(0.0, f64::max);
+        Self {
+            starting_balance,
+            ending_balance,
+            realized_pnl,
+            max_drawdown,
+            total_trades: trades.len(),
+            winning_trades: trades.iter().filter(|t| t.pnl > 0.0).count(),
+            sharpe: sharpe(&returns),
+            sortino: sortino(&returns),
+            calmar: calmar(starting_balance, ending_balance, &timestamps, max_drawdown),
+            profit_factor: profit_factor(&trades),
+            exposure_time: exposure_time(&trades, &timestamps),
+            avg_holding_secs: avg_holding_secs(&trades),
+            max_drawdown_duration: drawdown_duration.iter().copied().max().unwrap_or(0),
+            by_strategy: attribute_by(&trades, |t| t.strategy.as_str()),
+            by_symbol: attribute_by(&trades, |t| t.symbol.as_str()),
+            equity_curve,
+            returns,
+            timestamps,
+            drawdown_series,
+            drawdown_duration,
+            trades,
+            rejected_signals: 0,
+        }
+    }
+
+    /// Write the full report, ledger included, as pretty-printed JSON
+    pub fn to_json<P: AsRef<std::path::Path>>(&self, path: P) -> anyhow::Result<()> {
+        let file = std::fs::File::create(path)?;
+        serde_json::to_writer_pretty(std::io::BufWriter::new(file), self)?;
+        Ok(())
+    }
+
+    /// Write a self-contained HTML tearsheet (inline SVG charts, no external assets)
+    pub fn to_html<P: AsRef<std::path::Path>>(&self, path: P) -> std::io::Result<()> {
+        std::fs::write(path, self.render_html())
+    }
+
+    fn render_html(&self) -> String {
+        let mut h = String::new();
+        let pct = |x: f64| format!("{:.2}%", x * 100.0);
+        let ratio = |x: f64| if x.is_finite() { format!("{x:.2}") } else { "∞".to_string() };
+        let win_rate = self.winning_trades as f64 / self.total_trades.max(1) as f64;
+        let metrics = [
+            ("Start balance", format!("{:.2}", self.starting_balance)),
+            ("End balance", format!("{:.2}", self.ending_balance)),
+            ("Realized PnL", format!("{:.2}", self.realized_pnl)),
+            ("Trades", self.total_trades.to_string()),
+            ("Win rate", pct(win_rate)),
+            ("Sharpe", ratio(self.sharpe)),
+            ("Sortino", ratio(self.sortino)),
+            ("Calmar", ratio(self.calmar)),
+            ("Profit factor", self.profit_factor.map_or(UNBOUNDED_PROFIT_FACTOR.into(), ratio)),
+            ("Max drawdown", pct(self.max_drawdown)),
+            ("Longest drawdown (bars)", self.max_drawdown_duration.to_string()),
+            ("Exposure", pct(self.exposure_time)),
+            ("Avg holding", format!("{:.1} h", self.avg_holding_secs / 3600.0)),
+            ("Rejected signals", self.rejected_signals.to_string()),
+        ];
+
+        h.push_str(
+            "<!DOCTYPE html><html><head><meta charset=\"utf-8\"><title>Backtest tearsheet</title>\
+             <style>body{font-family:sans-serif;margin:2em;color:#222}\
+             table{border-collapse:collapse;margin-bottom:2em}\
+             td,th{border:1px solid #ccc;padding:4px 8px;text-align:right}\
+             th{background:#f4f4f4}td:first-child,th:first-child{text-align:left}</style>\
+             </head><body><h1>Backtest tearsheet</h1>",
+        );
+        h.push_str("<table>");
+        for (name, value) in metrics {
+            let _ = write!(h, "<tr><td>{name}</td><td>{value}</td></tr>");
+        }
+        h.push_str("</table>");
+
+        h.push_str("<h2>Equity</h2>");
+        h.push_str(&svg_polyline(&self.equity_curve, "#1f77b4"));
+        let underwater: Vec<f64> = self.drawdown_series.iter().map(|d| -d).collect();
+        h.push_str("<h2>Drawdown</h2>");
+        h.push_str(&svg_polyline(&underwater, "#d62728"));
+
+        for (title, rows) in [("Strategy", &self.by_strategy), ("Symbol", &self.by_symbol)] {
+            let _ = write!(
+                h,
+                "<h2>PnL by {}</h2><table><tr><th>{title}</th><th>Trades</th><th>Wins</th>\
+                 <th>Realized</th><th>Fees</th><th>Net</th><th>Volume</th></tr>",
+                title.to_lowercase()
+            );
+            for (key, a) in rows {
+                let _ = write!(
+                    h,
+                    "<tr><td>{}</td><td>{}</td><td>{}</td><td>{:.2}</td><td>{:.2}</td>\
+                     <td>{:.2}</td><td>{:.2}</td></tr>",
+                    escape(key),
+                    a.trades,
+                    a.winning_trades,
+                    a.realized_pnl,
+                    a.fees,
+                    a.net_pnl(),
+                    a.volume
+                );
+            }
+            h.push_str("</table>");
+        }
+
+        h.push_str(
+            "<h2>Trades</h2><table><tr><th>Time</th><th>Strategy</th><th>Symbol</th>\
+             <th>Side</th><th>Qty</th><th>Price</th><th>Fee</th><th>PnL</th></tr>",
+        );
+        for t in &self.trades {
+            let time = chrono::DateTime::<chrono::Utc>::from_timestamp(t.timestamp, 0)
+                .map(|d| d.format("%Y-%m-%d %H:%M").to_string())
+                .unwrap_or_else(|| t.timestamp.to_string());
+            let _ = write!(
+                h,
+                "<tr><td>{time}</td><td>{}</td><td>{}</td><td>{:?}</td><td>{:.6}</td>\
+                 <td>{:.6}</td><td>{:.4}</td><td>{:.2}</td></tr>",
+                escape(&t.strategy),
+                escape(&t.symbol),
+                t.side,
+                t.qty,
+                t.price,
+                t.fee,
+                t.pnl
+            );
+        }
+        h.push_str("</table></body></html>");
+        h
+    }
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+/// Inline SVG line chart of `values`
+fn svg_polyline(values: &[f64], color: &str) -> String {
+    const W: f64 = 800.0;
+    const H: f64 = 240.0;
+    if values.len() < 2 {
+        return "<p>Not enough data</p>".to_string();
+    }
+    let min = values.iter().copied().fold(f64::INFINITY, f64::min);
+    let max = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    let range = if max > min { max - min } else { 1.0 };
+    let step = W / (values.len() - 1) as f64;
+    let mut points = String::new();
+    for (i, v) in values.iter().enumerate() {
+        let _ = write!(points, "{:.1},{:.1} ", i as f64 * step, H - (v - min) / range * H);
+    }
+    format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{W}\" height=\"{H}\" \
+         style=\"border:1px solid #ddd\"><polyline fill=\"none\" stroke=\"{color}\" \
+         stroke-width=\"1.5\" points=\"{}\"/></svg>",
+        points.trim_end()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trade(
+        ts: i64, strategy: &str, side: OrderSide, qty: f64, price: f64, pnl: f64,
+    ) -> SimulatedTrade {
+        SimulatedTrade {
+            timestamp: ts,
+            strategy: strategy.into(),
+            symbol: "SOL/USDC".into(),
+            side,
+            qty,
+            price,
+            pnl,
+            fee: 0.0,
+        }
+    }
+
+    #[test]
+    fn ledger_metrics() {
+        let trades = vec![
+            trade(0, "a", OrderSide::Buy, 1.0, 100.0, 0.0),
+            trade(7_200, "b", OrderSide::Sell, 1.0, 110.0, 10.0),
+            trade(10_800, "a", OrderSide::Buy, 1.0, 110.0, 0.0),
+            trade(14_400, "a", OrderSide::Sell, 1.0, 105.0, -5.0),
+        ];
+        assert!((profit_factor(&trades).unwrap() - 2.0).abs() < 1e-12);
+        assert_eq!(profit_factor(&trades[..2]), None);
+        // entry fees count against the trade that closes the position
+        let mut with_fees = trades.clone();
+        for t in with_fees.iter_mut() {
+            t.fee = 1.0;
+        }
+        assert!((profit_factor(&with_fees).unwrap() - 8.0 / 7.0).abs() < 1e-12);
+        assert!((avg_holding_secs(&trades) - 5_400.0).abs() < 1e-9);
+        let by_strategy = attribute_by(&trades, |t| t.strategy.as_str());
+        assert_eq!(by_strategy["a"].realized_pnl, -5.0);
+        assert_eq!(by_strategy["b"].winning_trades, 1);
+
+        let timestamps = [0, 3_600, 7_200, 10_800, 14_400, 18_000];
+        assert!((exposure_time(&trades, &timestamps) - 0.5).abs() < 1e-12);
+    }
+
+    #[test]
+    fn drawdown_duration_resets_at_new_peak() {
+        let (dd, dur) = drawdowns(&[100.0, 90.0, 95.0, 101.0, 100.0], 100.0);
+        assert_eq!(dur, vec![0, 1, 2, 0, 1]);
+        assert!((dd[1] - 0.1).abs() < 1e-12);
+    }
+}
@@ -0,0 +1,436 @@
+//! Monte Carlo robustness analysis of backtest results.
+//!
+//! Every [`Method`] derives `iterations` alternative equity paths from a finished
+//! [`BacktestReport`] and summarises final equity, max drawdown and Sharpe across them. A
+//! strategy whose metrics only look good on the realised path will show wide intervals, a
+//! material risk of ruin and a high [`RobustnessReport::sharpe_p_value`].
+
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::report::{drawdowns, sharpe, simple_returns};
+use super::BacktestReport;
+use crate::{Error, Result};
+
+/// How alternative paths are generated
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "snake_case")]
+pub enum Method {
+    /// Resample bar returns with replacement
+    Bootstrap,
+    /// Resample blocks of consecutive bar returns, keeping short-range autocorrelation
+    BlockBootstrap,
+    /// Resample per-trade net PnLs with replacement
+    TradeBootstrap,
+    /// Replay the realised trades in random order
+    TradeShuffle,
+    /// Charge random extra fees (per path) and slippage (per trade) on the realised path
+    CostPerturbation,
+}
+
+impl Method {
+    pub const ALL: [Method; 5] = [
+        Method::Bootstrap,
+        Method::BlockBootstrap,
+        Method::TradeBootstrap,
+        Method::TradeShuffle,
+        Method::CostPerturbation,
+    ];
+
+    fn uses_trades(&self) -> bool {
+        matches!(self, Method::TradeBootstrap | Method::TradeShuffle)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RobustnessConfig {
+    pub iterations: usize,
+    pub seed: u64,
+    /// Block length for [`Method::BlockBootstrap`]; 0 uses `sqrt(n)`
+    pub block_len: usize,
+    /// Two-sided confidence level of the reported intervals
+    pub confidence: f64,
+    /// Fraction of the starting balance whose loss counts as ruin
+    pub ruin_drawdown: f64,
+    /// Upper bound of the extra fee drawn once per path, in bps of notional
+    pub max_extra_fee_bps: f64,
+    /// Upper bound of the extra slippage drawn per trade, in bps of notional
+    pub max_extra_slippage_bps: f64,
+}
+
+impl Default for RobustnessConfig {
+    fn default() -> Self {
+        Self {
+            iterations: 1_000,
+            seed: 0,
+            block_len: 0,
+            confidence: 0.95,
+            ruin_drawdown: 0.5,
+            max_extra_fee_bps: 10.0,
+            max_extra_slippage_bps: 10.0,
+        }
+    }
+}
+
+/// Moments and quantiles of a simulated metric
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct DistributionSummary {
+    pub mean: f64,
+    pub std_dev: f64,
+    pub min: f64,
+    pub median: f64,
+    pub max: f64,
+    /// Lower bound of the confidence interval
+    pub lower: f64,
+    /// Upper bound of the confidence interval
+    pub upper: f64,
+}
+
+impl DistributionSummary {
+    pub fn from_samples(samples: &[f64], confidence: f64) -> Self {
+        let mut sorted: Vec<f64> = samples.iter().copied().filter(|x| x.is_finite()).collect();
+        if sorted.is_empty() {
+            return Self::default();
+        }
+        sorted.sort_by(f64::total_cmp);
+        let n = sorted.len() as f64;
+        let mean = sorted.iter().sum::<f64>() / n;
+        let var = sorted.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / (n - 1.0).max(1.0);
+        let tail = (1.0 - confidence.clamp(0.0, 1.0)) / 2.0;
+        Self {
+            mean,
+            std_dev: var.sqrt(),
+            min: sorted[0],
+            median: percentile(&sorted, 0.5),
+            max: sorted[sorted.len() - 1],
+            lower: percentile(&sorted, tail),
+            upper: percentile(&sorted, 1.0 - tail),
+        }
+    }
+}
+
+/// Linearly interpolated quantile `q` (0..=1) of an ascending slice
+pub fn percentile(sorted: &[f64], q: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    let pos = q.clamp(0.0, 1.0) * (sorted.len() - 1) as f64;
+    let (lo, hi) = (pos.floor() as usize, pos.ceil() as usize);
+    sorted[lo] + (sorted[hi] - sorted[lo]) * (pos - lo as f64)
+}
+
+/// Distribution of one method's simulated paths. Trade-based methods measure Sharpe over
+/// per-trade returns, so it is not directly comparable with the bar-based figure.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MethodSummary {
+    pub method: Method,
+    pub final_equity: DistributionSummary,
+    pub max_drawdown: DistributionSummary,
+    pub sharpe: DistributionSummary,
+    /// Share of paths that lost `ruin_drawdown` of the starting balance at any point
+    pub risk_of_ruin: f64,
+    /// Share of paths ending below the starting balance
+    pub prob_loss: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RobustnessReport {
+    pub config: RobustnessConfig,
+    pub observed_sharpe: f64,
+    /// Share of bootstrapped zero-mean return series whose Sharpe reaches the observed one,
+    /// i.e. how often luck alone produces the result
+    pub sharpe_p_value: f64,
+    pub methods: Vec<MethodSummary>,
+}
+
+impl RobustnessReport {
+    pub fn print(&self) {
+        println!("===== ROBUSTNESS ({} paths) =====", self.config.iterations);
+        println!(
+            "Observed Sharpe : {:.2} (p-value {:.3})",
+            self.observed_sharpe, self.sharpe_p_value
+        );
+        let pct = self.config.confidence * 100.0;
+        for m in &self.methods {
+            println!("--- {:?} ---", m.method);
+            println!(
+                "Final equity : {:.2} [{:.2}, {:.2}] @ {pct:.0}%",
+                m.final_equity.median, m.final_equity.lower, m.final_equity.upper
+            );
+            println!(
+                "Max drawdown : {:.2}% [{:.2}%, {:.2}%]",
+                m.max_drawdown.median * 100.0,
+                m.max_drawdown.lower * 100.0,
+                m.max_drawdown.upper * 100.0
+            );
+            println!(
+                "Sharpe       : {:.2} [{:.2}, {:.2}]",
+                m.sharpe.median, m.sharpe.lower, m.sharpe.upper
+            );
+            println!(
+                "Risk of ruin : {:.2}%  P(loss): {:.2}%",
+                m.risk_of_ruin * 100.0,
+                m.prob_loss * 100.0
+            );
+        }
+    }
+}
+
+/// Run every method in `methods`. Methods without the data they need (no returns, no trades)
+/// are skipped with a warning.
+pub fn analyze(
+    report: &BacktestReport, methods: &[Method], cfg: &RobustnessConfig,
+) -> Result<RobustnessReport> {
+    let mut summaries = Vec::with_capacity(methods.len());
+    for method in methods {
+        match simulate(report, *method, cfg) {
+            | Ok(summary) => summaries.push(summary),
+            | Err(e) => log::warn!("Skipping {method:?}: {e}"),
+        }
+    }
+    if summaries.is_empty() {
+        return Err(Error::DataError("report has no returns or trades to resample".into()));
+    }
+    Ok(RobustnessReport {
+        config: cfg.clone(),
+        observed_sharpe: report.sharpe,
+        sharpe_p_value: sharpe_p_value(&report.returns, report.sharpe, cfg),
+        methods: summaries,
+    })
+}
+
+/// Simulate `cfg.iterations` paths with `method`
+pub fn simulate(
+    report: &BacktestReport, method: Method, cfg: &RobustnessConfig,
+) -> Result<MethodSummary> {
+    let pnls = trade_pnls(report);
+    if method.uses_trades() && pnls.is_empty() {
+        return Err(Error::DataError("no trades to resample".into()));
+    }
+    if !method.uses_trades() && report.returns.is_empty() {
+        return Err(Error::DataError("no returns to resample".into()));
+    }
+    if cfg.iterations == 0 {
+        return Err(Error::InvalidArgument("iterations must be positive".into()));
+    }
+
+    let start = report.starting_balance;
+    let ruin_level = start * (1.0 - cfg.ruin_drawdown);
+    let stats: Vec<PathStats> = (0..cfg.iterations)
+        .into_par_iter()
+        .map(|i| {
+            // one seed per path keeps results independent of the thread schedule
+            let mut rng = StdRng::seed_from_u64(cfg.seed.wrapping_add(i as u64));
+            let (equity, returns) = match method {
+                | Method::Bootstrap => returns_path(start, &bootstrap(&report.returns, &mut rng)),
+                | Method::BlockBootstrap => {
+                    returns_path(start, &block_bootstrap(&report.returns, cfg.block_len, &mut rng))
+                }
+                | Method::TradeBootstrap => pnl_path(start, &bootstrap(&pnls, &mut rng)),
+                | Method::TradeShuffle => {
+                    let mut shuffled = pnls.clone();
+                    shuffled.shuffle(&mut rng);
+                    pnl_path(start, &shuffled)
+                }
+                | Method::CostPerturbation => perturbed_costs(report, cfg, &mut rng),
+            };
+            PathStats::new(&equity, start, &returns, ruin_level)
+        })
+        .collect();
+
+    let n = stats.len() as f64;
+    let summarize = |f: fn(&PathStats) -> f64| {
+        DistributionSummary::from_samples(&stats.iter().map(f).collect::<Vec<_>>(), cfg.confidence)
+    };
+    Ok(MethodSummary {
+        method,
+        final_equity: summarize(|s| s.final_equity),
+        max_drawdown: summarize(|s| s.max_drawdown),
+        sharpe: summarize(|s| s.sharpe),
+        risk_of_ruin: stats.iter().filter(|s| s.ruined).count() as f64 / n,
+        prob_loss: stats.iter().filter(|s| s.final_equity < start).count() as f64 / n,
+    })
+}
+
+/// Bootstrap p-value of `observed` Sharpe under the null of zero mean returns
+pub fn sharpe_p_value(returns: &[f64], observed: f64, cfg: &RobustnessConfig) -> f64 {
+    if returns.is_empty() || cfg.iterations == 0 {
+        return 1.0;
+    }
+    let m = returns.iter().sum::<f64>() / returns.len() as f64;
+    let centred: Vec<f64> = returns.iter().map(|r| r - m).collect();
+    let hits = (0..cfg.iterations)
+        .into_par_iter()
+        .filter(|i| {
+            let mut rng = StdRng::seed_from_u64(cfg.seed.wrapping_add(*i as u64) ^ 0x5eed);
+            sharpe(&bootstrap(&centred, &mut rng)) >= observed
+        })
+        .count();
+    // add-one smoothing so the estimate never claims certainty
+    (hits + 1) as f64 / (cfg.iterations + 1) as f64
+}
+
+struct PathStats {
+    final_equity: f64,
+    max_drawdown: f64,
+    sharpe: f64,
+    ruined: bool,
+}
+
+impl PathStats {
+    fn new(equity: &[f64], start: f64, returns: &[f64], ruin_level: f64) -> Self {
+        let (dd, _) = drawdowns(equity, start);
+        Self {
+            final_equity: equity.last().copied().unwrap_or(start),
+            max_drawdown: dd.into_iter().fold(0.0, f64::max),
+            sharpe: sharpe(returns),
+            ruined: equity.iter().any(|e| *e <= ruin_level),
+        }
+    }
+}
+
+/// Net PnL of every ledger entry; opening fills contribute their fee
+fn trade_pnls(report: &BacktestReport) -> Vec<f64> {
+    report.trades.iter().map(|t| t.pnl - t.fee).collect()
+}
+
+fn bootstrap(xs: &[f64], rng: &mut StdRng) -> Vec<f64> {
+    (0..xs.len()).map(|_| xs[rng.gen_range(0..xs.len())]).collect()
+}
+
+/// Circular block bootstrap
+fn block_bootstrap(xs: &[f64], block_len: usize, rng: &mut StdRng) -> Vec<f64> {
+    let n = xs.len();
+    let block = if block_len == 0 { (n as f64).sqrt().ceil() as usize } else { block_len };
+    let mut out = Vec::with_capacity(n);
+    while out.len() < n {
+        let start = rng.gen_range(0..n);
+        out.extend((0..block.min(n - out.len())).map(|k| xs[(start + k) % n]));
+    }
+    out
+}
+
+fn returns_path(start: f64, returns: &[f64]) -> (Vec<f64>, Vec<f64>) {
+    let mut eq = start;
+    let equity = returns
+        .iter()
+        .map(|r| {
+            eq *= 1.0 + r;
+            eq
+        })
+        .collect();
+    (equity, returns.to_vec())
+}
+
+fn pnl_path(start: f64, pnls: &[f64]) -> (Vec<f64>, Vec<f64>) {
+    let mut eq = start;
+    let mut equity = Vec::with_capacity(pnls.len());
+    let mut returns = Vec::with_capacity(pnls.len());
+    for pnl in pnls {
+        if eq > 0.0 {
+            returns.push(pnl / eq);
+        }
+        eq += pnl;
+        equity.push(eq);
+    }
+    (equity, returns)
+}
+
+/// Realised equity curve minus extra costs charged from the bar of each trade onwards
+fn perturbed_costs(
+    report: &BacktestReport, cfg: &RobustnessConfig, rng: &mut StdRng,
+) -> (Vec<f64>, Vec<f64>) {
+    let len = report.equity_curve.len();
+    let fee_bps = rng.gen_range(0.0..=cfg.max_extra_fee_bps.max(0.0));
+    let mut extra = vec![0.0; len];
+    for t in &report.trades {
+        let bps = fee_bps + rng.gen_range(0.0..=cfg.max_extra_slippage_bps.max(0.0));
+        let idx = if report.timestamps.len() == len {
+            report.timestamps.partition_point(|ts| *ts < t.timestamp)
+        } else {
+            len.saturating_sub(1)
+        };
+        if let Some(slot) = extra.get_mut(idx.min(len.saturating_sub(1))) {
+            *slot += t.qty * t.price * bps / 10_000.0;
+        }
+    }
+    let mut charged = 0.0;
+    let equity: Vec<f64> = report
+        .equity_curve
+        .iter()
+        .zip(extra)
+        .map(|(eq, cost)| {
+            charged += cost;
+            eq - charged
+        })
+        .collect();
+    let returns = simple_returns(&equity);
+    (equity, returns)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backtest::SimulatedTrade;
+    use crate::utils::types::OrderSide;
+
+    fn report(returns: Vec<f64>, pnls: &[f64]) -> BacktestReport {
+        let mut equity = vec![];
+        let mut eq = 10_000.0;
+        for r in &returns {
+            eq *= 1.0 + r;
+            equity.push(eq);
+        }
+        let trades = pnls
+            .iter()
+            .enumerate()
+            .map(|(i, pnl)| SimulatedTrade {
+                timestamp: i as i64,
+                strategy: "s".into(),
+                symbol: "SOL/USDC".into(),
+                side: OrderSide::Sell,
+                qty: 1.0,
+                price: 100.0,
+                pnl: *pnl,
+                fee: 0.0,
+            })
+            .collect();
+        let timestamps = (0..equity.len() as i64).collect();
+        BacktestReport::from_ledger(10_000.0, eq, 0.0, equity, timestamps, trades)
+    }
+
+    #[test]
+    fn percentiles_interpolate() {
+        let s = DistributionSummary::from_samples(&[4.0, 1.0, 3.0, 2.0, 5.0], 0.5);
+        assert_eq!(s.median, 3.0);
+        assert_eq!((s.lower, s.upper), (2.0, 4.0));
+        assert_eq!((s.min, s.max), (1.0, 5.0));
+    }
+
+    #[test]
+    fn shuffle_keeps_final_equity_but_not_drawdown() {
+        let r = report(vec![0.0; 4], &[100.0, -300.0, 50.0, 200.0]);
+        let cfg = RobustnessConfig { iterations: 200, ..Default::default() };
+        let s = simulate(&r, Method::TradeShuffle, &cfg).unwrap();
+        assert!((s.final_equity.min - 10_050.0).abs() < 1e-9);
+        assert!((s.final_equity.max - 10_050.0).abs() < 1e-9);
+        assert!(s.max_drawdown.max > s.max_drawdown.min);
+    }
+
+    #[test]
+    fn consistent_edge_is_not_luck() {
+        let returns: Vec<f64> = (0..250).map(|i| if i % 5 == 0 { -0.004 } else { 0.003 }).collect();
+        let r = report(returns, &[]);
+        let cfg = RobustnessConfig { iterations: 300, ..Default::default() };
+        let out = analyze(&r, &Method::ALL, &cfg).unwrap();
+        assert!(out.sharpe_p_value < 0.01);
+        // trade methods are skipped without a ledger
+        assert_eq!(out.methods.len(), 3);
+        let boot = &out.methods[0];
+        assert!(boot.final_equity.lower > 10_000.0);
+        assert_eq!(boot.risk_of_ruin, 0.0);
+    }
+}
@@ -1,5 +1,6 @@
 use super::HistoricalDataProvider;
 use crate::backtest::cache;
+use crate::backtest::providers::{pair_from_path, resolve_pair};
 use crate::utils::types::{MarketData, TradingPair};
 use crate::Result;
 use csv::ReaderBuilder;
@@ -8,54 +9,65 @@ use std::fs;
 use std::io::Cursor;
 use std::path::Path;
 
-/// CSV schema for tick data: timestamp,price,qty
+/// CSV schema for tick data: timestamp,price,qty with an optional symbol column
 #[derive(Debug, Deserialize)]
 struct TickRow {
     timestamp: i64,
+    #[serde(default)]
+    symbol: Option<String>,
     price: f64,
     qty: f64,
 }
 
-#[derive(Clone)]
-pub struct CSVTicksProvider;
+/// Tick provider; pairs are resolved the same way as for `CSVHistoricalDataProvider`
+#[derive(Clone, Default)]
+pub struct CSVTicksProvider {
+    pair: Option<TradingPair>,
+}
 
 impl CSVTicksProvider {
     pub fn new() -> Self {
-        Self
+        Self { pair: None }
     }
-}
 
-impl Default for CSVTicksProvider {
-    fn default() -> Self {
-        Self::new()
+    /// Assign every tick without a symbol column to `pair`
+    pub fn with_pair(mut self, pair: TradingPair) -> Self {
+        self.pair = Some(pair);
+        self
     }
 }
 
 impl HistoricalDataProvider for CSVTicksProvider {
     fn load(&self, data_file: &Path) -> Result<Vec<MarketData>> {
         let no_cache = std::env::var("BACKTEST_NO_CACHE").is_ok();
-        let key = cache::build_key(&["ticks", data_file.to_string_lossy().as_ref()]);
-        let raw_bytes = if !no_cache {
-            if let Some(b) = cache::get_raw(&key)? {
-                b
-            } else {
-                fs::read(data_file)?
-            }
+        let key = cache::file_key("ticks", data_file)?;
+        // the cache is an optimisation; a locked or broken cache falls back to the file
+        let cached = if no_cache {
+            None
         } else {
-            fs::read(data_file)?
+            cache::get_raw(&key).ok().flatten()
+        };
+        let raw_bytes = match cached {
+            | Some(b) => b,
+            | None => {
+                let bytes = fs::read(data_file)?;
+                if !no_cache {
+                    cache::put_raw(&key, &bytes).ok();
+                }
+                bytes
+            }
         };
-        if !no_cache {
-            cache::put_raw(&key, &raw_bytes).ok();
-        }
         let mut rdr = ReaderBuilder::new()
             .has_headers(true)
             .from_reader(Cursor::new(raw_bytes));
+        let default_pair = self.pair.clone().or_else(|| pair_from_path(data_file));
         let mut out = Vec::new();
         for rec in rdr.deserialize::<TickRow>() {
             let row = rec.map_err(|e| crate::Error::DataError(format!("CSV parse error: {e}")))?;
+            let pair = resolve_pair(row.symbol.as_deref(), default_pair.as_ref(), data_file)?;
             out.push(MarketData {
-                pair: TradingPair::new("UNK", "UNK"),
-                symbol: "UNK/UNK".to_string(),
+                symbol: pair.to_string(),
+                pair,
                 candles: Vec::new(),
                 last_price: row.price,
                 volume_24h: 0.0,
@@ -70,6 +82,7 @@ impl HistoricalDataProvider for CSVTicksProvider {
                 dex_prices: None,
             });
         }
+        out.sort_by_key(|d| d.timestamp);
         Ok(out)
     }
 
@@ -2,6 +2,8 @@
 //! At this stage it only loads the configuration and exits.  We'll extend it
 //! once the engine API is finalised.
 
+use algotraderv2::backtest::optimizer::Objective;
+use algotraderv2::backtest::robustness::Method as RobustnessMethod;
 use algotraderv2::config::Config;
 use anyhow::{Context, Result};
 use axum::{response::IntoResponse, routing::get, Router};
@@ -31,10 +33,9 @@ struct Args {
 enum Command {
     /// Run a historical backtest against market data
     Backtest {
-        #[arg(long, value_name = "CSV")]
-        /// Path to historical market data (e.g. CSV)
-        #[arg(long)]
-        data: String,
+        /// Historical market data files (e.g. one CSV per pair), merged by timestamp
+        #[arg(long, value_name = "CSV", num_args = 1.., required = true)]
+        data: Vec<String>,
         /// Timeframe, e.g. 1m, 5m, 1h (optional)
         #[arg(long)]
         timeframe: Option<String>,
@@ -44,6 +45,121 @@ enum Command {
         /// Use meta-strategy engine to pick best strategy
         #[arg(long)]
         meta: bool,
+        /// Backtest the strategies, sizing, risk and cost settings of this config file
+        #[arg(long, value_name = "TOML", conflicts_with = "meta")]
+        config: Option<String>,
+        /// Pair of data files without a symbol column whose name does not give it, e.g. SOL/USDC
+        #[arg(long, conflicts_with = "meta")]
+        pair: Option<String>,
+    },
+    /// Search strategy parameters by backtesting every candidate
+    Optimize {
+        /// Historical market data files, merged by timestamp
+        #[arg(long, value_name = "CSV", num_args = 1.., required = true)]
+        data: Vec<String>,
+        /// Strategy to optimise (factory name, e.g. mean_reversion)
+        #[arg(long)]
+        strategy: String,
+        /// Parameter space as JSON: arrays are choices, {"min","max","step"} objects ranges
+        #[arg(long, default_value = "{}")]
+        params: String,
+        /// Metric to maximise
+        #[arg(long, value_enum, default_value = "sharpe")]
+        objective: Objective,
+        /// Random search with this many trials (grid search when omitted)
+        #[arg(long)]
+        trials: Option<usize>,
+        /// Seed for random search
+        #[arg(long, default_value_t = 0)]
+        seed: u64,
+        /// Timeframe, e.g. 1m, 5m, 1h (optional)
+        #[arg(long)]
+        timeframe: Option<String>,
+        /// Fee per fill in basis points
+        #[arg(long, default_value_t = 0)]
+        fee_bps: u16,
+        /// Re-optimise on rolling windows of this many training days
+        #[arg(long)]
+        train_days: Option<i64>,
+        /// Out-of-sample days per walk-forward window
+        #[arg(long, default_value_t = 30)]
+        test_days: i64,
+        /// Days between walk-forward windows (defaults to --test-days)
+        #[arg(long)]
+        step_days: Option<i64>,
+        /// Grow the training window from the first bar instead of rolling it
+        #[arg(long, requires = "train_days")]
+        anchored: bool,
+        /// Purged k-fold cross-validation with this many folds instead of walk-forward
+        #[arg(long, conflicts_with = "train_days")]
+        folds: Option<usize>,
+        /// Hours purged before and embargoed after each k-fold test fold
+        #[arg(long, default_value_t = 0, requires = "folds")]
+        embargo_hours: i64,
+    },
+    /// Monte Carlo robustness analysis of a saved backtest report (JSON)
+    Robustness {
+        /// Report written by `backtest` (the .json next to the output CSV)
+        #[arg(long)]
+        report: String,
+        /// Simulated paths per method
+        #[arg(long, default_value_t = 1_000)]
+        iterations: usize,
+        #[arg(long, default_value_t = 0)]
+        seed: u64,
+        /// Methods to run (all when omitted)
+        #[arg(long, value_enum, num_args = 1..)]
+        method: Vec<RobustnessMethod>,
+        /// Loss of starting balance that counts as ruin (fraction)
+        #[arg(long, default_value_t = 0.5)]
+        ruin_drawdown: f64,
+        /// Write the analysis as JSON to this path
+        #[arg(long)]
+        output: Option<String>,
+    },
+    /// Backtest strategies × symbols × parameter sets in parallel and rank them
+    Batch {
+        /// Historical market data files, merged by timestamp
+        #[arg(long, value_name = "CSV", num_args = 1.., required = true)]
+        data: Vec<String>,
+        /// Strategies to run (factory names)
+        #[arg(long = "strategy", num_args = 1.., required = true)]
+        strategies: Vec<String>,
+        /// Parameter spaces as JSON keyed by strategy name, in the `optimize --params` format
+        #[arg(long, default_value = "{}")]
+        params: String,
+        /// Symbols to trade (every symbol in the data when omitted)
+        #[arg(long, num_args = 1..)]
+        symbols: Vec<String>,
+        /// Metric to rank by
+        #[arg(long, value_enum, default_value = "sharpe")]
+        objective: Objective,
+        /// Timeframe, e.g. 1m, 5m, 1h (optional)
+        #[arg(long)]
+        timeframe: Option<String>,
+        /// Fee per fill in basis points
+        #[arg(long, default_value_t = 0)]
+        fee_bps: u16,
+        /// Writes the leaderboard to <OUTPUT>.csv and, with full reports, <OUTPUT>.json
+        #[arg(long, default_value = "leaderboard")]
+        output: String,
+    },
+    /// Replay strategies with and without future candles and flag lookahead bias
+    Lookahead {
+        /// Historical market data files, merged by timestamp
+        #[arg(long, value_name = "CSV", num_args = 1.., required = true)]
+        data: Vec<String>,
+        /// Factory strategy to check (every strategy when omitted)
+        #[arg(long)]
+        strategy: Option<String>,
+        /// Strategy params as JSON (with --strategy)
+        #[arg(long, default_value = "{}", requires = "strategy")]
+        params: String,
+    },
+    /// Inspect or clean the backtest report cache under ~/.algotrader/cache
+    Cache {
+        #[command(subcommand)]
+        action: CacheAction,
     },
     /// Import historical data from CryptoCompare and save to CSV
     Import {
@@ -89,6 +205,26 @@ enum Command {
     },
 }
 
+#[derive(Debug, Subcommand)]
+enum CacheAction {
+    /// List cached reports, oldest first
+    List,
+    /// Print the cached report(s) whose key starts with KEY as JSON
+    Inspect { key: String },
+    /// Remove cached reports (unreadable entries are always removed)
+    Prune {
+        /// Entries written more than this many days ago
+        #[arg(long)]
+        older_than_days: Option<i64>,
+        /// Entries written by another algotrader version
+        #[arg(long)]
+        other_versions: bool,
+        /// Every entry
+        #[arg(long)]
+        all: bool,
+    },
+}
+
 #[tokio::main]
 async fn main() -> Result<()> {
     if std::env::var("RUST_LOG").is_err() {
@@ -107,10 +243,10 @@ async fn main() -> Result<()> {
     // Handle subcommands first so we can fall back to legacy default behaviour
     if let Some(cmd) = &args.command {
         match cmd {
-            | Command::Backtest { data, timeframe, output, meta } => {
+            | Command::Backtest { data, timeframe, output, meta, config, pair } => {
                 println!(
                     "⚙️  Starting backtest on {} (tf={})",
-                    data,
+                    data.join(", "),
                     timeframe.clone().unwrap_or_else(|| "default".into())
                 );
                 use algotraderv2::backtest::simple_backtest;
@@ -118,24 +254,290 @@ async fn main() -> Result<()> {
 
                 // Run simple backtest
                 if *meta {
+                    if data.len() > 1 {
+                        anyhow::bail!("--meta ranks strategies on a single data file");
+                    }
                     let tf = timeframe.as_deref().unwrap_or("default");
                     let mut engine = MetaStrategyEngine::new(tf, 10_000.0, "meta_cache")?;
-                    let ranked = engine.select_best_strategy(&std::path::PathBuf::from(&data))?;
+                    let ranked = engine.select_best_strategy(&std::path::PathBuf::from(&data[0]))?;
                     println!(
                         "🏆 Best strategy: {} (Sharpe {:.2}, DD {:.2}%)",
                         ranked.strategy.name(),
                         ranked.sharpe,
                         ranked.max_drawdown * 100.0
                     );
+                    if let Some(o) = &ranked.overfitting {
+                        let pbo = o.pbo.map_or("n/a".to_string(), |p| format!("{:.1}%", p * 100.0));
+                        println!(
+                            "   {} trials | PSR {:.1}% | DSR {:.1}% | PBO {}",
+                            o.trials,
+                            o.probabilistic_sharpe * 100.0,
+                            o.deflated_sharpe * 100.0,
+                            pbo
+                        );
+                    }
                 } else {
                     let tf = timeframe.as_deref().unwrap_or("default");
                     let out_path = output.as_ref().map(std::path::Path::new);
                     use algotraderv2::backtest::SimMode;
-                    simple_backtest(std::path::Path::new(&data), tf, SimMode::Bar, out_path)
+                    let paths: Vec<std::path::PathBuf> = data.iter().map(Into::into).collect();
+                    let cfg = match config {
+                        | Some(path) => Some(
+                            Config::from_file(path)
+                                .with_context(|| format!("Failed to load configuration {path}"))?,
+                        ),
+                        | None => None,
+                    };
+                    let pair = match pair {
+                        | Some(p) => Some(
+                            algotraderv2::backtest::providers::parse_pair(p)
+                                .with_context(|| format!("Invalid --pair {p}"))?,
+                        ),
+                        | None => None,
+                    };
+                    simple_backtest(&paths, pair.as_ref(), tf, SimMode::Bar, cfg.as_ref(), out_path)
                         .await?;
                 }
                 return Ok(());
             }
+            | Command::Optimize {
+                data,
+                strategy,
+                params,
+                objective,
+                trials,
+                seed,
+                timeframe,
+                fee_bps,
+                train_days,
+                test_days,
+                step_days,
+                anchored,
+                folds,
+                embargo_hours,
+            } => {
+                use algotraderv2::backtest::harness::{
+                    run_purged_kfold, run_walk_forward_optimized, PurgedKFoldConfig,
+                    WalkForwardConfig, WindowMode,
+                };
+                use algotraderv2::backtest::optimizer::{Optimizer, ParamSpace, SearchMethod};
+                use algotraderv2::backtest::providers::CSVHistoricalDataProvider;
+                use algotraderv2::backtest::HistoricalDataProvider;
+                use algotraderv2::strategies::StrategyConfig;
+
+                let cfg = StrategyConfig {
+                    name: strategy.clone(),
+                    enabled: true,
+                    params: serde_json::from_str(params).context("--params must be JSON")?,
+                    performance: None,
+                };
+                let method = match trials {
+                    | Some(trials) => SearchMethod::Random { trials: *trials, seed: *seed },
+                    | None => SearchMethod::Grid,
+                };
+                let optimizer = Optimizer {
+                    timeframe: timeframe.clone().unwrap_or_else(|| "default".into()),
+                    fee_bps: *fee_bps,
+                    ..Optimizer::new(ParamSpace::from_config(&cfg)?, *objective, method)
+                };
+                let paths: Vec<std::path::PathBuf> = data.iter().map(Into::into).collect();
+                let market_data = CSVHistoricalDataProvider::new().load_many(&paths)?;
+
+                if let Some(train_days) = train_days {
+                    let wf = WalkForwardConfig {
+                        train_days: *train_days,
+                        test_days: *test_days,
+                        step_days: step_days.unwrap_or(*test_days),
+                        mode: if *anchored { WindowMode::Anchored } else { WindowMode::Rolling },
+                    };
+                    let windows = run_walk_forward_optimized(&market_data, &wf, &optimizer)?;
+                    for w in &windows {
+                        println!(
+                            "[{} .. {}) IS {:?} {:.3} | OOS Sharpe {:.2} PnL {:.2} | {}",
+                            w.train_end,
+                            w.test_end,
+                            objective,
+                            w.in_sample_score,
+                            w.report.sharpe,
+                            w.report.ending_balance - w.report.starting_balance,
+                            w.params
+                        );
+                    }
+                    println!("{} walk-forward windows", windows.len());
+                } else if let Some(folds) = folds {
+                    let kfold = PurgedKFoldConfig {
+                        folds: *folds,
+                        purge_secs: embargo_hours * 3_600,
+                        embargo_secs: embargo_hours * 3_600,
+                    };
+                    for f in run_purged_kfold(&market_data, &kfold, &optimizer)? {
+                        println!(
+                            "[{} .. {}] IS {:?} {:.3} | OOS Sharpe {:.2} PnL {:.2} | {}",
+                            f.test_start,
+                            f.test_end,
+                            objective,
+                            f.in_sample_score,
+                            f.report.sharpe,
+                            f.report.ending_balance - f.report.starting_balance,
+                            f.params
+                        );
+                    }
+                } else {
+                    let result = optimizer.optimize(&market_data)?;
+                    for (params, score) in result.trials.iter().take(10) {
+                        println!("{score:>10.4}  {params}");
+                    }
+                    let best = &result.best;
+                    println!("🏆 Best {:?} {:.4}: {}", objective, best.score, best.params);
+                    best.report.print();
+                }
+                return Ok(());
+            }
+            | Command::Robustness { report, iterations, seed, method, ruin_drawdown, output } => {
+                use algotraderv2::backtest::robustness::{analyze, RobustnessConfig};
+                use algotraderv2::backtest::BacktestReport;
+
+                let file = std::fs::File::open(report).with_context(|| format!("open {report}"))?;
+                let bt: BacktestReport = serde_json::from_reader(std::io::BufReader::new(file))
+                    .context("not a backtest report")?;
+                let cfg = RobustnessConfig {
+                    iterations: *iterations,
+                    seed: *seed,
+                    ruin_drawdown: *ruin_drawdown,
+                    ..Default::default()
+                };
+                let methods =
+                    if method.is_empty() { RobustnessMethod::ALL.to_vec() } else { method.clone() };
+                let analysis = analyze(&bt, &methods, &cfg)?;
+                analysis.print();
+                if let Some(out) = output {
+                    std::fs::write(out, serde_json::to_string_pretty(&analysis)?)?;
+                }
+                return Ok(());
+            }
+            | Command::Batch {
+                data,
+                strategies,
+                params,
+                symbols,
+                objective,
+                timeframe,
+                fee_bps,
+                output,
+            } => {
+                use algotraderv2::backtest::batch::BatchRunner;
+                use algotraderv2::backtest::providers::CSVHistoricalDataProvider;
+                use algotraderv2::backtest::HistoricalDataProvider;
+                use algotraderv2::strategies::StrategyConfig;
+
+                let spaces: serde_json::Value =
+                    serde_json::from_str(params).context("--params must be JSON")?;
+                let configs = strategies
+                    .iter()
+                    .map(|name| StrategyConfig {
+                        name: name.clone(),
+                        enabled: true,
+                        params: spaces.get(name).cloned().unwrap_or(serde_json::Value::Null),
+                        performance: None,
+                    })
+                    .collect();
+                let runner = BatchRunner {
+                    symbols: symbols.clone(),
+                    timeframe: timeframe.clone().unwrap_or_else(|| "default".into()),
+                    fee_bps: *fee_bps,
+                    ..BatchRunner::new(configs, *objective)
+                };
+                let paths: Vec<std::path::PathBuf> = data.iter().map(Into::into).collect();
+                let market_data = CSVHistoricalDataProvider::new().load_many(&paths)?;
+                let board = runner.run(&market_data)?;
+                board.print(20);
+                board.write_csv(format!("{output}.csv"))?;
+                board.write_json(format!("{output}.json"))?;
+                println!("📄 Leaderboard written to {output}.csv / {output}.json");
+                return Ok(());
+            }
+            | Command::Lookahead { data, strategy, params } => {
+                use algotraderv2::backtest::lookahead::{check, check_all, default_params};
+                use algotraderv2::backtest::providers::CSVHistoricalDataProvider;
+                use algotraderv2::backtest::HistoricalDataProvider;
+
+                let paths: Vec<std::path::PathBuf> = data.iter().map(Into::into).collect();
+                let market_data = CSVHistoricalDataProvider::new().load_many(&paths)?;
+                let reports = match strategy {
+                    | Some(name) => {
+                        let overrides: serde_json::Value =
+                            serde_json::from_str(params).context("--params must be JSON")?;
+                        let symbol = market_data.first().map(|md| md.symbol.clone());
+                        let params =
+                            default_params(name, &symbol.unwrap_or_default(), &overrides);
+                        vec![check(name, &params, &market_data).await?]
+                    }
+                    | None => check_all(&market_data).await?,
+                };
+                let mut biased = 0;
+                for r in &reports {
+                    match r.mismatches.first() {
+                        | None => println!("✅ {:<20} {} signals", r.strategy, r.signals),
+                        | Some(m) => {
+                            biased += 1;
+                            println!(
+                                "❌ {:<20} {} of {} bars differ, first at {}",
+                                r.strategy,
+                                r.mismatches.len(),
+                                r.bars,
+                                m.timestamp
+                            );
+                        }
+                    }
+                }
+                if biased > 0 {
+                    anyhow::bail!("{biased} strategies use future data");
+                }
+                return Ok(());
+            }
+            | Command::Cache { action } => {
+                use algotraderv2::backtest::cache::{cache_dir, BacktestCache, PruneFilter};
+
+                let cache = BacktestCache::open_default().context("open backtest cache")?;
+                match action {
+                    | CacheAction::List => {
+                        let entries = cache.entries()?;
+                        for e in &entries {
+                            println!(
+                                "{}  {}  {:<24} {:<20} {:<4} {} trades  Sharpe {:>6.2}  v{}",
+                                &e.key[..12.min(e.key.len())],
+                                chrono::DateTime::<chrono::Utc>::from_timestamp(e.created_at, 0)
+                                    .map(|t| t.format("%Y-%m-%d %H:%M").to_string())
+                                    .unwrap_or_default(),
+                                e.strategy,
+                                e.symbols,
+                                e.timeframe,
+                                e.report.total_trades,
+                                e.report.sharpe,
+                                e.version
+                            );
+                        }
+                        println!("{} entries in {}", entries.len(), cache_dir()?.display());
+                    }
+                    | CacheAction::Inspect { key } => {
+                        let found = cache.find(key)?;
+                        if found.is_empty() {
+                            anyhow::bail!("no cache entry starts with {key}");
+                        }
+                        println!("{}", serde_json::to_string_pretty(&found)?);
+                    }
+                    | CacheAction::Prune { older_than_days, other_versions, all } => {
+                        let filter = PruneFilter {
+                            older_than: older_than_days
+                                .map(|d| chrono::Utc::now().timestamp() - d * 86_400),
+                            other_versions: *other_versions,
+                            all: *all,
+                        };
+                        println!("🧹 Removed {} cache entries", cache.prune(filter)?);
+                    }
+                }
+                return Ok(());
+            }
             | Command::Import { base, quote, timeframe, limit, wallet, out_dir } => {
                 use algotraderv2::blockchain::wallet_scanner::get_wallet_token_symbols;
                 use std::path::PathBuf;
@@ -286,7 +688,7 @@ async fn run_service(config: &Config, paper: bool) -> Result<()> {
     let symbols = vec![config.trading.default_pair.clone()];
     // Spawn the async trading loop – runs until cancelled
     let engine_handle = tokio::spawn(async move {
-        if let Err(e) = engine.start_with_market_router(symbols, None, None).await {
+        if let Err(e) = engine.start_with_market_router(symbols).await {
             log::error!("TradingEngine exited with error: {e}");
         }
     });
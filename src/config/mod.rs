@@ -1,12 +1,14 @@
 //! Configuration module for the trading bot
 
 pub mod position_sizer;
+pub mod risk_rules;
 mod template;
 
 use crate::utils::error::{Error, Result};
 use aes_gcm::aead::Aead;
 use aes_gcm::{Aes256Gcm, KeyInit, Nonce};
 use position_sizer::PositionSizerConfig;
+use risk_rules::{BreakEvenConfig, TakeProfitRung, TrailingStopConfig};
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 use solana_sdk::signature::Keypair;
@@ -40,6 +42,14 @@ pub struct Config {
     /// Optional sidecar (Python ML) integration
     #[serde(default)]
     pub sidecar: Option<SidecarConfig>,
+
+    /// Live market data sources
+    #[serde(default)]
+    pub market_data: MarketDataConfig,
+
+    /// Backtest-only settings
+    #[serde(default)]
+    pub backtest: BacktestConfig,
 }
 
 /// Solana RPC configuration
@@ -113,9 +123,84 @@ pub struct TradingConfig {
     /// Maximum random delay (in ms) between split chunks. Default 1200 ms.
     #[serde(default = "default_split_delay_ms")]
     pub split_delay_ms: u64,
+
+    /// Simulated venue used when paper trading
+    #[serde(default)]
+    pub paper: PaperTradingConfig,
+
+    /// Seconds between checkpoints of the strategies' runtime state; 0 disables them.
+    /// Default 60.
+    #[serde(default = "default_state_checkpoint_secs")]
+    pub state_checkpoint_secs: u64,
+
+    /// Historical bars replayed through the strategies before live trading starts
+    #[serde(default)]
+    pub warmup: WarmupConfig,
     // ---------- helper defaults below ----------
 }
 
+/// Settings for the simulated DEX that backs paper trading
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PaperTradingConfig {
+    /// RNG seed for reproducible fills and transaction signatures
+    #[serde(default)]
+    pub seed: u64,
+    /// LP fee charged by simulated pools (basis points). Default 30.
+    #[serde(default = "default_paper_fee_bps")]
+    pub fee_bps: u16,
+    /// Quote-side liquidity of each simulated pool. Default 1,000,000.
+    #[serde(default = "default_paper_pool_depth")]
+    pub pool_depth_quote: f64,
+}
+
+impl Default for PaperTradingConfig {
+    fn default() -> Self {
+        Self {
+            seed: 0,
+            fee_bps: default_paper_fee_bps(),
+            pool_depth_quote: default_paper_pool_depth(),
+        }
+    }
+}
+
+/// Indicator warm-up on live start. Bars come from `data_dir` when it holds
+/// `BASE_QUOTE_<tf>.csv` files (as written by the importer), else from the remote providers.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WarmupConfig {
+    /// Default true
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// Bars per symbol and timeframe. Default 200.
+    #[serde(default = "default_warmup_bars")]
+    pub bars: usize,
+    /// Local data store searched before the network
+    #[serde(default)]
+    pub data_dir: Option<String>,
+    /// Fetch from Birdeye / CryptoCompare when no local file exists. Default true.
+    #[serde(default = "default_true")]
+    pub remote: bool,
+}
+
+impl Default for WarmupConfig {
+    fn default() -> Self {
+        Self { enabled: true, bars: default_warmup_bars(), data_dir: None, remote: true }
+    }
+}
+
+fn default_warmup_bars() -> usize {
+    200
+}
+fn default_true() -> bool {
+    true
+}
+
+fn default_paper_fee_bps() -> u16 {
+    30
+}
+fn default_paper_pool_depth() -> f64 {
+    1_000_000.0
+}
+
 /// Sidecar (Python ML) configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SidecarConfig {
@@ -130,6 +215,163 @@ pub struct SidecarConfig {
     pub weight: f64,
 }
 
+/// Live market data streams and their supervision
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MarketDataConfig {
+    /// `[[market_data.sources]]` entries. When empty, the keyless Binance, Coinbase and Kraken
+    /// feeds are used for the traded symbols.
+    #[serde(default)]
+    pub sources: Vec<MarketDataSource>,
+    /// Reconnect a source that delivered nothing for this many seconds; 0 disables. Default 60.
+    #[serde(default = "default_stale_after_secs")]
+    pub stale_after_secs: u64,
+    /// Upper bound of the reconnect backoff in seconds. Default 60.
+    #[serde(default = "default_max_backoff_secs")]
+    pub max_backoff_secs: u64,
+}
+
+impl Default for MarketDataConfig {
+    fn default() -> Self {
+        Self {
+            sources: Vec::new(),
+            stale_after_secs: default_stale_after_secs(),
+            max_backoff_secs: default_max_backoff_secs(),
+        }
+    }
+}
+
+/// Stream implementations a source can use
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MarketDataSourceKind {
+    Binance,
+    Coinbase,
+    Kraken,
+    /// One OpenBook relayer stream per market
+    Serum,
+    /// One stream per market; needs an API key
+    Triton,
+    /// On-chain program updates; needs an API key
+    Helius,
+}
+
+/// One `[[market_data.sources]]` entry
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MarketDataSource {
+    #[serde(rename = "type")]
+    pub kind: MarketDataSourceKind,
+    /// Default true
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    #[serde(default)]
+    pub api_key: Option<String>,
+    /// Environment variable holding the API key, read when `api_key` is not set
+    #[serde(default)]
+    pub api_key_env: Option<String>,
+    /// Engine symbol to venue symbol, e.g. `"SOL/USDC" = "SOLUSDC"`. When set, only mapped
+    /// symbols are subscribed; when empty, symbols are passed through unchanged.
+    #[serde(default)]
+    pub symbols: std::collections::BTreeMap<String, String>,
+    /// Program filter for Helius, e.g. the OpenBook program id
+    #[serde(default)]
+    pub program_id: Option<String>,
+}
+
+impl MarketDataSource {
+    /// `api_key`, else the value of `api_key_env`
+    pub fn resolve_api_key(&self) -> Option<String> {
+        self.api_key
+            .clone()
+            .or_else(|| self.api_key_env.as_ref().and_then(|var| env::var(var).ok()))
+            .filter(|k| !k.trim().is_empty())
+    }
+
+    /// Venue symbols for the traded `symbols`, paired with the engine symbol they map from
+    pub fn venue_symbols(&self, symbols: &[String]) -> Vec<(String, String)> {
+        symbols
+            .iter()
+            .filter_map(|s| {
+                if self.symbols.is_empty() {
+                    return Some((s.clone(), s.clone()));
+                }
+                self.symbols.get(s).map(|venue| (s.clone(), venue.clone()))
+            })
+            .collect()
+    }
+}
+
+fn default_stale_after_secs() -> u64 {
+    60
+}
+fn default_max_backoff_secs() -> u64 {
+    60
+}
+
+/// `[backtest]` section
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BacktestConfig {
+    /// `[backtest.fill_model]`; instant fills when absent
+    #[serde(default)]
+    pub fill_model: FillModelConfig,
+}
+
+/// How backtest orders execute
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum FillModelConfig {
+    /// Every order fills in full at the signal price plus the flat slippage
+    #[default]
+    Instant,
+    /// Latency, limit/stop triggers, volume caps and market impact
+    Realistic {
+        #[serde(default = "default_latency_bars")]
+        latency_bars: usize,
+        /// Time in force of resting limit orders in bars; 0 keeps them until the end of the run
+        #[serde(default = "default_expiry_bars")]
+        expiry_bars: usize,
+        /// Maximum fraction of a bar's volume one order may take; 0 disables the cap
+        #[serde(default = "default_participation_rate")]
+        participation_rate: f64,
+        /// Square-root impact coefficient
+        #[serde(default = "default_impact_coef")]
+        impact_coef: f64,
+    },
+}
+
+impl FillModelConfig {
+    /// Fill model described by this config
+    pub fn build(&self) -> Box<dyn crate::backtest::fill_model::FillModel> {
+        use crate::backtest::fill_model::*;
+        match self {
+            | FillModelConfig::Instant => Box::new(InstantFillModel),
+            | FillModelConfig::Realistic {
+                latency_bars,
+                expiry_bars,
+                participation_rate,
+                impact_coef,
+            } => Box::new(RealisticFillModel {
+                latency_bars: *latency_bars,
+                expiry_bars: (*expiry_bars > 0).then_some(*expiry_bars),
+                participation_rate: *participation_rate,
+                impact_coef: *impact_coef,
+            }),
+        }
+    }
+}
+
+fn default_latency_bars() -> usize {
+    1
+}
+fn default_expiry_bars() -> usize {
+    crate::backtest::fill_model::DEFAULT_EXPIRY_BARS
+}
+fn default_participation_rate() -> f64 {
+    0.1
+}
+fn default_impact_coef() -> f64 {
+    0.1
+}
+
 fn default_sidecar_endpoint() -> String {
     "http://127.0.0.1:8000".to_string()
 }
@@ -164,6 +406,142 @@ pub struct RiskConfig {
     /// Optional position sizer configuration
     #[serde(default)]
     pub position_sizer: Option<PositionSizerConfig>,
+
+    /// Cap on a single order in base units (uncapped when absent)
+    #[serde(default)]
+    pub max_position_abs: Option<f64>,
+
+    /// Portfolio-wide pre-trade limits (disabled when absent)
+    #[serde(default)]
+    pub portfolio: Option<PortfolioRiskConfig>,
+
+    /// Trailing stop (disabled when absent)
+    #[serde(default)]
+    pub trailing_stop: Option<TrailingStopConfig>,
+
+    /// Close positions held longer than this many hours
+    #[serde(default)]
+    pub max_holding_hours: Option<f64>,
+
+    /// Break-even stop (disabled when absent)
+    #[serde(default)]
+    pub break_even: Option<BreakEvenConfig>,
+
+    /// Partial take-profit ladder (empty = disabled)
+    #[serde(default)]
+    pub take_profit_ladder: Vec<TakeProfitRung>,
+}
+
+impl RiskConfig {
+    /// Exit rules described by this config, shared by the live engine and backtests
+    pub fn build_rules(&self) -> Vec<Box<dyn crate::risk::RiskRule>> {
+        use crate::risk::*;
+        let mut rules: Vec<Box<dyn RiskRule>> = Vec::new();
+        if self.stop_loss_enabled {
+            rules.push(Box::new(StopLossRule::new(self.default_stop_loss_pct / 100.0)));
+        }
+        if let Some(trailing) = &self.trailing_stop {
+            let distance = match trailing {
+                | TrailingStopConfig::Percent { pct } => TrailDistance::Percent(pct / 100.0),
+                | TrailingStopConfig::Atr { multiplier } => TrailDistance::Atr(*multiplier),
+            };
+            rules.push(Box::new(TrailingStopRule::new(distance)));
+        }
+        if let Some(be) = &self.break_even {
+            let rule = BreakEvenRule::new(be.trigger_pct / 100.0, be.offset_pct / 100.0);
+            rules.push(Box::new(rule));
+        }
+        if let Some(hours) = self.max_holding_hours {
+            rules.push(Box::new(MaxHoldingRule::new((hours * 3_600.0) as i64)));
+        }
+        if !self.take_profit_ladder.is_empty() {
+            let rungs = self
+                .take_profit_ladder
+                .iter()
+                .map(|r| (r.gain_pct / 100.0, r.close_pct / 100.0))
+                .collect();
+            rules.push(Box::new(TakeProfitLadderRule::new(rungs)));
+        }
+        if self.default_take_profit_pct > 0.0 {
+            rules.push(Box::new(TakeProfitRule::new(self.default_take_profit_pct / 100.0)));
+        }
+        rules
+    }
+
+    /// Position sizer described by `position_sizer`, 1% fixed-fractional when unset
+    pub fn build_position_sizer(&self) -> Box<dyn crate::risk::position_sizer::PositionSizer> {
+        use crate::risk::position_sizer::*;
+        match &self.position_sizer {
+            | Some(PositionSizerConfig::FixedFractional { pct }) => {
+                Box::new(FixedFractionalSizer::new(*pct))
+            }
+            | Some(PositionSizerConfig::Kelly { win_rate, payoff_ratio, cap }) => {
+                Box::new(KellySizer::new(*win_rate, *payoff_ratio, *cap))
+            }
+            | Some(PositionSizerConfig::KellyLive { cap }) => {
+                let pm = std::sync::Arc::new(crate::performance::PerformanceMonitor::new());
+                Box::new(LiveKellySizer::new(*cap, pm))
+            }
+            | Some(PositionSizerConfig::Volatility { risk_pct, atr_mult }) => {
+                // Latest ATR published by the strategies
+                let fetcher = |sym: &str| -> Option<f64> { crate::utils::atr_cache::get(sym) };
+                Box::new(VolatilitySizer::new(*risk_pct, *atr_mult, fetcher))
+            }
+            | None => Box::new(FixedFractionalSizer::new(0.01)),
+        }
+    }
+}
+
+/// Pre-trade limits on the whole portfolio, in percent of equity. Unset limits are off.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PortfolioRiskConfig {
+    /// Sum of absolute position values
+    #[serde(default)]
+    pub max_gross_exposure_pct: Option<f64>,
+    /// Absolute value of the summed (signed) position values
+    #[serde(default)]
+    pub max_net_exposure_pct: Option<f64>,
+    /// Exposure to one token across all of its pairs
+    #[serde(default)]
+    pub max_token_concentration_pct: Option<f64>,
+    /// Exposure to the traded asset plus every asset correlated with it above
+    /// `correlation_threshold`, estimated from recent price returns
+    #[serde(default)]
+    pub max_correlated_exposure_pct: Option<f64>,
+    /// Correlation from which two assets count as one exposure. Default 0.7.
+    #[serde(default = "default_correlation_threshold")]
+    pub correlation_threshold: f64,
+    /// Returns kept per symbol to estimate correlations. Default 100.
+    #[serde(default = "default_correlation_window")]
+    pub correlation_window: usize,
+    /// Orders that would shrink below this percentage of their size are rejected instead of
+    /// resized. Default 10.
+    #[serde(default = "default_min_resize_pct")]
+    pub min_resize_pct: f64,
+}
+
+impl Default for PortfolioRiskConfig {
+    fn default() -> Self {
+        Self {
+            max_gross_exposure_pct: None,
+            max_net_exposure_pct: None,
+            max_token_concentration_pct: None,
+            max_correlated_exposure_pct: None,
+            correlation_threshold: default_correlation_threshold(),
+            correlation_window: default_correlation_window(),
+            min_resize_pct: default_min_resize_pct(),
+        }
+    }
+}
+
+fn default_correlation_threshold() -> f64 {
+    0.7
+}
+fn default_correlation_window() -> usize {
+    100
+}
+fn default_min_resize_pct() -> f64 {
+    10.0
 }
 
 /// Wallet configuration
@@ -214,6 +592,8 @@ impl Default for Config {
             wallet: WalletConfig::default(),
             performance: PerformanceConfig::default(),
             sidecar: None,
+            market_data: MarketDataConfig::default(),
+            backtest: BacktestConfig::default(),
         }
     }
 }
@@ -248,6 +628,9 @@ impl Default for TradingConfig {
             split_chunk_sol: default_split_chunk_sol(),
             split_delay_ms: default_split_delay_ms(),
             starting_balance_usd: default_starting_balance_usd(),
+            paper: PaperTradingConfig::default(),
+            state_checkpoint_secs: default_state_checkpoint_secs(),
+            warmup: WarmupConfig::default(),
         }
     }
 }
@@ -271,6 +654,9 @@ fn default_split_delay_ms() -> u64 {
 fn default_starting_balance_usd() -> f64 {
     1000.0
 }
+fn default_state_checkpoint_secs() -> u64 {
+    60
+}
 
 impl Default for RiskConfig {
     fn default() -> Self {
@@ -283,6 +669,12 @@ impl Default for RiskConfig {
             default_stop_loss_pct: 5.0,
             default_take_profit_pct: 10.0,
             position_sizer: None,
+            max_position_abs: None,
+            portfolio: None,
+            trailing_stop: None,
+            max_holding_hours: None,
+            break_even: None,
+            take_profit_ladder: Vec::new(),
         }
     }
 }
@@ -327,6 +719,15 @@ impl Config {
         Ok(cfg)
     }
 
+    /// Per-signal limits: `max_open_positions`, `daily_loss_limit_pct` and `max_position_abs`
+    pub fn signal_limits(&self) -> crate::risk::SignalLimits {
+        crate::risk::SignalLimits {
+            max_position_abs: self.risk.max_position_abs.unwrap_or(f64::INFINITY),
+            max_open_trades: self.trading.max_open_positions,
+            max_daily_loss_pct: self.risk.daily_loss_limit_pct / 100.0,
+        }
+    }
+
     /// Save the configuration to a file
     pub fn save<P: AsRef<std::path::Path>>(&self, path: P) -> Result<()> {
         let path = path.as_ref();
@@ -566,4 +967,36 @@ mod tests {
             },
         );
     }
+
+    #[test]
+    fn test_market_data_sources() {
+        let toml = r#"
+            [[sources]]
+            type = "binance"
+            symbols = { "SOL/USDC" = "SOLUSDC" }
+
+            [[sources]]
+            type = "triton"
+            enabled = false
+            api_key_env = "TEST_TRITON_KEY"
+        "#;
+        let cfg: MarketDataConfig = toml::from_str(toml).unwrap();
+        assert_eq!(cfg.stale_after_secs, 60);
+        let binance = &cfg.sources[0];
+        assert!(binance.enabled);
+        let traded = vec!["SOL/USDC".to_string(), "BONK/USDC".to_string()];
+        assert_eq!(
+            binance.venue_symbols(&traded),
+            vec![("SOL/USDC".to_string(), "SOLUSDC".to_string())]
+        );
+        let triton = &cfg.sources[1];
+        assert_eq!(triton.kind, MarketDataSourceKind::Triton);
+        assert_eq!(triton.venue_symbols(&traded).len(), 2);
+        temp_env::with_var("TEST_TRITON_KEY", Some("k"), || {
+            assert_eq!(triton.resolve_api_key().as_deref(), Some("k"));
+        });
+        temp_env::with_var("TEST_TRITON_KEY", None::<&str>, || {
+            assert_eq!(triton.resolve_api_key(), None);
+        });
+    }
 }
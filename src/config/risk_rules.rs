@@ -0,0 +1,31 @@
+//! Exit-rule configuration structs for serde deserialization.
+use serde::{Deserialize, Serialize};
+
+/// Trailing stop distance below the high-water mark
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TrailingStopConfig {
+    /// Percent of the high-water mark (0-100)
+    Percent { pct: f64 },
+    /// Multiple of the symbol's latest ATR
+    Atr { multiplier: f64 },
+}
+
+/// Move the stop to break-even once the position is `trigger_pct` in profit
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BreakEvenConfig {
+    /// Gain over entry (0-100) that arms the rule
+    pub trigger_pct: f64,
+    /// Stop level above entry (0-100), e.g. to cover fees
+    #[serde(default)]
+    pub offset_pct: f64,
+}
+
+/// One rung of a partial take-profit ladder
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TakeProfitRung {
+    /// Gain over entry (0-100) at which the rung fires
+    pub gain_pct: f64,
+    /// Share of the initial position to sell (0-100)
+    pub close_pct: f64,
+}
@@ -93,6 +93,23 @@ default_stop_loss_pct = 5.0
 # Default take profit percentage (0-100)
 default_take_profit_pct = 10.0
 
+# Cap on a single order in base units (uncapped when absent)
+# max_position_abs = 5.0
+
+# Optional exit rules
+# max_holding_hours = 48.0
+# trailing_stop = { type = "percent", pct = 3.0 }   # or { type = "atr", multiplier = 2.0 }
+# break_even = { trigger_pct = 2.0, offset_pct = 0.2 }
+# take_profit_ladder = [{ gain_pct = 3.0, close_pct = 50.0 }, { gain_pct = 6.0, close_pct = 25.0 }]
+
+# Portfolio-wide pre-trade limits in percent of equity (uncomment to enable)
+# [risk.portfolio]
+# max_gross_exposure_pct = 100.0
+# max_net_exposure_pct = 100.0
+# max_token_concentration_pct = 40.0
+# max_correlated_exposure_pct = 60.0
+# correlation_threshold = 0.7
+
 [performance]
 # Enable/disable performance tracking
 enabled = true
@@ -105,6 +122,34 @@ max_data_points = 10000
 
 # Enable/disable detailed logging
 detailed_logging = true
+
+[market_data]
+# Reconnect a source that delivered nothing for this many seconds (0 disables)
+stale_after_secs = 60
+
+# Upper bound of the reconnect backoff in seconds
+max_backoff_secs = 60
+
+# Live market data sources. Without any, the keyless Binance, Coinbase and Kraken feeds are used.
+# type is one of binance, coinbase, kraken, serum, triton, helius; symbols maps the engine symbol
+# to the venue symbol and limits the subscription to the mapped symbols.
+# [[market_data.sources]]
+# type = "binance"
+# symbols = { "SOL/USDC" = "SOLUSDC" }
+#
+# [[market_data.sources]]
+# type = "triton"
+# api_key_env = "TRITON_API_KEY"
+# enabled = false
+
+# Backtest execution. type = "instant" fills every order in full at the signal price;
+# "realistic" adds latency, limit/stop triggers, volume caps and market impact.
+# [backtest.fill_model]
+# type = "realistic"
+# latency_bars = 1
+# expiry_bars = 24          # 0 keeps resting limit orders until the end of the run
+# participation_rate = 0.1  # max fraction of a bar's volume per fill, 0 disables the cap
+# impact_coef = 0.1
 "#;
 
     // Create parent directories if they don't exist
@@ -4,14 +4,20 @@ use crate::Result;
 use async_trait::async_trait;
 use reqwest::Client;
 use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::Mutex;
 use std::time::Duration;
 
+use crate::wallet::WSOL_MINT;
+
 const JUPITER_API_BASE: &str = "https://quote-api.jup.ag/v6";
 
 /// Client for interacting with the Jupiter DEX
 pub struct JupiterClient {
     client: Client,
     api_key: Option<String>,
+    /// Executed price of our swaps by transaction signature, until the engine picks it up
+    fills: Mutex<HashMap<String, f64>>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -60,14 +66,14 @@ impl JupiterClient {
     pub fn new() -> Result<Self> {
         let client = Client::builder().timeout(Duration::from_secs(30)).build()?;
 
-        Ok(Self { client, api_key: None })
+        Ok(Self { client, api_key: None, fills: Mutex::new(HashMap::new()) })
     }
 
     /// Create a new Jupiter client with an API key
     pub fn with_api_key(api_key: String) -> Result<Self> {
         let client = Client::builder().timeout(Duration::from_secs(30)).build()?;
 
-        Ok(Self { client, api_key: Some(api_key) })
+        Ok(Self { client, api_key: Some(api_key), fills: Mutex::new(HashMap::new()) })
     }
 
     async fn get_quote(
@@ -223,9 +229,23 @@ impl super::DexClient for JupiterClient {
             )
             .await?;
         let sig = wallet.sign_and_send_serialized_tx(&tx_b64).await?;
+        // book the fill at what the swap actually moved, not at the quote or the mid
+        match wallet.balance_changes(&sig).await {
+            | Ok(changes) => match executed_price(&changes, base_token, quote_token) {
+                | Some(price) => {
+                    self.fills.lock().unwrap().insert(sig.to_string(), price);
+                }
+                | None => log::warn!("Swap {sig} moved no {base_token}/{quote_token} balance"),
+            },
+            | Err(e) => log::warn!("Failed to read the executed amounts of swap {sig}: {e}"),
+        }
         Ok(sig.to_string())
     }
 
+    async fn fill_price(&self, tx_signature: &str) -> Option<f64> {
+        self.fills.lock().unwrap().remove(tx_signature)
+    }
+
     async fn get_balance(&self, token: &str) -> Result<f64> {
         // In a real implementation, we would:
         // 1. Query the user's wallet for the token balance
@@ -236,10 +256,28 @@ impl super::DexClient for JupiterClient {
     }
 }
 
+/// Average price of a swap from the wallet's balance changes: quote paid (or received) per
+/// unit of base received (or paid)
+fn executed_price(changes: &HashMap<String, f64>, base: &str, quote: &str) -> Option<f64> {
+    let moved = |token: &str| {
+        let mint = if token.eq_ignore_ascii_case("SOL") { WSOL_MINT } else { token };
+        changes.get(mint).map(|d| d.abs()).filter(|d| *d > 0.0)
+    };
+    Some(moved(quote)? / moved(base)?)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn executed_price_comes_from_balance_changes() {
+        let usdc = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
+        let changes = HashMap::from([(WSOL_MINT.to_string(), 2.0), (usdc.to_string(), -301.0)]);
+        assert_eq!(executed_price(&changes, "SOL", usdc), Some(150.5));
+        assert_eq!(executed_price(&changes, "SOL", "unknown"), None);
+    }
+
     #[tokio::test]
     async fn test_jupiter_client_initialization() {
         let client = JupiterClient::new();
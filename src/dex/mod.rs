@@ -3,6 +3,7 @@
 mod jupiter;
 mod photon;
 mod raydium;
+pub mod simulated;
 
 use async_trait::async_trait;
 use std::collections::HashMap;
@@ -10,6 +11,7 @@ use std::collections::HashMap;
 pub use jupiter::JupiterClient;
 pub use photon::PhotonClient;
 pub use raydium::RaydiumClient;
+pub use simulated::SimulatedDex;
 
 /// Trait defining the common interface for all DEX clients
 #[async_trait]
@@ -31,6 +33,11 @@ pub trait DexClient: Send + Sync {
 
     /// Get the current balance of a token
     async fn get_balance(&self, token: &str) -> crate::Result<f64>;
+
+    /// Average execution price of a confirmed transaction, when the venue reports one
+    async fn fill_price(&self, _tx_signature: &str) -> Option<f64> {
+        None
+    }
 }
 
 /// Factory for creating DEX clients
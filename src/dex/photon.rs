@@ -172,8 +172,8 @@ impl super::DexClient for PhotonClient {
         // 3. Send it to the network
         // 4. Return the transaction signature
 
-        // For now, return a placeholder
-        Ok("tx_signature_placeholder".to_string())
+        // Until then nothing is sent; a placeholder signature would be booked as a fill
+        Err(crate::Error::DexError("Photon swap execution is not implemented".into()))
     }
 
     async fn get_balance(&self, _token: &str) -> Result<f64> {
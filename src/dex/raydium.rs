@@ -127,9 +127,10 @@ impl super::DexClient for RaydiumClient {
             }
         }
 
-        // TODO: Implement actual trade execution logic here. Placeholder for now.
+        // TODO: Implement actual trade execution logic here. Until then nothing is sent; a
+        // placeholder signature would be booked as a fill.
         let _ = (amount, is_buy, slippage_bps, max_fee_lamports, _wallet); // suppress unused warnings
-        Ok("tx_signature_placeholder".to_string())
+        Err(crate::Error::DexError("Raydium swap execution is not implemented".into()))
     }
 
     async fn get_balance(&self, _token: &str) -> Result<f64> {
@@ -0,0 +1,327 @@
+//! Simulated DEX backed by constant-product (x*y=k) pools.
+//!
+//! Paper trading and offline tests route orders through this client so they exercise the same
+//! code path as live execution, including slippage, LP fees and price impact.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use async_trait::async_trait;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::market_data::ws::PriceCache;
+use crate::utils::types::{OrderType, TradingPair};
+use crate::Result;
+
+/// Liquidity pool holding `base_reserve` against `quote_reserve`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Pool {
+    pub base_reserve: f64,
+    pub quote_reserve: f64,
+}
+
+impl Pool {
+    /// Pool seeded at `price` with `depth_quote` of quote liquidity
+    pub fn at_price(price: f64, depth_quote: f64) -> Self {
+        Self { base_reserve: depth_quote / price, quote_reserve: depth_quote }
+    }
+
+    /// Marginal price (quote per base)
+    pub fn spot_price(&self) -> f64 {
+        self.quote_reserve / self.base_reserve
+    }
+
+    /// Move reserves along the curve until the marginal price equals `price`, the way
+    /// arbitrageurs keep a real pool in line with the wider market.
+    pub fn recenter(&mut self, price: f64) {
+        let k = self.base_reserve * self.quote_reserve;
+        self.base_reserve = (k / price).sqrt();
+        self.quote_reserve = (k * price).sqrt();
+    }
+
+    /// Quote paid to receive `base_out`, with the LP fee charged on the input.
+    /// Returns `(quote_in, quote_in_excluding_fee)`.
+    fn quote_for_buy(&self, base_out: f64, fee: f64) -> Option<(f64, f64)> {
+        if base_out <= 0.0 || base_out >= self.base_reserve {
+            return None;
+        }
+        let net_in = self.quote_reserve * base_out / (self.base_reserve - base_out);
+        Some((net_in / (1.0 - fee), net_in))
+    }
+
+    /// Quote received for `base_in`, with the LP fee charged on the input.
+    /// Returns `(quote_out, quote_out_without_fee)`.
+    fn quote_for_sell(&self, base_in: f64, fee: f64) -> Option<(f64, f64)> {
+        if base_in <= 0.0 {
+            return None;
+        }
+        let out = |inp: f64| self.quote_reserve * inp / (self.base_reserve + inp);
+        Some((out(base_in * (1.0 - fee)), out(base_in)))
+    }
+}
+
+/// Record of a simulated swap
+#[derive(Debug, Clone)]
+pub struct SimulatedFill {
+    pub tx_signature: String,
+    pub pair: TradingPair,
+    pub is_buy: bool,
+    pub base_qty: f64,
+    pub quote_qty: f64,
+    /// Average execution price including fees (quote per base)
+    pub avg_price: f64,
+    /// Spot price before the swap
+    pub spot_before: f64,
+    /// Fee paid in quote units
+    pub fee_paid: f64,
+    /// Relative price impact of the swap, excluding fees
+    pub price_impact: f64,
+}
+
+struct SimState {
+    pools: HashMap<TradingPair, Pool>,
+    balances: HashMap<String, f64>,
+    fills: HashMap<String, SimulatedFill>,
+    rng: StdRng,
+}
+
+/// [`DexClient`](super::DexClient) that settles trades against in-memory AMM pools
+pub struct SimulatedDex {
+    state: Mutex<SimState>,
+    fee_bps: u16,
+    /// Quote liquidity used when a pool is created lazily from the price source
+    default_depth_quote: f64,
+    price_source: Option<PriceCache>,
+}
+
+impl SimulatedDex {
+    /// Deterministic simulator: the same seed yields the same transaction signatures
+    pub fn new(seed: u64) -> Self {
+        Self {
+            state: Mutex::new(SimState {
+                pools: HashMap::new(),
+                balances: HashMap::new(),
+                fills: HashMap::new(),
+                rng: StdRng::seed_from_u64(seed),
+            }),
+            fee_bps: 30,
+            default_depth_quote: 1_000_000.0,
+            price_source: None,
+        }
+    }
+
+    /// LP fee charged on every swap (default 30 bps)
+    pub fn with_fee_bps(mut self, fee_bps: u16) -> Self {
+        self.fee_bps = fee_bps;
+        self
+    }
+
+    /// Quote-side depth for pools created from the price source (default 1M)
+    pub fn with_default_depth(mut self, depth_quote: f64) -> Self {
+        self.default_depth_quote = depth_quote;
+        self
+    }
+
+    /// Create and re-center pools from live prices (paper trading)
+    pub fn with_price_source(mut self, prices: PriceCache) -> Self {
+        self.price_source = Some(prices);
+        self
+    }
+
+    /// Register a pool with explicit reserves
+    pub fn with_pool(self, base: &str, quote: &str, base_reserve: f64, quote_reserve: f64) -> Self {
+        self.set_pool(base, quote, Pool { base_reserve, quote_reserve });
+        self
+    }
+
+    /// Seed a token balance reported by `get_balance`
+    pub fn with_balance(self, token: &str, amount: f64) -> Self {
+        self.state.lock().unwrap().balances.insert(token.to_uppercase(), amount);
+        self
+    }
+
+    pub fn set_pool(&self, base: &str, quote: &str, pool: Pool) {
+        self.state.lock().unwrap().pools.insert(TradingPair::new(base, quote), pool);
+    }
+
+    pub fn pool(&self, base: &str, quote: &str) -> Option<Pool> {
+        self.state.lock().unwrap().pools.get(&TradingPair::new(base, quote)).copied()
+    }
+
+    /// Look up a previous simulated swap by its signature; gone once `fill_price` has read it
+    pub fn fill(&self, tx_signature: &str) -> Option<SimulatedFill> {
+        self.state.lock().unwrap().fills.get(tx_signature).cloned()
+    }
+
+    /// Bring the pool for `pair` in line with the price source, creating it if needed
+    async fn sync_pool(&self, pair: &TradingPair) -> Result<()> {
+        let reference = match &self.price_source {
+            | Some(cache) => cache.read().await.get(pair).copied(),
+            | None => None,
+        };
+        let mut guard = self.state.lock().unwrap();
+        let state = &mut *guard;
+        if let Some(pool) = state.pools.get_mut(pair) {
+            if let Some(price) = reference.filter(|p| *p > 0.0) {
+                pool.recenter(price);
+            }
+            return Ok(());
+        }
+        match reference {
+            | Some(price) if price > 0.0 => {
+                state.pools.insert(pair.clone(), Pool::at_price(price, self.default_depth_quote));
+                Ok(())
+            }
+            | _ => Err(crate::Error::DexError(format!("No simulated pool for {}", pair))),
+        }
+    }
+
+    fn fake_signature(rng: &mut StdRng) -> String {
+        let mut bytes = [0u8; 64];
+        rng.fill(&mut bytes[..]);
+        bs58::encode(bytes).into_string()
+    }
+}
+
+#[async_trait]
+impl super::DexClient for SimulatedDex {
+    fn name(&self) -> &'static str {
+        "Simulated"
+    }
+
+    async fn get_price(&self, base_token: &str, quote_token: &str) -> Result<f64> {
+        let pair = TradingPair::new(base_token, quote_token);
+        self.sync_pool(&pair).await?;
+        let state = self.state.lock().unwrap();
+        Ok(state.pools[&pair].spot_price())
+    }
+
+    async fn execute_trade(
+        &self, base_token: &str, quote_token: &str, amount: f64, is_buy: bool, slippage_bps: u16,
+        _max_fee_lamports: u64, order_type: OrderType, limit_price: Option<f64>,
+        stop_price: Option<f64>, _take_profit_price: Option<f64>, _wallet: &crate::wallet::Wallet,
+    ) -> Result<String> {
+        if amount <= 0.0 || !amount.is_finite() {
+            return Err(crate::Error::InvalidArgument("amount must be positive".into()));
+        }
+        let pair = TradingPair::new(base_token, quote_token);
+        self.sync_pool(&pair).await?;
+
+        let mut state = self.state.lock().unwrap();
+        let pool = state.pools[&pair];
+        let spot = pool.spot_price();
+
+        // Trigger / limit semantics mirror the live Jupiter client
+        match order_type {
+            | OrderType::Market => {}
+            | OrderType::Limit => {
+                let lp = limit_price.ok_or_else(|| {
+                    crate::Error::InvalidArgument("limit_price required for Limit order".into())
+                })?;
+                if (is_buy && spot > lp) || (!is_buy && spot < lp) {
+                    return Err(crate::Error::DexError("Limit price not satisfied".into()));
+                }
+            }
+            | OrderType::Stop | OrderType::StopLimit => {
+                let sp = stop_price.ok_or_else(|| {
+                    crate::Error::InvalidArgument("stop_price required for Stop order".into())
+                })?;
+                if (is_buy && spot < sp) || (!is_buy && spot > sp) {
+                    return Err(crate::Error::DexError("Stop price not triggered".into()));
+                }
+                if let (OrderType::StopLimit, Some(lp)) = (order_type, limit_price) {
+                    if (is_buy && spot > lp) || (!is_buy && spot < lp) {
+                        return Err(crate::Error::DexError(
+                            "Limit condition after stop not satisfied".into(),
+                        ));
+                    }
+                }
+            }
+        }
+
+        let fee = self.fee_bps as f64 / 10_000.0;
+        let (quote_qty, quote_no_fee) = if is_buy {
+            pool.quote_for_buy(amount, fee)
+        } else {
+            pool.quote_for_sell(amount, fee)
+        }
+        .ok_or_else(|| crate::Error::DexError("Insufficient pool liquidity".into()))?;
+
+        let price_impact = ((quote_no_fee / amount) - spot).abs() / spot;
+        if price_impact > slippage_bps as f64 / 10_000.0 {
+            return Err(crate::Error::DexError(format!(
+                "Slippage tolerance exceeded: impact {:.2} bps > {} bps",
+                price_impact * 10_000.0,
+                slippage_bps
+            )));
+        }
+
+        let new_pool = if is_buy {
+            Pool {
+                base_reserve: pool.base_reserve - amount,
+                quote_reserve: pool.quote_reserve + quote_qty,
+            }
+        } else {
+            Pool {
+                base_reserve: pool.base_reserve + amount,
+                quote_reserve: pool.quote_reserve - quote_qty,
+            }
+        };
+        state.pools.insert(pair.clone(), new_pool);
+
+        let (base_delta, quote_delta) =
+            if is_buy { (amount, -quote_qty) } else { (-amount, quote_qty) };
+        *state.balances.entry(pair.base.clone()).or_insert(0.0) += base_delta;
+        *state.balances.entry(pair.quote.clone()).or_insert(0.0) += quote_delta;
+
+        let tx_signature = Self::fake_signature(&mut state.rng);
+        let fill = SimulatedFill {
+            tx_signature: tx_signature.clone(),
+            pair,
+            is_buy,
+            base_qty: amount,
+            quote_qty,
+            avg_price: quote_qty / amount,
+            spot_before: spot,
+            fee_paid: (quote_qty - quote_no_fee).abs(),
+            price_impact,
+        };
+        log::debug!("Simulated fill {:?}", fill);
+        state.fills.insert(tx_signature.clone(), fill);
+        Ok(tx_signature)
+    }
+
+    async fn get_balance(&self, token: &str) -> Result<f64> {
+        let state = self.state.lock().unwrap();
+        Ok(state.balances.get(&token.to_uppercase()).copied().unwrap_or(0.0))
+    }
+
+    /// Consumes the fill record: the engine reads each signature once, so fills do not pile up
+    async fn fill_price(&self, tx_signature: &str) -> Option<f64> {
+        self.state.lock().unwrap().fills.remove(tx_signature).map(|f| f.avg_price)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn buys_pay_more_than_spot_and_sells_receive_less() {
+        let pool = Pool::at_price(100.0, 1_000_000.0);
+        let (buy_in, _) = pool.quote_for_buy(10.0, 0.003).unwrap();
+        let (sell_out, _) = pool.quote_for_sell(10.0, 0.003).unwrap();
+        assert!(buy_in / 10.0 > 100.0);
+        assert!(sell_out / 10.0 < 100.0);
+    }
+
+    #[test]
+    fn recenter_preserves_invariant() {
+        let mut pool = Pool::at_price(100.0, 1_000_000.0);
+        let k = pool.base_reserve * pool.quote_reserve;
+        pool.recenter(120.0);
+        assert!((pool.spot_price() - 120.0).abs() < 1e-6);
+        assert!((pool.base_reserve * pool.quote_reserve - k).abs() / k < 1e-9);
+    }
+}
@@ -0,0 +1,283 @@
+//! Candle aggregator that turns live trades into OHLCV bars.
+//!
+//! Bars are built per symbol for every [`TimeFrame`] a strategy declares. Timestamps are in
+//! milliseconds, as on [`MarketEvent`](crate::utils::market_stream::MarketEvent); a bar is
+//! stamped with its open time. A bar closes once a trade at least `lateness_ms` past its end
+//! has been seen, or on [`flush`](CandleAggregator::flush) when the clock passes that point,
+//! so trades arriving slightly out of order still land in the right bar. Trades for a bar that
+//! already closed are dropped. Periods without trades between two bars are filled with flat,
+//! zero-volume bars at the previous close so indicator windows stay aligned in time.
+
+use std::collections::{BTreeMap, HashMap, VecDeque};
+
+use crate::strategies::{TimeFrame, TradingStrategy};
+use crate::utils::types::{Candle, MarketData, TradingPair};
+
+/// Default closed bars kept per symbol and timeframe
+pub const DEFAULT_HISTORY: usize = 500;
+
+/// Default grace period for out-of-order trades
+pub const DEFAULT_LATENESS_MS: i64 = 2_000;
+
+/// A bar that just closed, with the rolling history of its series
+#[derive(Debug, Clone)]
+pub struct ClosedBar {
+    pub timeframe: TimeFrame,
+    /// OHLCV of the bar; `candles` holds the closed history up to and including it
+    pub data: MarketData,
+}
+
+#[derive(Debug, Clone)]
+struct Building {
+    candle: Candle,
+    /// Times of the trades that set the open and the close
+    open_ts: i64,
+    close_ts: i64,
+}
+
+#[derive(Debug, Clone, Default)]
+struct Series {
+    open: BTreeMap<i64, Building>,
+    closed: VecDeque<Candle>,
+    /// Latest trade time seen
+    watermark: i64,
+}
+
+/// Builds bars per symbol and timeframe from a stream of trades
+#[derive(Debug, Clone)]
+pub struct CandleAggregator {
+    timeframes: Vec<TimeFrame>,
+    history: usize,
+    lateness_ms: i64,
+    series: HashMap<(String, TimeFrame), Series>,
+    dropped: u64,
+}
+
+fn period_ms(tf: TimeFrame) -> i64 {
+    tf.as_seconds() as i64 * 1_000
+}
+
+impl CandleAggregator {
+    pub fn new(timeframes: impl IntoIterator<Item = TimeFrame>) -> Self {
+        let mut tfs: Vec<TimeFrame> = Vec::new();
+        for tf in timeframes {
+            if !tfs.contains(&tf) {
+                tfs.push(tf);
+            }
+        }
+        tfs.sort_by_key(|tf| tf.as_seconds());
+        Self {
+            timeframes: tfs,
+            history: DEFAULT_HISTORY,
+            lateness_ms: DEFAULT_LATENESS_MS,
+            series: HashMap::new(),
+            dropped: 0,
+        }
+    }
+
+    /// Aggregator for every timeframe declared by `strategies`
+    pub fn for_strategies(strategies: &[Box<dyn TradingStrategy>]) -> Self {
+        Self::new(strategies.iter().map(|s| s.timeframe()))
+    }
+
+    /// Keep `bars` closed bars per series
+    pub fn with_history(mut self, bars: usize) -> Self {
+        self.history = bars.max(1);
+        self
+    }
+
+    /// Hold bars open `ms` past their end for out-of-order trades
+    pub fn with_lateness(mut self, ms: i64) -> Self {
+        self.lateness_ms = ms.max(0);
+        self
+    }
+
+    pub fn timeframes(&self) -> &[TimeFrame] {
+        &self.timeframes
+    }
+
+    /// Trades dropped because their bar had already closed
+    pub fn dropped(&self) -> u64 {
+        self.dropped
+    }
+
+    /// Closed bars of `symbol` at `timeframe`, oldest first
+    pub fn history(&self, symbol: &str, timeframe: TimeFrame) -> Vec<Candle> {
+        self.series
+            .get(&(symbol.to_string(), timeframe))
+            .map(|s| s.closed.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Fold a trade into every timeframe and return the bars it closed, oldest first
+    pub fn on_trade(&mut self, symbol: &str, price: f64, qty: f64, ts: i64) -> Vec<ClosedBar> {
+        let mut out = Vec::new();
+        for tf in self.timeframes.clone() {
+            let period = period_ms(tf);
+            let start = ts.div_euclid(period) * period;
+            let series = self.series.entry((symbol.to_string(), tf)).or_default();
+            let closed_until = series.closed.back().map(|c| c.timestamp + period);
+            if closed_until.is_some_and(|end| start < end) {
+                self.dropped += 1;
+                log::debug!("Dropping late {symbol} trade at {ts} for a closed {} bar", tf.label());
+                continue;
+            }
+            let bar = series.open.entry(start).or_insert_with(|| Building {
+                candle: Candle {
+                    timestamp: start,
+                    open: price,
+                    high: price,
+                    low: price,
+                    close: price,
+                    volume: 0.0,
+                },
+                open_ts: ts,
+                close_ts: ts,
+            });
+            let c = &mut bar.candle;
+            c.high = c.high.max(price);
+            c.low = c.low.min(price);
+            c.volume += qty;
+            if ts < bar.open_ts {
+                c.open = price;
+                bar.open_ts = ts;
+            }
+            if ts >= bar.close_ts {
+                c.close = price;
+                bar.close_ts = ts;
+            }
+            series.watermark = series.watermark.max(ts);
+            let watermark = series.watermark;
+            self.close_due(symbol, tf, watermark, &mut out);
+        }
+        out
+    }
+
+    /// Close every bar whose end plus the grace period lies before `now`, even without a newer
+    /// trade. Call it on a clock so quiet markets still deliver their bars.
+    pub fn flush(&mut self, now: i64) -> Vec<ClosedBar> {
+        let mut out = Vec::new();
+        let keys: Vec<(String, TimeFrame)> = self.series.keys().cloned().collect();
+        for (symbol, tf) in keys {
+            self.close_due(&symbol, tf, now, &mut out);
+        }
+        out.sort_by_key(|b| b.data.timestamp);
+        out
+    }
+
+    fn close_due(&mut self, symbol: &str, tf: TimeFrame, now: i64, out: &mut Vec<ClosedBar>) {
+        let period = period_ms(tf);
+        let history = self.history;
+        let Some(series) = self.series.get_mut(&(symbol.to_string(), tf)) else {
+            return;
+        };
+        while let Some((&start, _)) = series.open.first_key_value() {
+            if start + period + self.lateness_ms > now {
+                break;
+            }
+            let bar = series.open.remove(&start).expect("first key exists").candle;
+            // flat bars for the periods without trades since the last closed bar
+            if let Some(prev) = series.closed.back().cloned() {
+                let gaps = ((start - prev.timestamp) / period - 1).clamp(0, history as i64);
+                let first_gap = start - gaps * period;
+                for i in 0..gaps {
+                    let flat = Candle {
+                        timestamp: first_gap + i * period,
+                        open: prev.close,
+                        high: prev.close,
+                        low: prev.close,
+                        close: prev.close,
+                        volume: 0.0,
+                    };
+                    push_closed(series, flat, history, symbol, tf, out);
+                }
+            }
+            push_closed(series, bar, history, symbol, tf, out);
+        }
+    }
+}
+
+fn push_closed(
+    series: &mut Series, candle: Candle, history: usize, symbol: &str, tf: TimeFrame,
+    out: &mut Vec<ClosedBar>,
+) {
+    series.closed.push_back(candle.clone());
+    while series.closed.len() > history {
+        series.closed.pop_front();
+    }
+    let pair = TradingPair::from_str(symbol).unwrap_or_else(|| TradingPair::new(symbol, "USDC"));
+    out.push(ClosedBar {
+        timeframe: tf,
+        data: MarketData {
+            pair,
+            symbol: symbol.to_string(),
+            candles: series.closed.iter().cloned().collect(),
+            last_price: candle.close,
+            volume: Some(candle.volume),
+            timestamp: candle.timestamp,
+            open: Some(candle.open),
+            high: Some(candle.high),
+            low: Some(candle.low),
+            close: candle.close,
+            ..Default::default()
+        },
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MIN: i64 = 60_000;
+
+    fn closes(bars: &[ClosedBar]) -> Vec<(i64, f64)> {
+        bars.iter().map(|b| (b.data.timestamp / MIN, b.data.close)).collect()
+    }
+
+    #[test]
+    fn builds_ohlcv_per_timeframe() {
+        let mut agg = CandleAggregator::new([TimeFrame::FiveMinutes, TimeFrame::OneMinute])
+            .with_lateness(0);
+        assert!(agg.on_trade("SOL/USDC", 10.0, 1.0, 0).is_empty());
+        assert!(agg.on_trade("SOL/USDC", 12.0, 2.0, 20_000).is_empty());
+        assert!(agg.on_trade("SOL/USDC", 9.0, 1.0, 40_000).is_empty());
+        let closed = agg.on_trade("SOL/USDC", 11.0, 1.0, MIN);
+        assert_eq!(closed.len(), 1);
+        let bar = &closed[0].data;
+        assert_eq!(closed[0].timeframe, TimeFrame::OneMinute);
+        assert_eq!((bar.open, bar.high, bar.low), (Some(10.0), Some(12.0), Some(9.0)));
+        assert_eq!(bar.close, 9.0);
+        assert_eq!(bar.volume, Some(4.0));
+        assert_eq!(bar.candles.len(), 1);
+
+        let closed = agg.on_trade("SOL/USDC", 13.0, 1.0, 5 * MIN);
+        let five: Vec<_> =
+            closed.iter().filter(|b| b.timeframe == TimeFrame::FiveMinutes).collect();
+        assert_eq!(five.len(), 1);
+        assert_eq!(five[0].data.high, Some(12.0));
+        assert_eq!(five[0].data.close, 11.0);
+    }
+
+    #[test]
+    fn gaps_are_filled_flat_and_late_trades_respected() {
+        let mut agg = CandleAggregator::new([TimeFrame::OneMinute]).with_lateness(5_000);
+        agg.on_trade("SOL/USDC", 10.0, 1.0, 30_000);
+        assert!(agg.on_trade("SOL/USDC", 11.0, 1.0, MIN + 1_000).is_empty());
+        // within the grace period: still part of the first bar
+        assert!(agg.on_trade("SOL/USDC", 15.0, 1.0, 50_000).is_empty());
+        let closed = agg.on_trade("SOL/USDC", 12.0, 1.0, 2 * MIN);
+        assert_eq!(closes(&closed), vec![(0, 15.0)]);
+
+        // too late for the closed bar
+        assert!(agg.on_trade("SOL/USDC", 99.0, 1.0, 59_000).is_empty());
+        assert_eq!(agg.dropped(), 1);
+
+        let closed = agg.on_trade("SOL/USDC", 13.0, 1.0, 5 * MIN);
+        assert_eq!(closes(&closed), vec![(1, 11.0), (2, 12.0)]);
+        // no trades in minutes 3 and 4; minute 5 closes on the clock
+        let closed = agg.flush(7 * MIN);
+        assert_eq!(closes(&closed), vec![(3, 12.0), (4, 12.0), (5, 13.0)]);
+        assert_eq!(closed[0].data.volume, Some(0.0));
+        assert_eq!(agg.history("SOL/USDC", TimeFrame::OneMinute).len(), 6);
+    }
+}
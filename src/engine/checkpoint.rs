@@ -0,0 +1,143 @@
+//! Strategy checkpoints: the runtime state of live strategies is saved through [`Persistence`]
+//! and restored on startup, so indicator windows, positions and trade history survive restarts.
+//! A checkpoint is only restored into an instance with the same state version and params.
+//! Paper and live runs use separate databases, so neither restores the other's checkpoints.
+
+use crate::persistence::{Persistence, StrategyStateRecord};
+use crate::strategies::TradingStrategy;
+
+/// Persistence key of a strategy instance: its name and symbols
+pub fn state_key(strategy: &dyn TradingStrategy) -> String {
+    format!("{}:{}", strategy.name(), strategy.symbols().join(","))
+}
+
+/// Save the state of every strategy that has one. Returns how many were saved.
+pub async fn save(
+    persistence: &dyn Persistence, strategies: &[Box<dyn TradingStrategy>], now: i64,
+) -> anyhow::Result<usize> {
+    let mut saved = 0;
+    for strategy in strategies {
+        let Some(state) = strategy.snapshot_state() else {
+            continue;
+        };
+        let record = StrategyStateRecord {
+            key: state_key(strategy.as_ref()),
+            version: strategy.state_version(),
+            params: strategy.params(),
+            state,
+            saved_at: now,
+        };
+        persistence.save_strategy_state(&record).await?;
+        saved += 1;
+    }
+    Ok(saved)
+}
+
+/// Restore every strategy from its checkpoint. Checkpoints written with another state version
+/// or params, or that fail to load, are deleted so the strategy warms up from scratch.
+/// Returns the indices of the restored strategies.
+pub async fn restore(
+    persistence: &dyn Persistence, strategies: &mut [Box<dyn TradingStrategy>],
+) -> anyhow::Result<Vec<usize>> {
+    let records = persistence.load_strategy_states().await?;
+    let mut restored = Vec::new();
+    for (idx, strategy) in strategies.iter_mut().enumerate() {
+        let key = state_key(strategy.as_ref());
+        let Some(record) = records.iter().find(|r| r.key == key) else {
+            continue;
+        };
+        if record.version != strategy.state_version() || record.params != strategy.params() {
+            log::info!("Discarding stale checkpoint of {key} (version {})", record.version);
+            persistence.delete_strategy_state(&key).await?;
+            continue;
+        }
+        match strategy.restore_state(record.state.clone()) {
+            | Ok(()) => restored.push(idx),
+            | Err(e) => {
+                log::warn!("Discarding unreadable checkpoint of {key}: {e}");
+                persistence.delete_strategy_state(&key).await?;
+            }
+        }
+    }
+    Ok(restored)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::persistence::sqlite::SqlitePersistence;
+    use crate::strategies::{MeanReversionStrategy, TimeFrame};
+    use crate::trading::MarketData;
+    use crate::utils::types::TradingPair;
+
+    fn bar(i: i64) -> MarketData {
+        let close = 100.0 + 10.0 * (i as f64 / 5.0).sin();
+        MarketData {
+            pair: TradingPair::new("SOL", "USDC"),
+            symbol: "SOL/USDC".into(),
+            timestamp: i * 3600,
+            close,
+            last_price: close,
+            ..Default::default()
+        }
+    }
+
+    fn strategies(lookback: usize) -> Vec<Box<dyn TradingStrategy>> {
+        let s = MeanReversionStrategy::new("SOL/USDC", TimeFrame::OneHour, lookback, 2.0, 5.0, 3.0);
+        vec![Box::new(s)]
+    }
+
+    #[tokio::test]
+    async fn restored_strategy_continues_where_it_stopped() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = SqlitePersistence::new(Some(dir.path().join("state.db"))).await.unwrap();
+
+        let mut warm = strategies(20);
+        for i in 0..60 {
+            warm[0].generate_signals(&bar(i)).await;
+        }
+        assert_eq!(save(&db, &warm, 0).await.unwrap(), 1);
+
+        let mut restarted = strategies(20);
+        assert_eq!(restore(&db, &mut restarted).await.unwrap(), vec![0]);
+        for i in 60..120 {
+            let a = warm[0].generate_signals(&bar(i)).await;
+            let b = restarted[0].generate_signals(&bar(i)).await;
+            let kinds = |s: &[crate::trading::Signal]| {
+                s.iter().map(|s| s.signal_type.clone()).collect::<Vec<_>>()
+            };
+            assert_eq!(kinds(&a), kinds(&b), "bar {i}");
+        }
+
+        // other params make the checkpoint stale; it is dropped rather than restored
+        let mut reconfigured = strategies(30);
+        assert!(restore(&db, &mut reconfigured).await.unwrap().is_empty());
+        assert!(db.load_strategy_states().await.unwrap().is_empty());
+    }
+
+    fn trend_following(fast_ema: u64) -> Vec<Box<dyn TradingStrategy>> {
+        let cfg = crate::strategies::StrategyConfig {
+            name: "trend_following".into(),
+            enabled: true,
+            params: serde_json::json!({ "fast_ema_period": fast_ema }),
+            performance: None,
+        };
+        vec![Box::new(crate::strategies::TrendFollowingStrategy::try_from(&cfg).unwrap())]
+    }
+
+    #[tokio::test]
+    async fn checkpoints_stay_with_their_params_and_mode() {
+        let dir = tempfile::tempdir().unwrap();
+        let open = |paper| {
+            SqlitePersistence::new(Some(SqlitePersistence::path_in(dir.path(), paper)))
+        };
+        let paper = open(true).await.unwrap();
+        assert_eq!(save(&paper, &trend_following(9), 0).await.unwrap(), 1);
+
+        let live = open(false).await.unwrap();
+        assert!(restore(&live, &mut trend_following(9)).await.unwrap().is_empty());
+        assert_eq!(restore(&paper, &mut trend_following(9)).await.unwrap(), vec![0]);
+        assert!(restore(&paper, &mut trend_following(12)).await.unwrap().is_empty());
+        assert!(paper.load_strategy_states().await.unwrap().is_empty());
+    }
+}
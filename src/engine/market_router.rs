@@ -1,7 +1,20 @@
 //! MarketRouter: orchestrates multiple MarketDataStream sources and routes MarketEvents to trading logic
+//!
+//! Every stream runs under its own supervisor task. A stream that errors, ends, or stays silent
+//! for longer than the staleness timeout is dropped and reconnected after a jittered exponential
+//! backoff, while the other streams keep delivering. The supervisors stop once the receiving
+//! side of the event channel is closed.
 
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use rand::Rng;
+use tokio::sync::mpsc::{self, Sender};
+use tokio::time::Instant;
+
+use crate::config::{MarketDataConfig, MarketDataSource, MarketDataSourceKind};
 use crate::utils::market_stream::MarketEvent;
-use tokio::sync::mpsc::Sender;
 
 /// Trait for trading logic that can handle MarketEvents
 pub trait MarketEventHandler: Send + Sync + 'static {
@@ -11,15 +24,101 @@ pub trait MarketEventHandler: Send + Sync + 'static {
 /// New trait for streams that can send events via channel
 #[async_trait::async_trait]
 pub trait ChannelMarketDataStream: Send + Sync {
+    /// Source name used in logs and in the stream status map
+    fn name(&self) -> &str;
+
     async fn connect_and_stream_channel(
         &mut self, symbols: Vec<String>, sender: Sender<MarketEvent>,
     ) -> anyhow::Result<()>;
 }
 
+type BoxedStream = Box<dyn ChannelMarketDataStream + Send + Sync>;
+
+/// Reconnect and staleness settings shared by every stream of a router
+#[derive(Debug, Clone)]
+pub struct SupervisorConfig {
+    /// Delay before the first reconnect; doubled on each failure in a row
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+    /// Reconnect a stream that delivered nothing for this long; `None` disables the watchdog
+    pub stale_after: Option<Duration>,
+}
+
+impl Default for SupervisorConfig {
+    fn default() -> Self {
+        Self {
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(60),
+            stale_after: Some(Duration::from_secs(60)),
+        }
+    }
+}
+
+impl SupervisorConfig {
+    /// Backoff before reconnect number `attempt` (from 0), jittered down to half its value
+    fn backoff(&self, attempt: u32) -> Duration {
+        let full = self.initial_backoff.saturating_mul(1 << attempt.min(16)).min(self.max_backoff);
+        full.mul_f64(rand::thread_rng().gen_range(0.5..=1.0))
+    }
+}
+
+/// Health of one stream
+#[derive(Debug, Clone, Default)]
+pub struct StreamStatus {
+    /// Delivered at least one event since its last (re)connect
+    pub connected: bool,
+    pub last_message: Option<Instant>,
+    pub reconnects: u64,
+    pub last_error: Option<String>,
+}
+
+impl StreamStatus {
+    pub fn last_message_age(&self) -> Option<Duration> {
+        self.last_message.map(|t| t.elapsed())
+    }
+}
+
+/// Shared per-stream status, updated by the supervisors
+#[derive(Debug, Clone, Default)]
+pub struct StreamStatusMap(Arc<Mutex<HashMap<String, StreamStatus>>>);
+
+impl StreamStatusMap {
+    pub fn get(&self, name: &str) -> Option<StreamStatus> {
+        self.0.lock().ok()?.get(name).cloned()
+    }
+
+    /// Status of every stream, sorted by name
+    pub fn snapshot(&self) -> Vec<(String, StreamStatus)> {
+        let Ok(map) = self.0.lock() else {
+            return Vec::new();
+        };
+        let mut out: Vec<_> = map.iter().map(|(k, v)| (k.clone(), v.clone())).collect();
+        out.sort_by(|a, b| a.0.cmp(&b.0));
+        out
+    }
+
+    fn update(&self, name: &str, f: impl FnOnce(&mut StreamStatus)) {
+        if let Ok(mut map) = self.0.lock() {
+            f(map.entry(name.to_string()).or_default());
+        }
+    }
+}
+
+/// Why one connection of a stream ended
+enum Ended {
+    Closed,
+    Failed(anyhow::Error),
+    Stale,
+    /// The router's receiver is gone; stop supervising
+    Shutdown,
+}
+
 /// MarketRouter manages multiple market data streams and routes events to the trading engine
 pub struct MarketRouter {
-    // Event streams feeding market data
-    streams: Vec<Box<dyn ChannelMarketDataStream + Send + Sync>>,
+    // Event streams feeding market data, keyed by their unique name
+    streams: Vec<(String, Box<dyn ChannelMarketDataStream + Send + Sync>)>,
+    supervisor: SupervisorConfig,
+    status: StreamStatusMap,
 }
 
 impl Default for MarketRouter {
@@ -30,27 +129,376 @@ impl Default for MarketRouter {
 
 impl MarketRouter {
     pub fn new() -> Self {
-        Self { streams: Vec::new() }
+        Self {
+            streams: Vec::new(),
+            supervisor: SupervisorConfig::default(),
+            status: StreamStatusMap::default(),
+        }
+    }
+
+    /// Router over the enabled `[[market_data.sources]]` that list at least one of `symbols`.
+    /// Without configured sources the keyless Binance, Coinbase and Kraken feeds are used.
+    pub fn from_config(cfg: &MarketDataConfig, symbols: &[String]) -> crate::Result<Self> {
+        let mut router = Self::new().with_supervisor(SupervisorConfig {
+            max_backoff: Duration::from_secs(cfg.max_backoff_secs.max(1)),
+            stale_after: (cfg.stale_after_secs > 0)
+                .then(|| Duration::from_secs(cfg.stale_after_secs)),
+            ..Default::default()
+        });
+        let sources: Vec<MarketDataSource> = if cfg.sources.is_empty() {
+            use MarketDataSourceKind::*;
+            [Binance, Coinbase, Kraken].into_iter().map(MarketDataSource::from).collect()
+        } else {
+            cfg.sources.clone()
+        };
+        for source in sources.iter().filter(|s| s.enabled) {
+            router.add_source(source, symbols)?;
+        }
+        Ok(router)
     }
 
+    /// Add the streams of one configured source; a source that needs a key and has none is a
+    /// config error
+    fn add_source(&mut self, source: &MarketDataSource, symbols: &[String]) -> crate::Result<()> {
+        use crate::utils::{binance_stream, coinbase_stream, helius_stream, kraken_stream};
+        use crate::utils::{serum_stream, triton_stream};
+        let pairs = source.venue_symbols(symbols);
+        let kind = source.kind;
+        if pairs.is_empty() && kind != MarketDataSourceKind::Helius {
+            log::info!("Market data source {kind:?} lists none of the traded symbols; skipped");
+            return Ok(());
+        }
+        let venue: Vec<String> = pairs.iter().map(|(_, v)| v.clone()).collect();
+        let api_key = || {
+            source.resolve_api_key().ok_or_else(|| {
+                crate::Error::ConfigError(format!("market data source {kind:?} needs an api_key"))
+            })
+        };
+        let streams: Vec<BoxedStream> = match kind {
+            | MarketDataSourceKind::Binance => {
+                vec![Box::new(binance_stream::BinanceStream::new(&venue))]
+            }
+            | MarketDataSourceKind::Coinbase => {
+                vec![Box::new(coinbase_stream::CoinbaseStream::new(&venue))]
+            }
+            | MarketDataSourceKind::Kraken => vec![Box::new(kraken_stream::KrakenStream::new())],
+            | MarketDataSourceKind::Serum => venue
+                .iter()
+                .map(|m| Box::new(serum_stream::SerumStream::new(m)) as BoxedStream)
+                .collect(),
+            | MarketDataSourceKind::Triton => {
+                let key = api_key()?;
+                venue
+                    .iter()
+                    .map(|m| Box::new(triton_stream::TritonStream::new(&key, m)) as BoxedStream)
+                    .collect()
+            }
+            | MarketDataSourceKind::Helius => {
+                let key = api_key()?;
+                let program = source.program_id.as_deref();
+                vec![Box::new(helius_stream::HeliusStream::new(&key, program))]
+            }
+        };
+        let to_engine: HashMap<String, String> = pairs.into_iter().map(|(e, v)| (v, e)).collect();
+        for inner in streams {
+            let to_engine = to_engine.clone();
+            let mapped = SymbolMapped { inner, subscribe: venue.clone(), to_engine };
+            self.add_stream(Box::new(mapped));
+        }
+        Ok(())
+    }
+
+    pub fn with_supervisor(mut self, supervisor: SupervisorConfig) -> Self {
+        self.supervisor = supervisor;
+        self
+    }
+
+    /// Add a stream; a name already taken gets a `#n` suffix
     pub fn add_stream(&mut self, stream: Box<dyn ChannelMarketDataStream + Send + Sync>) {
-        self.streams.push(stream);
+        let mut name = stream.name().to_string();
+        let mut n = 1;
+        while self.streams.iter().any(|(k, _)| *k == name) {
+            n += 1;
+            name = format!("{}#{n}", stream.name());
+        }
+        self.status.update(&name, |_| {});
+        self.streams.push((name, stream));
     }
 
+    /// Handle on the per-stream status; stays live while the router runs
+    pub fn status(&self) -> StreamStatusMap {
+        self.status.clone()
+    }
+
+    /// Supervise every stream until `sender`'s receiver is dropped
     pub async fn run(
         &mut self, symbols: Vec<String>, sender: Sender<MarketEvent>,
     ) -> anyhow::Result<()> {
         let mut handles = Vec::new();
         // Drain the streams so each is moved into its own task
-        for mut stream in self.streams.drain(..) {
+        for (name, stream) in self.streams.drain(..) {
             let s = symbols.clone();
             let tx = sender.clone();
-            handles
-                .push(tokio::spawn(async move { stream.connect_and_stream_channel(s, tx).await }));
+            let cfg = self.supervisor.clone();
+            let status = self.status.clone();
+            let task_name = name.clone();
+            let handle = tokio::spawn(supervise(task_name, stream, s, tx, cfg, status));
+            handles.push((name, handle));
         }
-        for handle in handles {
-            handle.await??;
+        drop(sender);
+        for (name, handle) in handles {
+            // a panicking stream only takes down its own supervisor
+            if let Err(e) = handle.await {
+                log::error!("Market stream {name} supervisor died: {e}");
+                self.status.update(&name, |st| {
+                    st.connected = false;
+                    st.last_error = Some(e.to_string());
+                });
+            }
         }
         Ok(())
     }
 }
+
+impl From<MarketDataSourceKind> for MarketDataSource {
+    fn from(kind: MarketDataSourceKind) -> Self {
+        Self {
+            kind,
+            enabled: true,
+            api_key: None,
+            api_key_env: None,
+            symbols: Default::default(),
+            program_id: None,
+        }
+    }
+}
+
+/// A configured source: subscribes to its own venue symbols and renames the events back to
+/// engine symbols
+struct SymbolMapped {
+    inner: BoxedStream,
+    subscribe: Vec<String>,
+    /// Venue symbol to engine symbol
+    to_engine: HashMap<String, String>,
+}
+
+#[async_trait::async_trait]
+impl ChannelMarketDataStream for SymbolMapped {
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    async fn connect_and_stream_channel(
+        &mut self, _symbols: Vec<String>, sender: Sender<MarketEvent>,
+    ) -> anyhow::Result<()> {
+        let Self { inner, subscribe, to_engine } = self;
+        let (tx, mut rx) = mpsc::channel(256);
+        let forward = async {
+            while let Some(mut evt) = rx.recv().await {
+                let (MarketEvent::Trade { symbol, .. }
+                | MarketEvent::OrderBook { symbol, .. }
+                | MarketEvent::Ticker { symbol, .. }) = &mut evt;
+                if let Some(engine) = to_engine.get(symbol.as_str()) {
+                    *symbol = engine.clone();
+                }
+                if sender.send(evt).await.is_err() {
+                    break;
+                }
+            }
+        };
+        let (res, ()) =
+            tokio::join!(inner.connect_and_stream_channel(subscribe.clone(), tx), forward);
+        res
+    }
+}
+
+/// Keep one stream connected, forwarding its events to `sender`
+async fn supervise(
+    name: String, mut stream: Box<dyn ChannelMarketDataStream + Send + Sync>,
+    symbols: Vec<String>, sender: Sender<MarketEvent>, cfg: SupervisorConfig,
+    status: StreamStatusMap,
+) {
+    let mut attempt = 0u32;
+    loop {
+        let (delivered, ended) =
+            connect_once(&name, stream.as_mut(), &symbols, &sender, &cfg, &status).await;
+        let reason = match ended {
+            | Ended::Shutdown => break,
+            | Ended::Closed => "connection closed".to_string(),
+            | Ended::Failed(e) => e.to_string(),
+            | Ended::Stale => "no messages within the staleness timeout".to_string(),
+        };
+        // a connection that delivered data resets the backoff
+        if delivered {
+            attempt = 0;
+        }
+        let delay = cfg.backoff(attempt);
+        attempt = attempt.saturating_add(1);
+        log::warn!("Market stream {name}: {reason}; reconnecting in {delay:?}");
+        status.update(&name, |st| {
+            st.connected = false;
+            st.reconnects += 1;
+            st.last_error = Some(reason);
+        });
+        tokio::select! {
+            _ = tokio::time::sleep(delay) => {},
+            _ = sender.closed() => break,
+        }
+    }
+    status.update(&name, |st| st.connected = false);
+}
+
+/// Run one connection until it ends, fails or goes stale. Returns whether it delivered anything.
+async fn connect_once(
+    name: &str, stream: &mut (dyn ChannelMarketDataStream + Send + Sync), symbols: &[String],
+    sender: &Sender<MarketEvent>, cfg: &SupervisorConfig, status: &StreamStatusMap,
+) -> (bool, Ended) {
+    let (tx, mut rx) = mpsc::channel(256);
+    let conn = stream.connect_and_stream_channel(symbols.to_vec(), tx);
+    tokio::pin!(conn);
+    let stale_after = cfg.stale_after.unwrap_or(Duration::MAX);
+    let check_every = cfg
+        .stale_after
+        .map_or(Duration::from_secs(3600), |d| (d / 4).max(Duration::from_millis(10)));
+    let mut watchdog = tokio::time::interval(check_every);
+    let mut last_seen = Instant::now();
+    let mut delivered = false;
+
+    let ended = loop {
+        tokio::select! {
+            res = &mut conn => break match res {
+                | Ok(()) => Ended::Closed,
+                | Err(e) => Ended::Failed(e),
+            },
+            Some(evt) = rx.recv() => {
+                if sender.send(evt).await.is_err() {
+                    return (delivered, Ended::Shutdown);
+                }
+                last_seen = Instant::now();
+                delivered = true;
+                status.update(name, |st| {
+                    st.connected = true;
+                    st.last_message = Some(last_seen);
+                });
+            },
+            _ = watchdog.tick() => {
+                if last_seen.elapsed() >= stale_after {
+                    break Ended::Stale;
+                }
+            },
+            _ = sender.closed() => return (delivered, Ended::Shutdown),
+        }
+    };
+    // events the stream queued before it ended
+    while let Ok(evt) = rx.try_recv() {
+        if sender.send(evt).await.is_err() {
+            return (delivered, Ended::Shutdown);
+        }
+        delivered = true;
+    }
+    (delivered, ended)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Fails its first connection, then sends `events` trades and stays silent
+    struct Flaky {
+        name: String,
+        connects: usize,
+        events: usize,
+    }
+
+    #[async_trait::async_trait]
+    impl ChannelMarketDataStream for Flaky {
+        fn name(&self) -> &str {
+            &self.name
+        }
+
+        async fn connect_and_stream_channel(
+            &mut self, _symbols: Vec<String>, sender: Sender<MarketEvent>,
+        ) -> anyhow::Result<()> {
+            self.connects += 1;
+            if self.connects == 1 {
+                anyhow::bail!("refused");
+            }
+            for i in 0..self.events {
+                let _ = sender
+                    .send(MarketEvent::Ticker {
+                        exchange: self.name.clone(),
+                        symbol: "SOL/USDC".into(),
+                        price: 100.0,
+                        timestamp: i as i64,
+                    })
+                    .await;
+            }
+            std::future::pending().await
+        }
+    }
+
+    fn flaky(name: &str, events: usize) -> Box<Flaky> {
+        Box::new(Flaky { name: name.into(), connects: 0, events })
+    }
+
+    #[tokio::test]
+    async fn failed_and_silent_streams_are_reconnected_without_stopping_others() {
+        let mut router = MarketRouter::new().with_supervisor(SupervisorConfig {
+            initial_backoff: Duration::from_millis(5),
+            max_backoff: Duration::from_millis(20),
+            stale_after: Some(Duration::from_millis(150)),
+        });
+        router.add_stream(flaky("live", 3));
+        router.add_stream(flaky("live", 0));
+        let status = router.status();
+        let (tx, mut rx) = mpsc::channel(16);
+        let handle = tokio::spawn(async move { router.run(vec!["SOL/USDC".into()], tx).await });
+
+        for _ in 0..3 {
+            let evt = tokio::time::timeout(Duration::from_secs(2), rx.recv()).await.unwrap();
+            assert!(matches!(evt, Some(MarketEvent::Ticker { .. })));
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        let live = status.get("live").unwrap();
+        assert!(live.connected);
+        assert_eq!(live.reconnects, 1);
+        assert_eq!(live.last_error.as_deref(), Some("refused"));
+        assert!(live.last_message_age().is_some());
+
+        // the silent duplicate keeps being recycled by the watchdog
+        tokio::time::sleep(Duration::from_millis(400)).await;
+        let silent = status.get("live#2").unwrap();
+        assert!(!silent.connected);
+        assert!(silent.reconnects >= 2, "{silent:?}");
+        assert!(silent.last_message_age().is_none());
+
+        drop(rx);
+        tokio::time::timeout(Duration::from_secs(2), handle).await.unwrap().unwrap().unwrap();
+        assert_eq!(status.snapshot().len(), 2);
+    }
+
+    #[test]
+    fn from_config_builds_only_enabled_sources_for_traded_symbols() {
+        let names = |router: &MarketRouter| -> Vec<String> {
+            router.status().snapshot().into_iter().map(|(n, _)| n).collect()
+        };
+        let symbols = vec!["SOL/USDC".to_string(), "BONK/USDC".to_string()];
+        let defaults = MarketRouter::from_config(&MarketDataConfig::default(), &symbols).unwrap();
+        assert_eq!(names(&defaults), vec!["binance", "coinbase", "kraken"]);
+
+        let mut serum = MarketDataSource::from(MarketDataSourceKind::Serum);
+        serum.symbols.insert("SOL/USDC".into(), "SOL-USDC".into());
+        serum.symbols.insert("JUP/USDC".into(), "JUP-USDC".into());
+        let mut kraken = MarketDataSource::from(MarketDataSourceKind::Kraken);
+        kraken.enabled = false;
+        let mut cfg = MarketDataConfig { sources: vec![serum, kraken], ..Default::default() };
+        assert_eq!(names(&MarketRouter::from_config(&cfg, &symbols).unwrap()), vec!["serum"]);
+
+        // no demo key fallback: a keyless triton source is rejected
+        cfg.sources.push(MarketDataSource::from(MarketDataSourceKind::Triton));
+        assert!(MarketRouter::from_config(&cfg, &symbols).is_err());
+        cfg.sources[2].api_key = Some("key".into());
+        let router = MarketRouter::from_config(&cfg, &symbols).unwrap();
+        assert_eq!(names(&router), vec!["serum", "triton", "triton#2"]);
+    }
+}
@@ -1 +1,7 @@
+pub mod candle_aggregator;
+pub mod checkpoint;
 pub mod market_router;
+pub mod order_book;
+pub mod order_manager;
+pub mod pending_orders;
+pub mod warmup;
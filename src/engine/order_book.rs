@@ -0,0 +1,268 @@
+//! L2 order books built from `MarketEvent::OrderBook` snapshots and deltas.
+//!
+//! Binance (`@depth` diffs on top of a REST snapshot) is the venue that produces these events.
+//!
+//! One book is kept per exchange and symbol. Deltas carrying a sequence number must follow the
+//! previous one (a delta spanning `first_sequence..=sequence` must start at or before the next
+//! expected number); after a gap the venue's book is cleared and left out of the consolidated
+//! view until its next snapshot. The consolidated book of a symbol sums the size at each price over
+//! every live venue, so its best bid may sit above its best ask when venues are crossed.
+
+use std::collections::{BTreeMap, HashMap};
+
+use crate::trading::{BookLevel, OrderBook};
+use crate::utils::market_stream::MarketEvent;
+
+/// Levels attached to the market data handed to strategies
+pub const DEFAULT_DEPTH: usize = 20;
+
+/// Outcome of applying one book event
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BookUpdate {
+    Applied,
+    /// Duplicate or stale delta, or a delta while the venue waits for a snapshot
+    Ignored,
+    /// Deltas were missed; the venue is out of the consolidated book until its next snapshot
+    Gap { expected: u64, got: u64 },
+}
+
+/// Prices of positive finite levels order like their bit patterns
+fn key(price: f64) -> u64 {
+    price.to_bits()
+}
+
+fn price(key: u64) -> f64 {
+    f64::from_bits(key)
+}
+
+#[derive(Debug, Clone, Default)]
+struct VenueBook {
+    bids: BTreeMap<u64, f64>,
+    asks: BTreeMap<u64, f64>,
+    sequence: Option<u64>,
+    /// Set after a gap until the next snapshot
+    awaiting_snapshot: bool,
+}
+
+impl VenueBook {
+    fn apply_levels(side: &mut BTreeMap<u64, f64>, levels: &[(f64, f64)]) {
+        for &(px, size) in levels {
+            if !(px.is_finite() && px > 0.0) {
+                continue;
+            }
+            if size > 0.0 {
+                side.insert(key(px), size);
+            } else {
+                side.remove(&key(px));
+            }
+        }
+    }
+}
+
+/// Per-venue books and their consolidated view
+#[derive(Debug, Clone, Default)]
+pub struct OrderBookManager {
+    books: HashMap<(String, String), VenueBook>,
+}
+
+impl OrderBookManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Apply an `OrderBook` event; other events are ignored
+    pub fn apply(&mut self, event: &MarketEvent) -> BookUpdate {
+        let MarketEvent::OrderBook {
+            exchange,
+            symbol,
+            bids,
+            asks,
+            is_snapshot,
+            sequence,
+            first_sequence,
+            ..
+        } = event
+        else {
+            return BookUpdate::Ignored;
+        };
+        let book = self.books.entry((exchange.clone(), symbol.clone())).or_default();
+        if *is_snapshot {
+            book.bids.clear();
+            book.asks.clear();
+            book.awaiting_snapshot = false;
+        } else if book.awaiting_snapshot {
+            return BookUpdate::Ignored;
+        } else if let (Some(prev), Some(seq)) = (book.sequence, *sequence) {
+            if seq <= prev {
+                return BookUpdate::Ignored;
+            }
+            let first = first_sequence.unwrap_or(seq);
+            if first > prev + 1 {
+                log::warn!("{exchange} {symbol} book missed deltas {}..{first}", prev + 1);
+                *book = VenueBook { awaiting_snapshot: true, ..Default::default() };
+                return BookUpdate::Gap { expected: prev + 1, got: first };
+            }
+        }
+        VenueBook::apply_levels(&mut book.bids, bids);
+        VenueBook::apply_levels(&mut book.asks, asks);
+        book.sequence = sequence.or(book.sequence);
+        BookUpdate::Applied
+    }
+
+    /// Venues with a usable book for `symbol`
+    pub fn venues(&self, symbol: &str) -> Vec<&str> {
+        let mut out: Vec<&str> = self
+            .books
+            .iter()
+            .filter(|((_, s), b)| s == symbol && !b.awaiting_snapshot)
+            .map(|((e, _), _)| e.as_str())
+            .collect();
+        out.sort_unstable();
+        out
+    }
+
+    /// Top `depth` levels of one venue's book
+    pub fn venue_book(&self, exchange: &str, symbol: &str, depth: usize) -> Option<OrderBook> {
+        let book = self.books.get(&(exchange.to_string(), symbol.to_string()))?;
+        if book.awaiting_snapshot {
+            return None;
+        }
+        let level = |(k, s): (&u64, &f64)| BookLevel { price: price(*k), size: *s };
+        Some(OrderBook {
+            bids: book.bids.iter().rev().take(depth).map(level).collect(),
+            asks: book.asks.iter().take(depth).map(level).collect(),
+        })
+    }
+
+    /// Top `depth` levels of the book consolidated over every venue, `None` while no venue
+    /// has a level for `symbol`
+    pub fn book(&self, symbol: &str, depth: usize) -> Option<OrderBook> {
+        let mut bids: BTreeMap<u64, f64> = BTreeMap::new();
+        let mut asks: BTreeMap<u64, f64> = BTreeMap::new();
+        for ((_, s), book) in &self.books {
+            if s != symbol || book.awaiting_snapshot {
+                continue;
+            }
+            for (k, size) in &book.bids {
+                *bids.entry(*k).or_default() += size;
+            }
+            for (k, size) in &book.asks {
+                *asks.entry(*k).or_default() += size;
+            }
+        }
+        if bids.is_empty() && asks.is_empty() {
+            return None;
+        }
+        let level = |(k, s): (&u64, &f64)| BookLevel { price: price(*k), size: *s };
+        Some(OrderBook {
+            bids: bids.iter().rev().take(depth).map(level).collect(),
+            asks: asks.iter().take(depth).map(level).collect(),
+        })
+    }
+
+    /// Best bid and ask of the consolidated book
+    pub fn best(&self, symbol: &str) -> Option<(f64, f64)> {
+        let book = self.book(symbol, 1)?;
+        Some((book.bids.first()?.price, book.asks.first()?.price))
+    }
+
+    pub fn mid(&self, symbol: &str) -> Option<f64> {
+        self.best(symbol).map(|(bid, ask)| (bid + ask) / 2.0)
+    }
+
+    /// Best ask minus best bid; negative while venues are crossed
+    pub fn spread(&self, symbol: &str) -> Option<f64> {
+        self.best(symbol).map(|(bid, ask)| ask - bid)
+    }
+
+    /// `(bid size - ask size) / (bid size + ask size)` over the top `depth` levels, in [-1, 1]
+    pub fn imbalance(&self, symbol: &str, depth: usize) -> Option<f64> {
+        let book = self.book(symbol, depth)?;
+        let bid: f64 = book.bids.iter().map(|l| l.size).sum();
+        let ask: f64 = book.asks.iter().map(|l| l.size).sum();
+        (bid + ask > 0.0).then(|| (bid - ask) / (bid + ask))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(
+        exchange: &str, bids: &[(f64, f64)], asks: &[(f64, f64)], is_snapshot: bool,
+        sequence: Option<u64>,
+    ) -> MarketEvent {
+        MarketEvent::OrderBook {
+            exchange: exchange.into(),
+            symbol: "SOL/USDC".into(),
+            bids: bids.to_vec(),
+            asks: asks.to_vec(),
+            timestamp: 0,
+            is_snapshot,
+            sequence,
+            first_sequence: None,
+        }
+    }
+
+    #[test]
+    fn deltas_follow_snapshots_and_gaps_wait_for_the_next_one() {
+        let mut books = OrderBookManager::new();
+        let snap = event("a", &[(99.0, 1.0), (98.0, 2.0)], &[(101.0, 1.0)], true, Some(10));
+        assert_eq!(books.apply(&snap), BookUpdate::Applied);
+        let delta = event("a", &[(99.0, 0.0), (100.0, 3.0)], &[], false, Some(11));
+        assert_eq!(books.apply(&delta), BookUpdate::Applied);
+        assert_eq!(books.apply(&delta), BookUpdate::Ignored);
+        assert_eq!(books.best("SOL/USDC"), Some((100.0, 101.0)));
+
+        let skipped = event("a", &[(100.5, 1.0)], &[], false, Some(13));
+        assert_eq!(books.apply(&skipped), BookUpdate::Gap { expected: 12, got: 13 });
+        assert_eq!(books.book("SOL/USDC", 5), None);
+        let late = event("a", &[(100.5, 1.0)], &[], false, Some(14));
+        assert_eq!(books.apply(&late), BookUpdate::Ignored);
+        let resync = event("a", &[(97.0, 1.0)], &[], true, Some(20));
+        assert_eq!(books.apply(&resync), BookUpdate::Applied);
+        assert_eq!(books.venues("SOL/USDC"), vec!["a"]);
+    }
+
+    #[test]
+    fn ranged_deltas_may_overlap_the_snapshot() {
+        let mut books = OrderBookManager::new();
+        books.apply(&event("binance", &[(99.0, 1.0)], &[(101.0, 1.0)], true, Some(160)));
+        // Binance-style diff covering update ids `first..=last`
+        let ranged = |first: u64, last: u64, bid: (f64, f64)| {
+            let mut evt = event("binance", &[bid], &[], false, Some(last));
+            if let MarketEvent::OrderBook { first_sequence, .. } = &mut evt {
+                *first_sequence = Some(first);
+            }
+            evt
+        };
+        // already in the snapshot
+        assert_eq!(books.apply(&ranged(150, 160, (98.0, 1.0))), BookUpdate::Ignored);
+        // straddles the snapshot's last update id
+        assert_eq!(books.apply(&ranged(157, 162, (100.0, 2.0))), BookUpdate::Applied);
+        assert_eq!(books.apply(&ranged(163, 170, (99.0, 0.0))), BookUpdate::Applied);
+        assert_eq!(books.best("SOL/USDC"), Some((100.0, 101.0)));
+        assert_eq!(
+            books.apply(&ranged(175, 180, (97.0, 1.0))),
+            BookUpdate::Gap { expected: 171, got: 175 }
+        );
+    }
+
+    #[test]
+    fn venues_are_consolidated() {
+        let mut books = OrderBookManager::new();
+        books.apply(&event("a", &[(100.0, 1.0), (99.0, 1.0)], &[(101.0, 2.0)], true, None));
+        books.apply(&event("b", &[(100.0, 2.0)], &[(100.5, 1.0), (101.0, 1.0)], true, None));
+        let book = books.book("SOL/USDC", 10).unwrap();
+        assert_eq!(
+            book.bids,
+            vec![BookLevel { price: 100.0, size: 3.0 }, BookLevel { price: 99.0, size: 1.0 }]
+        );
+        assert_eq!(book.asks[1], BookLevel { price: 101.0, size: 3.0 });
+        assert_eq!(books.mid("SOL/USDC"), Some(100.25));
+        assert_eq!(books.spread("SOL/USDC"), Some(0.5));
+        // 4 on the bid against 4 on the ask
+        assert_eq!(books.imbalance("SOL/USDC", 10), Some(0.0));
+        assert_eq!(books.imbalance("SOL/USDC", 1), Some(0.5));
+    }
+}
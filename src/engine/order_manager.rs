@@ -0,0 +1,346 @@
+//! OrderManager: tracks every order routed by the engine through its lifecycle
+//! (`New` → `PartiallyFilled` → `Filled`, or `Canceled` / `Rejected` / `Expired`).
+
+use std::collections::HashMap;
+
+use anyhow::{anyhow, bail, Result};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+
+use crate::utils::types::{
+    Order, OrderSide, OrderStatus, OrderType, PendingOrder, Signal, SignalAction, TradingPair,
+};
+
+/// Quantities below this are treated as fully filled
+const QTY_EPSILON: f64 = 1e-9;
+
+/// A single confirmed execution against an order
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Fill {
+    pub qty: f64,
+    pub price: f64,
+    /// Venue that executed the fill (DEX name or "paper")
+    pub venue: String,
+    pub tx_signature: Option<String>,
+    pub timestamp: i64,
+}
+
+/// Order tracked by the [`OrderManager`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ManagedOrder {
+    pub id: String,
+    pub strategy_id: String,
+    pub pair: TradingPair,
+    pub side: OrderSide,
+    pub order_type: OrderType,
+    /// Requested quantity in base units
+    pub quantity: f64,
+    pub limit_price: Option<f64>,
+    pub stop_price: Option<f64>,
+    pub status: OrderStatus,
+    pub fills: Vec<Fill>,
+    /// Reason recorded on rejection or cancellation
+    pub reason: Option<String>,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+impl ManagedOrder {
+    /// Total quantity filled so far
+    pub fn filled_qty(&self) -> f64 {
+        self.fills.iter().map(|f| f.qty).sum()
+    }
+
+    /// Quantity still open
+    pub fn remaining_qty(&self) -> f64 {
+        (self.quantity - self.filled_qty()).max(0.0)
+    }
+
+    /// Volume-weighted average fill price, if anything has filled
+    pub fn avg_fill_price(&self) -> Option<f64> {
+        let qty = self.filled_qty();
+        if qty <= 0.0 {
+            return None;
+        }
+        Some(self.fills.iter().map(|f| f.qty * f.price).sum::<f64>() / qty)
+    }
+
+    /// Strategy-facing view of the order. With `fill` set the view describes that single
+    /// execution, otherwise the aggregate of all fills (or the request if nothing filled).
+    pub fn to_order(&self, fill: Option<&Fill>) -> Order {
+        let (price, size, timestamp) = match fill {
+            | Some(f) => (f.price, f.qty, f.timestamp),
+            | None => (
+                self.avg_fill_price()
+                    .or(self.limit_price)
+                    .or(self.stop_price)
+                    .unwrap_or(0.0),
+                if self.filled_qty() > 0.0 { self.filled_qty() } else { self.quantity },
+                self.updated_at,
+            ),
+        };
+        Order {
+            id: self.id.clone(),
+            symbol: self.pair.to_string(),
+            price,
+            size,
+            side: self.side,
+            order_type: self.order_type,
+            timestamp,
+        }
+    }
+}
+
+/// In-memory registry of orders and their lifecycle state
+#[derive(Debug, Default)]
+pub struct OrderManager {
+    orders: HashMap<String, ManagedOrder>,
+    seq: u64,
+}
+
+impl OrderManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a new order for `qty` derived from an engine signal and return its id
+    pub fn submit(&mut self, sig: &Signal, qty: f64) -> String {
+        let id = self.next_id(&sig.strategy_id, sig.timestamp);
+        let now = Utc::now().timestamp();
+        let order = ManagedOrder {
+            id: id.clone(),
+            strategy_id: sig.strategy_id.clone(),
+            pair: sig.pair.clone(),
+            side: if sig.action == SignalAction::Buy { OrderSide::Buy } else { OrderSide::Sell },
+            order_type: sig.order_type,
+            quantity: qty,
+            limit_price: sig.limit_price,
+            stop_price: sig.stop_price,
+            status: OrderStatus::New,
+            fills: Vec::new(),
+            reason: None,
+            created_at: now,
+            updated_at: now,
+        };
+        log::debug!("Order {} submitted: {:?} {} {}", id, order.side, qty, order.pair);
+        self.orders.insert(id.clone(), order);
+        id
+    }
+
+    /// Make sure a resting order (e.g. reloaded after a restart) is tracked and return its id
+    pub fn track_pending(&mut self, po: &PendingOrder) -> String {
+        if !po.id.is_empty() && self.orders.contains_key(&po.id) {
+            return po.id.clone();
+        }
+        let id = if po.id.is_empty() {
+            self.next_id(&po.strategy_id, po.timestamp)
+        } else {
+            po.id.clone()
+        };
+        let now = Utc::now().timestamp();
+        self.orders.insert(
+            id.clone(),
+            ManagedOrder {
+                id: id.clone(),
+                strategy_id: po.strategy_id.clone(),
+                pair: po.pair.clone(),
+                side: if po.is_buy { OrderSide::Buy } else { OrderSide::Sell },
+                order_type: po.order_type,
+                quantity: po.amount,
+                limit_price: po.limit_price,
+                stop_price: po.stop_price,
+                status: OrderStatus::New,
+                fills: Vec::new(),
+                reason: None,
+                created_at: po.timestamp,
+                updated_at: now,
+            },
+        );
+        id
+    }
+
+    /// Fresh `strategy-timestamp-seq` id. The sequence restarts with the process, so ids
+    /// already tracked (e.g. resting orders restored from persistence) are skipped.
+    fn next_id(&mut self, strategy_id: &str, timestamp: i64) -> String {
+        loop {
+            self.seq += 1;
+            let id = format!("{}-{}-{}", strategy_id, timestamp, self.seq);
+            if !self.orders.contains_key(&id) {
+                return id;
+            }
+        }
+    }
+
+    pub fn get(&self, id: &str) -> Option<&ManagedOrder> {
+        self.orders.get(id)
+    }
+
+    /// Orders that can still receive fills
+    pub fn open_orders(&self) -> impl Iterator<Item = &ManagedOrder> {
+        self.orders.values().filter(|o| !o.status.is_terminal())
+    }
+
+    /// All orders currently in `status`
+    pub fn orders_with_status(&self, status: OrderStatus) -> Vec<&ManagedOrder> {
+        self.orders.values().filter(|o| o.status == status).collect()
+    }
+
+    /// Record a confirmed execution and return the resulting status
+    pub fn record_fill(
+        &mut self, id: &str, qty: f64, price: f64, venue: &str, tx_signature: Option<String>,
+    ) -> Result<OrderStatus> {
+        if qty <= 0.0 || !price.is_finite() || price <= 0.0 {
+            bail!("invalid fill for {}: qty={} price={}", id, qty, price);
+        }
+        let order = self.orders.get_mut(id).ok_or_else(|| anyhow!("unknown order {}", id))?;
+        if qty > order.remaining_qty() + QTY_EPSILON {
+            bail!("fill of {} exceeds remaining {} on order {}", qty, order.remaining_qty(), id);
+        }
+        let now = Utc::now().timestamp();
+        order.fills.push(Fill {
+            qty,
+            price,
+            venue: venue.to_string(),
+            tx_signature,
+            timestamp: now,
+        });
+        let next = if order.remaining_qty() <= QTY_EPSILON {
+            OrderStatus::Filled
+        } else {
+            OrderStatus::PartiallyFilled
+        };
+        // PartiallyFilled -> PartiallyFilled is a normal repeat fill, not a transition
+        if order.status != next {
+            Self::advance(order, next)?;
+        }
+        order.updated_at = now;
+        Ok(order.status)
+    }
+
+    /// Mark an order rejected by the venue or a pre-trade check
+    pub fn reject(&mut self, id: &str, reason: &str) -> Result<OrderStatus> {
+        self.close(id, OrderStatus::Rejected, Some(reason))
+    }
+
+    /// Cancel whatever is left of an order
+    pub fn cancel(&mut self, id: &str, reason: Option<&str>) -> Result<OrderStatus> {
+        self.close(id, OrderStatus::Canceled, reason)
+    }
+
+    /// Expire an order whose time in force elapsed
+    pub fn expire(&mut self, id: &str) -> Result<OrderStatus> {
+        self.close(id, OrderStatus::Expired, None)
+    }
+
+    /// Drop terminal orders last updated before `cutoff` (unix seconds)
+    pub fn prune_terminal(&mut self, cutoff: i64) {
+        self.orders.retain(|_, o| !(o.status.is_terminal() && o.updated_at < cutoff));
+    }
+
+    fn close(&mut self, id: &str, next: OrderStatus, reason: Option<&str>) -> Result<OrderStatus> {
+        let order = self.orders.get_mut(id).ok_or_else(|| anyhow!("unknown order {}", id))?;
+        Self::advance(order, next)?;
+        order.reason = reason.map(str::to_string);
+        order.updated_at = Utc::now().timestamp();
+        Ok(order.status)
+    }
+
+    fn advance(order: &mut ManagedOrder, next: OrderStatus) -> Result<()> {
+        if !order.status.can_transition_to(next) {
+            bail!("order {}: invalid transition {:?} -> {:?}", order.id, order.status, next);
+        }
+        log::debug!("Order {}: {:?} -> {:?}", order.id, order.status, next);
+        order.status = next;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn signal(action: SignalAction) -> Signal {
+        Signal {
+            strategy_id: "test".into(),
+            pair: TradingPair::new("SOL", "USDC"),
+            action,
+            price: 100.0,
+            size: 0.0,
+            confidence: 1.0,
+            order_type: OrderType::Market,
+            limit_price: None,
+            stop_price: None,
+            stop_loss: None,
+            take_profit: None,
+            timestamp: 1,
+            metadata: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn new_ids_skip_restored_orders() {
+        let mut om = OrderManager::new();
+        let restored = PendingOrder {
+            id: "test-1-1".into(),
+            strategy_id: "test".into(),
+            pair: TradingPair::new("SOL", "USDC"),
+            amount: 1.0,
+            is_buy: false,
+            order_type: OrderType::Stop,
+            limit_price: None,
+            stop_price: Some(90.0),
+            wallet: String::new(),
+            dex_preference: Vec::new(),
+            timestamp: 1,
+            attempts: 0,
+        };
+        assert_eq!(om.track_pending(&restored), "test-1-1");
+        let id = om.submit(&signal(SignalAction::Buy), 1.0);
+        assert_ne!(id, "test-1-1");
+        assert_eq!(om.get("test-1-1").unwrap().order_type, OrderType::Stop);
+    }
+
+    #[test]
+    fn partial_fills_complete_order() {
+        let mut om = OrderManager::new();
+        let id = om.submit(&signal(SignalAction::Buy), 2.0);
+        assert_eq!(om.get(&id).unwrap().status, OrderStatus::New);
+
+        let status = om.record_fill(&id, 1.0, 100.0, "paper", None).unwrap();
+        assert_eq!(status, OrderStatus::PartiallyFilled);
+        let status = om.record_fill(&id, 1.0, 102.0, "paper", None).unwrap();
+        assert_eq!(status, OrderStatus::Filled);
+
+        let order = om.get(&id).unwrap();
+        assert!((order.avg_fill_price().unwrap() - 101.0).abs() < 1e-9);
+        assert_eq!(om.open_orders().count(), 0);
+    }
+
+    #[test]
+    fn terminal_orders_reject_further_events() {
+        let mut om = OrderManager::new();
+        let id = om.submit(&signal(SignalAction::Sell), 1.0);
+        assert_eq!(om.reject(&id, "no liquidity").unwrap(), OrderStatus::Rejected);
+        assert!(om.record_fill(&id, 1.0, 100.0, "paper", None).is_err());
+        assert!(om.cancel(&id, None).is_err());
+        assert_eq!(om.get(&id).unwrap().reason.as_deref(), Some("no liquidity"));
+    }
+
+    #[test]
+    fn partially_filled_can_cancel_but_not_reject() {
+        let mut om = OrderManager::new();
+        let id = om.submit(&signal(SignalAction::Buy), 3.0);
+        om.record_fill(&id, 1.0, 100.0, "paper", None).unwrap();
+        assert!(om.reject(&id, "late").is_err());
+        assert_eq!(om.cancel(&id, Some("venue down")).unwrap(), OrderStatus::Canceled);
+        assert!((om.get(&id).unwrap().filled_qty() - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn overfill_is_an_error() {
+        let mut om = OrderManager::new();
+        let id = om.submit(&signal(SignalAction::Buy), 1.0);
+        assert!(om.record_fill(&id, 1.5, 100.0, "paper", None).is_err());
+        assert_eq!(om.get(&id).unwrap().status, OrderStatus::New);
+    }
+}
@@ -0,0 +1,193 @@
+//! PendingOrderBook: resting stop / stop-limit orders waiting for their trigger.
+//! Orders are mirrored into the persistence layer so they survive restarts.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::sync::mpsc::UnboundedSender;
+use tokio::sync::Mutex;
+use tokio::task::JoinHandle;
+
+use crate::market_data::ws::PriceCache;
+use crate::persistence::Persistence;
+use crate::utils::types::{OrderType, PendingOrder};
+
+/// Whether `price` crosses the stop of a resting order
+pub fn is_triggered(order: &PendingOrder, price: f64) -> bool {
+    match order.order_type {
+        | OrderType::Stop | OrderType::StopLimit => match order.stop_price {
+            | Some(sp) if order.is_buy => price >= sp,
+            | Some(sp) => price <= sp,
+            | None => false,
+        },
+        | _ => false,
+    }
+}
+
+/// Shared book of resting orders backed by [`Persistence`]
+#[derive(Clone)]
+pub struct PendingOrderBook {
+    orders: Arc<Mutex<Vec<PendingOrder>>>,
+    persistence: Arc<dyn Persistence + Send + Sync>,
+}
+
+impl PendingOrderBook {
+    pub fn new(persistence: Arc<dyn Persistence + Send + Sync>) -> Self {
+        Self { orders: Arc::new(Mutex::new(Vec::new())), persistence }
+    }
+
+    /// Reload untriggered orders saved by a previous run. Returns how many were restored.
+    pub async fn restore(&self) -> anyhow::Result<usize> {
+        let saved = self.persistence.load_pending_orders().await?;
+        let mut orders = self.orders.lock().await;
+        for po in saved {
+            if !orders.iter().any(|o| o.id == po.id) {
+                orders.push(po);
+            }
+        }
+        Ok(orders.len())
+    }
+
+    /// Park an order until its stop triggers
+    pub async fn add(&self, order: PendingOrder) -> anyhow::Result<()> {
+        self.persistence.save_pending_order(&order).await?;
+        let mut orders = self.orders.lock().await;
+        orders.retain(|o| o.id != order.id);
+        orders.push(order);
+        Ok(())
+    }
+
+    /// Put a triggered order back after a failed execution attempt
+    pub async fn rearm(&self, order: PendingOrder) -> anyhow::Result<()> {
+        self.add(order).await
+    }
+
+    /// Forget an order for good (executed, rejected or canceled)
+    pub async fn remove(&self, id: &str) -> anyhow::Result<Option<PendingOrder>> {
+        self.persistence.delete_pending_order(id).await?;
+        let mut orders = self.orders.lock().await;
+        let pos = orders.iter().position(|o| o.id == id);
+        Ok(pos.map(|i| orders.remove(i)))
+    }
+
+    pub async fn len(&self) -> usize {
+        self.orders.lock().await.len()
+    }
+
+    pub async fn is_empty(&self) -> bool {
+        self.orders.lock().await.is_empty()
+    }
+
+    /// Copy of the resting orders
+    pub async fn snapshot(&self) -> Vec<PendingOrder> {
+        self.orders.lock().await.clone()
+    }
+
+    /// Take every order whose stop is crossed by the cached prices. Triggered orders leave the
+    /// in-memory book but stay persisted until [`remove`](Self::remove) confirms the outcome.
+    pub async fn take_triggered(&self, prices: &PriceCache) -> Vec<PendingOrder> {
+        let mut orders = self.orders.lock().await;
+        if orders.is_empty() {
+            return Vec::new();
+        }
+        let cache = prices.read().await;
+        let mut triggered = Vec::new();
+        let mut i = 0;
+        while i < orders.len() {
+            let fire = cache
+                .get(&orders[i].pair)
+                .map(|price| is_triggered(&orders[i], *price))
+                .unwrap_or(false);
+            if fire {
+                triggered.push(orders.remove(i));
+            } else {
+                i += 1;
+            }
+        }
+        triggered
+    }
+
+    /// Poll the price cache every `every` and forward triggered orders to `tx`
+    pub fn spawn_trigger_watcher(
+        &self, prices: PriceCache, tx: UnboundedSender<PendingOrder>, every: Duration,
+    ) -> JoinHandle<()> {
+        let book = self.clone();
+        tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(every).await;
+                for order in book.take_triggered(&prices).await {
+                    log::info!("Stop triggered for order {} ({})", order.id, order.pair);
+                    if tx.send(order).is_err() {
+                        // Engine loop is gone; nothing left to feed
+                        return;
+                    }
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::persistence::NullPersistence;
+    use crate::utils::types::TradingPair;
+    use std::collections::HashMap;
+
+    fn stop(id: &str, is_buy: bool, stop_price: f64) -> PendingOrder {
+        PendingOrder {
+            id: id.into(),
+            strategy_id: "test".into(),
+            pair: TradingPair::new("SOL", "USDC"),
+            amount: 1.0,
+            is_buy,
+            order_type: OrderType::Stop,
+            limit_price: None,
+            stop_price: Some(stop_price),
+            wallet: String::new(),
+            dex_preference: Vec::new(),
+            timestamp: 0,
+            attempts: 0,
+        }
+    }
+
+    #[test]
+    fn trigger_direction_follows_side() {
+        assert!(is_triggered(&stop("a", true, 100.0), 101.0));
+        assert!(!is_triggered(&stop("a", true, 100.0), 99.0));
+        assert!(is_triggered(&stop("b", false, 100.0), 99.0));
+        assert!(!is_triggered(&stop("b", false, 100.0), 101.0));
+    }
+
+    #[tokio::test]
+    async fn only_crossed_orders_are_taken() {
+        let book = PendingOrderBook::new(Arc::new(NullPersistence));
+        book.add(stop("buy-stop", true, 110.0)).await.unwrap();
+        book.add(stop("sell-stop", false, 95.0)).await.unwrap();
+
+        let prices: PriceCache = Arc::new(tokio::sync::RwLock::new(HashMap::new()));
+        prices.write().await.insert(TradingPair::new("SOL", "USDC"), 94.0);
+
+        let fired = book.take_triggered(&prices).await;
+        assert_eq!(fired.len(), 1);
+        assert_eq!(fired[0].id, "sell-stop");
+        assert_eq!(book.len().await, 1);
+    }
+
+    #[tokio::test]
+    async fn paper_stops_are_not_restored_live() {
+        use crate::persistence::sqlite::SqlitePersistence;
+        let dir = tempfile::tempdir().unwrap();
+        let open = |paper| {
+            SqlitePersistence::new(Some(SqlitePersistence::path_in(dir.path(), paper)))
+        };
+
+        let paper = PendingOrderBook::new(Arc::new(open(true).await.unwrap()));
+        paper.add(stop("paper-stop", false, 95.0)).await.unwrap();
+
+        let live = PendingOrderBook::new(Arc::new(open(false).await.unwrap()));
+        assert_eq!(live.restore().await.unwrap(), 0);
+        let paper_again = PendingOrderBook::new(Arc::new(open(true).await.unwrap()));
+        assert_eq!(paper_again.restore().await.unwrap(), 1);
+    }
+}
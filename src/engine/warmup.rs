@@ -0,0 +1,174 @@
+//! Indicator warm-up on live start.
+//!
+//! Live strategies otherwise start from single-tick events with no candle history and need many
+//! of them before their indicators are valid. Before the feed starts, the last N bars of every
+//! subscribed symbol are loaded for each timeframe its strategies run on, from the local data
+//! store or the remote providers, and replayed through those strategies. Every replayed bar
+//! carries the candle history up to itself, as in a backtest; the signals are dropped.
+
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+use crate::backtest::providers::CSVHistoricalDataProvider;
+use crate::backtest::HistoricalDataProvider;
+use crate::config::WarmupConfig;
+use crate::strategies::{TimeFrame, TradingStrategy};
+use crate::utils::types::{Candle, MarketData, TradingPair};
+use crate::{Error, Result};
+
+/// Whether `strategy` consumes events of `symbol`; an empty symbol list means every symbol
+pub fn trades_symbol(strategy: &dyn TradingStrategy, symbol: &str) -> bool {
+    let symbols = strategy.symbols();
+    symbols.is_empty() || symbols.iter().any(|s| s.eq_ignore_ascii_case(symbol))
+}
+
+/// Last `bars` bars of `pair` from `BASE_QUOTE_<tf>.csv` in `dir`, `None` without such a file
+fn load_local(
+    dir: &Path, pair: &TradingPair, timeframe: TimeFrame, bars: usize,
+) -> Result<Option<Vec<MarketData>>> {
+    let file = dir.join(format!("{}_{}_{}.csv", pair.base, pair.quote, timeframe.label()));
+    if !file.exists() {
+        return Ok(None);
+    }
+    let mut data = CSVHistoricalDataProvider::new().with_pair(pair.clone()).load(&file)?;
+    let skip = data.len().saturating_sub(bars);
+    Ok(Some(data.split_off(skip)))
+}
+
+/// Warm-up bars of `symbol` at `timeframe`, oldest first: the local store first, then the
+/// remote providers
+pub async fn load_bars(
+    cfg: &WarmupConfig, symbol: &str, timeframe: TimeFrame,
+) -> Result<Vec<MarketData>> {
+    let pair = TradingPair::from_str(symbol)
+        .ok_or_else(|| Error::InvalidArgument(format!("invalid symbol '{symbol}'")))?;
+    if let Some(dir) = &cfg.data_dir {
+        if let Some(bars) = load_local(Path::new(dir), &pair, timeframe, cfg.bars)? {
+            return Ok(bars);
+        }
+    }
+    if !cfg.remote {
+        return Err(Error::DataError(format!("no local {} bars for {symbol}", timeframe.label())));
+    }
+    let label = timeframe.label();
+    let mut bars =
+        crate::backtest::importer::fetch_candles(&pair.base, &pair.quote, label, cfg.bars).await?;
+    for bar in &mut bars {
+        bar.symbol = symbol.to_string();
+        bar.pair = pair.clone();
+    }
+    bars.sort_by_key(|b| b.timestamp);
+    Ok(bars)
+}
+
+/// Feed `bars` (oldest first) through `strategy` and drop its signals
+pub async fn replay(strategy: &mut dyn TradingStrategy, bars: &[MarketData]) {
+    let candles: Vec<Candle> = bars.iter().map(MarketData::candle).collect();
+    for (i, bar) in bars.iter().enumerate() {
+        let md = MarketData { candles: candles[..=i].to_vec(), ..bar.clone() };
+        let _ = strategy.generate_signals(&md).await;
+    }
+}
+
+/// Warm up every strategy not in `skip` on the `symbols` it trades. Bars are loaded once per
+/// symbol and timeframe; symbols without bars are logged and left cold. Returns how many
+/// strategies saw at least one bar.
+pub async fn warm_up(
+    cfg: &WarmupConfig, symbols: &[String], strategies: &mut [Box<dyn TradingStrategy>],
+    skip: &HashSet<usize>,
+) -> usize {
+    let mut groups: HashMap<(&str, TimeFrame), Vec<usize>> = HashMap::new();
+    for (idx, strategy) in strategies.iter().enumerate() {
+        if skip.contains(&idx) {
+            continue;
+        }
+        for symbol in symbols {
+            if trades_symbol(strategy.as_ref(), symbol) {
+                let key = (symbol.as_str(), strategy.timeframe());
+                groups.entry(key).or_default().push(idx);
+            }
+        }
+    }
+
+    let mut warmed = HashSet::new();
+    for ((symbol, timeframe), members) in groups {
+        let bars = match load_bars(cfg, symbol, timeframe).await {
+            | Ok(bars) if !bars.is_empty() => bars,
+            | Ok(_) => continue,
+            | Err(e) => {
+                log::warn!("No warm-up bars for {symbol} {}: {e}", timeframe.label());
+                continue;
+            }
+        };
+        log::debug!("Warming up {} strategies on {} {symbol} bars", members.len(), bars.len());
+        for idx in members {
+            replay(strategies[idx].as_mut(), &bars).await;
+            warmed.insert(idx);
+        }
+    }
+    warmed.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trading::{Position, Signal};
+    use async_trait::async_trait;
+
+    /// Records the length of the candle history of every bar it sees
+    #[derive(Clone, Default)]
+    struct Recorder {
+        seen: Vec<usize>,
+    }
+
+    #[async_trait]
+    impl TradingStrategy for Recorder {
+        fn name(&self) -> &str {
+            "recorder"
+        }
+
+        fn timeframe(&self) -> TimeFrame {
+            TimeFrame::OneHour
+        }
+
+        fn symbols(&self) -> Vec<String> {
+            vec!["SOL/USDC".into()]
+        }
+
+        async fn generate_signals(&mut self, md: &MarketData) -> Vec<Signal> {
+            self.seen.push(md.candles.len());
+            Vec::new()
+        }
+
+        fn get_positions(&self) -> Vec<&Position> {
+            Vec::new()
+        }
+    }
+
+    #[tokio::test]
+    async fn local_bars_are_replayed_with_growing_history() {
+        std::env::set_var("BACKTEST_NO_CACHE", "1");
+        let dir = tempfile::tempdir().unwrap();
+        let body: String = (0..10).map(|i| format!("{},{}\n", i * 3600, 100 + i)).collect();
+        let file = dir.path().join("SOL_USDC_1h.csv");
+        std::fs::write(file, format!("timestamp,close\n{body}")).unwrap();
+        let cfg = WarmupConfig {
+            bars: 4,
+            data_dir: Some(dir.path().to_string_lossy().into_owned()),
+            remote: false,
+            ..Default::default()
+        };
+
+        let mut strategies: Vec<Box<dyn TradingStrategy>> =
+            vec![Box::new(Recorder::default()), Box::new(Recorder::default())];
+        let symbols = vec!["SOL/USDC".to_string(), "BONK/USDC".to_string()];
+        let skip = HashSet::from([1]);
+        assert_eq!(warm_up(&cfg, &symbols, &mut strategies, &skip).await, 1);
+
+        let bars = load_bars(&cfg, "SOL/USDC", TimeFrame::OneHour).await.unwrap();
+        assert_eq!(bars.first().map(|b| b.close), Some(106.0));
+        let mut probe = Recorder::default();
+        replay(&mut probe, &bars).await;
+        assert_eq!(probe.seen, vec![1, 2, 3, 4]);
+    }
+}
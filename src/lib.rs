@@ -46,9 +46,10 @@ use crate::analysis::wallet_analyzer::WalletAnalyzer;
 use crate::market_data::ws::{self as market_ws, PriceCache};
 use crate::performance::PerformanceMonitor;
 use crate::persistence::{EquitySnapshot, Persistence, TradeRecord};
-use crate::risk::position_sizer::{FixedFractionalSizer, PositionSizer};
-use crate::risk::{RiskAction, RiskRule};
-use crate::strategies::TradingStrategy;
+use crate::risk::position_sizer::PositionSizer;
+use crate::risk::portfolio_risk::{OrderIntent, PreTradeRiskEngine};
+use crate::risk::RiskRule;
+use crate::strategies::{StrategyContext, TimerBook, TradingStrategy};
 use crate::trading::{Signal as StratSignal, SignalType};
 use crate::utils::types::PendingOrder;
 use tokio_postgres::types::ToSql;
@@ -60,6 +61,9 @@ use std::sync::Arc;
 use tokio::sync::mpsc;
 use tokio::task::JoinHandle;
 
+/// Strategy id of the sell signals raised by the exit rules
+pub const RISK_EXIT_STRATEGY: &str = "risk_exit";
+
 /// Arbitrage opportunity struct
 #[derive(Debug, Clone)]
 pub struct ArbitrageOpportunity {
@@ -78,6 +82,16 @@ pub struct TradingEngine {
 
     // Trading strategies with performance monitoring
     strategies: Vec<Box<dyn strategies::TradingStrategy>>,
+    /// Timers registered by the strategies through their lifecycle hooks
+    strategy_timers: TimerBook,
+    /// Strategies restored from a checkpoint; they skip the indicator warm-up
+    restored_strategies: std::collections::HashSet<usize>,
+    /// Bars built from live trades at every strategy timeframe
+    candles: crate::engine::candle_aggregator::CandleAggregator,
+    /// L2 books built from order book events, per venue and consolidated
+    order_books: crate::engine::order_book::OrderBookManager,
+    /// Health of the market data streams while the router runs
+    stream_status: crate::engine::market_router::StreamStatusMap,
 
     // Performance monitoring
     performance_monitors: std::collections::HashMap<String, performance::PerformanceMonitor>,
@@ -93,14 +107,18 @@ pub struct TradingEngine {
     pub trading_wallet: String,  // For all trade execution
     pub personal_wallet: String, // For review and analytics
     pub wallet_analyzer: Option<WalletAnalyzer>,
-    // Wallet (None in paper mode)
+    // Signing wallet; paper mode uses a throwaway keypair, None when the live keypair is missing
     pub wallet: Option<Wallet>,
     // Portfolio tracking
     pub portfolio: crate::portfolio::Portfolio,
     // Position sizing
     position_sizer: Box<dyn PositionSizer>,
+    /// Lifecycle state of every order routed by the engine
+    pub order_manager: crate::engine::order_manager::OrderManager,
     // Risk management rules
     pub risk_rules: Vec<Box<dyn crate::risk::RiskRule>>,
+    /// Portfolio-level pre-trade limits (`risk.portfolio` in the config)
+    pub pre_trade_risk: Option<PreTradeRiskEngine>,
     // --- EXECUTION PARAMETERS ---
     pub slippage_bps: u16,
     pub max_fee_lamports: u64,
@@ -128,8 +146,8 @@ pub struct TradingEngine {
     // --- MARKET DATA CACHE ---
     pub price_cache: PriceCache,
     price_feed_handle: Option<JoinHandle<()>>,
-    // Pending stop/stop-limit orders
-    pending_orders: Arc<tokio::sync::Mutex<Vec<crate::utils::types::PendingOrder>>>,
+    /// Resting stop/stop-limit orders, mirrored into persistence
+    pub pending_orders: crate::engine::pending_orders::PendingOrderBook,
     scheduler_handle: Option<JoinHandle<()>>,
     dashboard_handle: Option<JoinHandle<()>>,
     dashboard_state: Option<crate::dashboard::SharedSnapshot>,
@@ -175,29 +193,7 @@ impl TradingEngine {
         use bs58;
         use solana_client::nonblocking::rpc_client::RpcClient;
         use solana_sdk::signature::{Keypair, Signer};
-        // Build position sizer from config
-        use crate::config::position_sizer::PositionSizerConfig;
-        use crate::risk::position_sizer::{KellySizer, LiveKellySizer, VolatilitySizer};
-        let position_sizer: Box<dyn PositionSizer> = match &config.risk.position_sizer {
-            | Some(PositionSizerConfig::FixedFractional { pct }) => {
-                Box::new(FixedFractionalSizer::new(*pct))
-            }
-            | Some(PositionSizerConfig::Kelly { win_rate, payoff_ratio, cap }) => {
-                Box::new(KellySizer::new(*win_rate, *payoff_ratio, *cap))
-            }
-            | Some(PositionSizerConfig::KellyLive { cap }) => {
-                use crate::performance::PerformanceMonitor;
-                use std::sync::Arc;
-                let pm = Arc::new(PerformanceMonitor::new());
-                Box::new(LiveKellySizer::new(*cap, pm))
-            }
-            | Some(PositionSizerConfig::Volatility { risk_pct, atr_mult }) => {
-                // Live ATR fetcher from global cache
-                let fetcher = |sym: &str| -> Option<f64> { crate::utils::atr_cache::get(sym) };
-                Box::new(VolatilitySizer::new(*risk_pct, *atr_mult, fetcher))
-            }
-            | None => Box::new(FixedFractionalSizer::new(0.01)),
-        };
+        let position_sizer = config.risk.build_position_sizer();
 
         // Build strategies first
         // Extract execution parameters before moving config
@@ -223,10 +219,12 @@ impl TradingEngine {
                 }
             }
         }
-        // Build persistence (TODO: load backend choice from config)
+        // Build persistence (TODO: load backend choice from config); paper and live keep
+        // separate databases
         use crate::persistence::sqlite::SqlitePersistence;
+        let db_path = SqlitePersistence::default_path(paper_trading);
         let persistence: std::sync::Arc<dyn Persistence + Send + Sync> =
-            match SqlitePersistence::new(None).await {
+            match SqlitePersistence::new(Some(db_path)).await {
                 | Ok(db) => std::sync::Arc::new(db),
                 | Err(_) => std::sync::Arc::new(crate::persistence::NullPersistence),
             };
@@ -240,6 +238,20 @@ impl TradingEngine {
                 }
             }
         }
+        // pick up indicator windows and positions from the last run's checkpoints
+        let mut restored_strategies = std::collections::HashSet::new();
+        if config.trading.state_checkpoint_secs > 0 {
+            let restored =
+                crate::engine::checkpoint::restore(persistence.as_ref(), &mut strategies_vec).await;
+            match restored {
+                | Ok(idx) if idx.is_empty() => {}
+                | Ok(idx) => {
+                    log::info!("Restored the state of {} strategies", idx.len());
+                    restored_strategies.extend(idx);
+                }
+                | Err(e) => log::warn!("Failed to restore strategy state: {e}"),
+            }
+        }
         // Build performance monitors map
         // Initialize price cache and WebSocket feed
         let price_cache: PriceCache = Arc::new(tokio::sync::RwLock::new(HashMap::new()));
@@ -271,13 +283,38 @@ impl TradingEngine {
             None
         };
 
+        // Paper mode executes against a simulated AMM through the same DexClient path as live
+        let mut dex_clients: HashMap<String, Box<dyn dex::DexClient>> = HashMap::new();
+        let wallet_instance = if paper_trading {
+            let paper = &config.trading.paper;
+            dex_clients.insert(
+                "simulated".to_string(),
+                Box::new(
+                    crate::dex::SimulatedDex::new(paper.seed)
+                        .with_fee_bps(paper.fee_bps)
+                        .with_default_depth(paper.pool_depth_quote)
+                        .with_price_source(price_cache.clone()),
+                ),
+            );
+            Some(Wallet::new(RpcClient::new(config.solana.rpc_url.clone()), Keypair::new()))
+        } else {
+            wallet_instance
+        };
+
         let portfolio = crate::portfolio::Portfolio::new(starting_cash);
-        let risk_rules: Vec<Box<dyn crate::risk::RiskRule>> = vec![
-            Box::new(crate::risk::StopLossRule::new(0.05)),
-            Box::new(crate::risk::TakeProfitRule::new(0.10)),
-        ];
-        let pending_orders: Arc<tokio::sync::Mutex<Vec<crate::utils::types::PendingOrder>>> =
-            Arc::new(tokio::sync::Mutex::new(Vec::new()));
+        let risk_rules = config.risk.build_rules();
+        let pending_orders =
+            crate::engine::pending_orders::PendingOrderBook::new(persistence.clone());
+        match pending_orders.restore().await {
+            | Ok(0) => {}
+            | Ok(n) => log::info!("Restored {} pending stop orders", n),
+            | Err(e) => log::warn!("Failed to restore pending orders: {e}"),
+        }
+        // track restored orders up front so new order ids never collide with theirs
+        let mut order_manager = crate::engine::order_manager::OrderManager::new();
+        for po in pending_orders.snapshot().await {
+            order_manager.track_pending(&po);
+        }
         let (retry_tx, retry_rx) =
             tokio::sync::mpsc::unbounded_channel::<crate::utils::types::PendingOrder>();
         // Start dashboard server if feature enabled
@@ -365,52 +402,11 @@ impl TradingEngine {
             tokio::spawn(hub.run());
         }
 
-        let scheduler_handle = {
-            let tx = tx_clone;
-            let cache_clone = price_cache.clone();
-            let orders_clone = pending_orders.clone();
-            tokio::spawn(async move {
-                use tokio::time::{sleep, Duration};
-                loop {
-                    sleep(Duration::from_secs(5)).await;
-                    let mut orders = orders_clone.lock().await;
-                    if orders.is_empty() {
-                        continue;
-                    }
-                    let mut i = 0;
-                    while i < orders.len() {
-                        let o = &orders[i];
-                        if let Some(price) = {
-                            let guard = cache_clone.read().await;
-                            guard.get(&o.pair).cloned()
-                        } {
-                            let triggered = match o.order_type {
-                                | crate::utils::types::OrderType::Stop
-                                | crate::utils::types::OrderType::StopLimit => {
-                                    if let Some(sp) = o.stop_price {
-                                        if o.is_buy {
-                                            price >= sp
-                                        } else {
-                                            price <= sp
-                                        }
-                                    } else {
-                                        false
-                                    }
-                                }
-                                | _ => false,
-                            };
-                            if triggered {
-                                // Currently we just remove it; engine will need explicit retry logic
-                                let triggered_order = orders.remove(i);
-                                let _ = tx.send(triggered_order);
-                                continue;
-                            }
-                        }
-                        i += 1;
-                    }
-                }
-            })
-        };
+        let scheduler_handle = pending_orders.spawn_trigger_watcher(
+            price_cache.clone(),
+            tx_clone,
+            std::time::Duration::from_secs(5),
+        );
 
         let mut perf_map = std::collections::HashMap::new();
         for strat in &strategies_vec {
@@ -426,9 +422,18 @@ impl TradingEngine {
         } else {
             None
         };
+        let pre_trade_risk = config.risk.portfolio.clone().map(PreTradeRiskEngine::new);
+        let limits = config.signal_limits();
         TradingEngine {
-            dex_clients: std::collections::HashMap::new(),
+            dex_clients,
+            candles: crate::engine::candle_aggregator::CandleAggregator::for_strategies(
+                &strategies_vec,
+            ),
+            order_books: crate::engine::order_book::OrderBookManager::new(),
+            stream_status: Default::default(),
             strategies: strategies_vec,
+            strategy_timers: TimerBook::default(),
+            restored_strategies,
             performance_monitors: perf_map,
             config,
             is_running: false,
@@ -439,15 +444,16 @@ impl TradingEngine {
             starting_balance: starting_cash,
             current_balance: starting_cash,
             max_position_pct: 0.0,
-            max_position_abs: 0.0,
-            max_open_trades: 0,
+            max_position_abs: limits.max_position_abs,
+            max_open_trades: limits.max_open_trades,
             stop_loss_pct: 0.0,
-            max_daily_loss_pct: 0.0,
+            max_daily_loss_pct: limits.max_daily_loss_pct,
             daily_loss: 0.0,
             open_trades: 0,
             trade_history: Vec::new(),
             open_positions: std::collections::HashMap::new(),
             position_sizer,
+            order_manager,
 
             portfolio,
             price_cache,
@@ -473,6 +479,7 @@ impl TradingEngine {
             paper_trading,
             enable_arbitrage: false,
             risk_rules,
+            pre_trade_risk,
             wallet: wallet_instance,
         }
     }
@@ -688,6 +695,15 @@ impl TradingEngine {
         }
     }
 
+    /// Limits applied to every incoming signal
+    pub fn signal_limits(&self) -> crate::risk::SignalLimits {
+        crate::risk::SignalLimits {
+            max_position_abs: self.max_position_abs,
+            max_open_trades: self.max_open_trades,
+            max_daily_loss_pct: self.max_daily_loss_pct,
+        }
+    }
+
     /// Evaluate position size for next trade
     pub fn position_size(&self) -> f64 {
         let pct_size = self.current_balance * self.max_position_pct;
@@ -716,18 +732,26 @@ impl TradingEngine {
 
     /// Apply trade effects of a single trade chunk, updating portfolio and returning realized PnL.
     pub fn apply_trade_effects(&mut self, sig: &Signal, chunk: f64) -> f64 {
+        self.apply_fill(sig, chunk, sig.price)
+    }
+
+    /// Apply a confirmed fill of `qty` at `price`, returning realized PnL.
+    pub fn apply_fill(&mut self, sig: &Signal, qty: f64, price: f64) -> f64 {
         let symbol_key = sig.pair.to_string();
         let pnl = match sig.action {
             | SignalAction::Buy => {
                 self.open_trades += 1;
-                self.portfolio.update_on_buy(&symbol_key, chunk, sig.price);
+                self.portfolio.update_on_buy(&symbol_key, qty, price);
                 0.0
             }
             | SignalAction::Sell => {
-                let realized = self.portfolio.update_on_sell(&symbol_key, chunk, sig.price);
+                let realized = self.portfolio.update_on_sell(&symbol_key, qty, price);
                 if self.open_trades > 0 {
                     self.open_trades -= 1;
                 }
+                if !self.portfolio.positions.contains_key(&symbol_key) {
+                    self.on_position_closed(&symbol_key);
+                }
                 realized
             }
             | _ => 0.0,
@@ -743,36 +767,87 @@ impl TradingEngine {
             .collect();
         // Update daily loss (USD cash for now)
         self.daily_loss = (self.starting_balance - self.current_balance).max(0.0);
-
-        // Evaluate risk after trade
-        self.evaluate_risk_rules();
+        self.notify_position_update(&symbol_key);
 
         pnl
     }
 
-    /// Evaluate risk rules
-    pub fn evaluate_risk_rules(&mut self) {
-        let cache_guard = self.price_cache.try_read();
-        if cache_guard.is_err() {
-            return;
+    /// Mark `pair` at `price` in the price cache and run the exit rules against it, so
+    /// trailing stops and other exits fire on price moves rather than only after fills
+    pub async fn on_price_update(&mut self, pair: &TradingPair, price: f64) -> anyhow::Result<()> {
+        self.price_cache.write().await.insert(pair.clone(), price);
+        self.enforce_risk_rules().await
+    }
+
+    /// Send a sell order for every exit the risk rules ask for. The ledger follows once the
+    /// venue confirms the fill, like for any other order.
+    pub async fn enforce_risk_rules(&mut self) -> anyhow::Result<()> {
+        let exits = self.evaluate_risk_rules();
+        if exits.is_empty() {
+            return Ok(());
         }
-        let cache = cache_guard.unwrap();
-        let positions_snapshot = self.portfolio.positions.clone();
-        for (sym, pos) in positions_snapshot {
+        self.handle_signals(exits).await
+    }
+
+    /// Evaluate risk rules against the cached prices of the open positions and return the
+    /// exits they call for as sell signals sized to the quantity to close
+    pub fn evaluate_risk_rules(&mut self) -> Vec<Signal> {
+        let Ok(cache) = self.price_cache.try_read() else {
+            return Vec::new();
+        };
+        let now = Utc::now().timestamp();
+        let mut exits = Vec::new();
+        for (sym, pos) in &self.portfolio.positions {
             if pos.size <= 0.0 {
                 continue;
             }
-            if let Some(pair) = TradingPair::from_str(&sym) {
-                if let Some(price) = cache.get(&pair) {
-                    for rule in &self.risk_rules {
-                        if let Some(RiskAction::ClosePosition) = rule.evaluate(&sym, &pos, *price) {
-                            let _ = self.portfolio.update_on_sell(&sym, pos.size, *price);
-                            break;
-                        }
-                    }
+            let Some(pair) = TradingPair::from_str(sym) else {
+                continue;
+            };
+            let Some(price) = cache.get(&pair).copied() else {
+                continue;
+            };
+            for rule in self.risk_rules.iter_mut() {
+                let action = rule.evaluate(sym, pos, price, now);
+                if let Some(qty) = action.and_then(|a| a.exit_qty(pos.size)) {
+                    log::info!("{} exit of {} {} at {}", rule.describe(), qty, sym, price);
+                    exits.push(Signal {
+                        strategy_id: RISK_EXIT_STRATEGY.to_string(),
+                        pair,
+                        action: SignalAction::Sell,
+                        price,
+                        size: qty,
+                        confidence: 1.0,
+                        order_type: crate::utils::types::OrderType::Market,
+                        limit_price: None,
+                        stop_price: None,
+                        stop_loss: None,
+                        take_profit: None,
+                        timestamp: now,
+                        metadata: HashMap::from([("exit_rule".to_string(), rule.describe())]),
+                    });
+                    break;
                 }
             }
         }
+        exits
+    }
+
+    /// Reset the per-position state of every risk rule for `symbol`
+    fn on_position_closed(&mut self, symbol: &str) {
+        for rule in self.risk_rules.iter_mut() {
+            rule.on_position_closed(symbol);
+        }
+    }
+
+    /// Live order books, for mid, spread and imbalance queries
+    pub fn order_books(&self) -> &crate::engine::order_book::OrderBookManager {
+        &self.order_books
+    }
+
+    /// Connection state, last message age and reconnect count of every market stream
+    pub fn stream_status(&self) -> Vec<(String, crate::engine::market_router::StreamStatus)> {
+        self.stream_status.snapshot()
     }
 
     /// Return total equity in USD (cash + unrealized)
@@ -789,66 +864,24 @@ impl TradingEngine {
             .total_sol_value(&|pair| cache_ref.as_ref().and_then(|c| c.get(pair)).cloned())
     }
 
-    pub async fn start_with_market_router(
-        &mut self, symbols: Vec<String>, helius_api_key: Option<String>,
-        openbook_program_id: Option<String>,
-    ) -> anyhow::Result<()> {
-        let mut router = MarketRouter::new();
-
-        // Prepare symbols for streams
-        let symbol_strs = symbols.to_vec();
-
-        // Add Binance stream
-        let binance_stream =
-            Box::new(crate::utils::binance_stream::BinanceStream::new(&symbol_strs));
-        router.add_stream(binance_stream);
-
-        // Add Helius stream if API key provided
-        if let Some(api_key) = helius_api_key {
-            let helius_stream = Box::new(crate::utils::helius_stream::HeliusStream::new(
-                &api_key,
-                openbook_program_id.as_deref(),
-            ));
-            router.add_stream(helius_stream);
-        }
-
-        // Add more streams as needed (Coinbase, Kraken, Serum, Triton, etc)
-        let coinbase_stream =
-            Box::new(crate::utils::coinbase_stream::CoinbaseStream::new(&symbol_strs));
-        router.add_stream(coinbase_stream);
-
-        let kraken_stream = Box::new(crate::utils::kraken_stream::KrakenStream::new());
-        router.add_stream(kraken_stream);
-        // SerumStream requires a market symbol, use the first symbol or fallback
-        let serum_market = symbol_strs
-            .first()
-            .cloned()
-            .unwrap_or_else(|| "SOL/USDC".to_string());
-        let serum_stream = Box::new(crate::utils::serum_stream::SerumStream::new(&serum_market));
-        router.add_stream(serum_stream);
-        // TritonStream requires api_key and market, use dummy key for now
-        let triton_api_key =
-            std::env::var("TRITON_API_KEY").unwrap_or_else(|_| "demo-key".to_string());
-        let triton_market = symbol_strs
-            .first()
-            .cloned()
-            .unwrap_or_else(|| "SOL/USDC".to_string());
-        let triton_stream = Box::new(crate::utils::triton_stream::TritonStream::new(
-            &triton_api_key,
-            &triton_market,
-        ));
-        router.add_stream(triton_stream);
+    pub async fn start_with_market_router(&mut self, symbols: Vec<String>) -> anyhow::Result<()> {
+        // Only the configured venues are subscribed (Helius included); see
+        // `[[market_data.sources]]`
+        let router = MarketRouter::from_config(&self.config.market_data, &symbols)?;
 
         // --- DEX Integration ---
         // Initialize all DEX clients and store in registry
-        let dex_names = ["jupiter", "raydium", "photon"];
-        let mut dex_clients = HashMap::new();
-        for name in dex_names.iter() {
-            if let Ok(client) = DexFactory::create_client(name) {
-                dex_clients.insert(name.to_string(), client);
+        // Paper mode keeps the simulated venue installed at construction
+        if !self.paper_trading {
+            let dex_names = ["jupiter", "raydium", "photon"];
+            let mut dex_clients = HashMap::new();
+            for name in dex_names.iter() {
+                if let Ok(client) = DexFactory::create_client(name) {
+                    dex_clients.insert(name.to_string(), client);
+                }
             }
+            self.dex_clients = dex_clients;
         }
-        self.dex_clients = dex_clients;
 
         // --- Wallet Setup ---
         // Set trading and personal wallet addresses
@@ -872,53 +905,225 @@ impl TradingEngine {
         self.update_dashboard_snapshot().await;
 
         log::info!("Subscribed symbols: {:?}", symbols);
+        // streams reconnect under the router's supervisors; a dead source never stops the loop
+        self.stream_status = router.status();
         let mut router_task = router;
         let symbols_clone = symbols.clone();
-        let router_handle = tokio::spawn(async move { router_task.run(symbols_clone, tx).await });
+        let mut router_handle =
+            tokio::spawn(async move { router_task.run(symbols_clone, tx).await });
 
         // Event loop: process market events until router terminates
         // Pending order receiver
         let mut retry_rx_opt = self.retry_rx.take();
-        tokio::select! {
-            res = async {
-                while let Some(evt) = rx.recv().await {
-                    if let Some(data) = TradingEngine::convert_market_event(&evt) {
-                        let mut collected_signals: Vec<Signal> = Vec::new();
-                        for strat in self.strategies.iter_mut() {
-                            let sigs = strat.generate_signals(&data).await;
-                            for s in sigs {
-
-                                if let Some(engine_sig) = TradingEngine::convert_strategy_signal(&s, strat.name()) {
-                                    collected_signals.push(engine_sig);
-                                }
-                            }
-                        }
-                        self.handle_signals(collected_signals).await?;
+        // strategy timers and quiet-market bar closes have one-second resolution
+        let mut timer_tick = tokio::time::interval(tokio::time::Duration::from_secs(1));
+        let checkpoint_secs = self.config.trading.state_checkpoint_secs;
+        let mut checkpoint_tick =
+            tokio::time::interval(tokio::time::Duration::from_secs(checkpoint_secs.max(1)));
+        self.warm_up_strategies(&symbols).await;
+        self.start_strategies();
+        let result = loop {
+            tokio::select! {
+                evt = rx.recv() => {
+                    let Some(evt) = evt else { break Ok(()) };
+                    if let Err(e) = self.dispatch_market_event(&evt).await {
+                        break Err(e);
                     }
-                }
-                Ok::<(), anyhow::Error>(())
-            } => { res?;
-                    Ok::<(), anyhow::Error>(())
                 },
-                        po_opt = async {
+                Some(order) = async {
                     if let Some(rx) = &mut retry_rx_opt { rx.recv().await } else { None }
                 } => {
-                    if let Some(order) = po_opt {
-                        self.process_pending_order(order).await?;
+                    if let Err(e) = self.process_pending_order(order).await {
+                        break Err(e);
                     }
-                    Ok::<(), anyhow::Error>(())
                 },
-            _ = snap_interval.tick() => {
-                self.sync_wallet_balance().await;
-                self.update_dashboard_snapshot().await;
-                Ok::<(), anyhow::Error>(())
-            },
-            router_res = router_handle => {
-                router_res??;
-                Ok::<(), anyhow::Error>(())
-            },
-        }?;
-        Ok(())
+                _ = snap_interval.tick() => {
+                    self.sync_wallet_balance().await;
+                    self.update_dashboard_snapshot().await;
+                    self.order_manager.prune_terminal(Utc::now().timestamp() - 86_400);
+                },
+                _ = timer_tick.tick() => {
+                    // prices of the websocket feed move the exit rules between trade events
+                    if let Err(e) = self.enforce_risk_rules().await {
+                        break Err(e);
+                    }
+                    if let Err(e) = self.flush_candles().await {
+                        break Err(e);
+                    }
+                    if let Err(e) = self.fire_strategy_timers().await {
+                        break Err(e);
+                    }
+                },
+                _ = checkpoint_tick.tick(), if checkpoint_secs > 0 => {
+                    self.checkpoint_strategies().await;
+                },
+                router_res = &mut router_handle => match router_res {
+                    | Ok(res) => break res,
+                    | Err(e) => break Err(e.into()),
+                },
+            }
+        };
+        self.stop_strategies();
+        if checkpoint_secs > 0 {
+            self.checkpoint_strategies().await;
+        }
+        result
+    }
+
+    /// Fold one market event into the candle aggregator and feed the bars it closes to the
+    /// strategies of their timeframe
+    async fn dispatch_market_event(
+        &mut self, evt: &crate::utils::market_stream::MarketEvent,
+    ) -> anyhow::Result<()> {
+        // book events update the depth attached to the next bars; gaps are logged by the manager
+        if matches!(evt, crate::utils::market_stream::MarketEvent::OrderBook { .. }) {
+            self.order_books.apply(evt);
+            return Ok(());
+        }
+        let Some(data) = TradingEngine::convert_market_event(evt) else {
+            return Ok(());
+        };
+        self.on_price_update(&data.pair, data.close).await?;
+        let qty = data.volume.unwrap_or(0.0);
+        let bars = self.candles.on_trade(&data.symbol, data.close, qty, data.timestamp);
+        self.observe_closed_bars(&bars);
+        let signals = self.signals_for_bars(bars).await;
+        self.handle_signals(signals).await
+    }
+
+    /// Feed the correlation estimator the closes of the finest timeframe's bars, so every
+    /// symbol's returns are sampled on the same clock (quiet periods close as flat bars)
+    fn observe_closed_bars(&mut self, bars: &[crate::engine::candle_aggregator::ClosedBar]) {
+        let (Some(engine), Some(&tf)) =
+            (self.pre_trade_risk.as_mut(), self.candles.timeframes().first())
+        else {
+            return;
+        };
+        for bar in bars.iter().filter(|b| b.timeframe == tf) {
+            engine.observe_price(&bar.data.pair.to_string(), bar.data.close);
+        }
+    }
+
+    /// Run every closed bar through the strategies trading at its timeframe, with the current
+    /// consolidated book of its symbol attached
+    async fn signals_for_bars(
+        &mut self, bars: Vec<crate::engine::candle_aggregator::ClosedBar>,
+    ) -> Vec<Signal> {
+        use crate::engine::order_book::DEFAULT_DEPTH;
+        let mut collected_signals: Vec<Signal> = Vec::new();
+        for mut bar in bars {
+            bar.data.order_book = self.order_books.book(&bar.data.symbol, DEFAULT_DEPTH);
+            for strat in self.strategies.iter_mut() {
+                if strat.timeframe() != bar.timeframe {
+                    continue;
+                }
+                for s in strat.generate_signals(&bar.data).await {
+                    if let Some(engine_sig) =
+                        TradingEngine::convert_strategy_signal(&s, strat.name())
+                    {
+                        collected_signals.push(engine_sig);
+                    }
+                }
+            }
+        }
+        collected_signals
+    }
+
+    /// Close the bars of quiet markets once their period is over
+    async fn flush_candles(&mut self) -> anyhow::Result<()> {
+        let bars = self.candles.flush(Utc::now().timestamp_millis());
+        if bars.is_empty() {
+            return Ok(());
+        }
+        self.observe_closed_bars(&bars);
+        let signals = self.signals_for_bars(bars).await;
+        if signals.is_empty() {
+            return Ok(());
+        }
+        self.handle_signals(signals).await
+    }
+
+    /// Replay recent history through the strategies so their indicators are valid from the
+    /// first live event. Signals of the replay are dropped, nothing is executed.
+    async fn warm_up_strategies(&mut self, symbols: &[String]) {
+        let cfg = &self.config.trading.warmup;
+        if !cfg.enabled || cfg.bars == 0 {
+            return;
+        }
+        let warmed = crate::engine::warmup::warm_up(
+            cfg,
+            symbols,
+            &mut self.strategies,
+            &self.restored_strategies,
+        )
+        .await;
+        log::info!("Warmed up {} of {} strategies", warmed, self.strategies.len());
+    }
+
+    /// Run `on_start` of every strategy and register the timers they ask for
+    fn start_strategies(&mut self) {
+        let now = Utc::now().timestamp();
+        for (idx, strat) in self.strategies.iter_mut().enumerate() {
+            let mut ctx = StrategyContext::new(now);
+            strat.on_start(&mut ctx);
+            self.strategy_timers.apply(idx, &mut ctx);
+        }
+    }
+
+    /// Run `on_stop` of every strategy and drop their timers
+    fn stop_strategies(&mut self) {
+        let now = Utc::now().timestamp();
+        for strat in self.strategies.iter_mut() {
+            strat.on_stop(&mut StrategyContext::new(now));
+        }
+        self.strategy_timers = TimerBook::default();
+    }
+
+    /// Save the runtime state of the strategies; a failed checkpoint is logged, not fatal
+    async fn checkpoint_strategies(&self) {
+        let now = Utc::now().timestamp();
+        let saved =
+            crate::engine::checkpoint::save(self.persistence.as_ref(), &self.strategies, now).await;
+        if let Err(e) = saved {
+            log::warn!("Failed to checkpoint strategy state: {e}");
+        }
+    }
+
+    /// Fire the strategy timers that are due and handle their signals
+    async fn fire_strategy_timers(&mut self) -> anyhow::Result<()> {
+        let now = Utc::now().timestamp();
+        let mut collected_signals: Vec<Signal> = Vec::new();
+        for (idx, id, _) in self.strategy_timers.due(now) {
+            let Some(strat) = self.strategies.get_mut(idx) else {
+                continue;
+            };
+            let mut ctx = StrategyContext::new(now);
+            for s in strat.on_timer(id, &mut ctx) {
+                if let Some(engine_sig) = TradingEngine::convert_strategy_signal(&s, strat.name()) {
+                    collected_signals.push(engine_sig);
+                }
+            }
+            self.strategy_timers.apply(idx, &mut ctx);
+        }
+        if collected_signals.is_empty() {
+            return Ok(());
+        }
+        self.handle_signals(collected_signals).await
+    }
+
+    /// Tell the strategies trading `symbol` that the portfolio position changed
+    fn notify_position_update(&mut self, symbol: &str) {
+        let position = self.portfolio.positions.get(symbol).cloned().unwrap_or_default();
+        let now = Utc::now().timestamp();
+        for (idx, strat) in self.strategies.iter_mut().enumerate() {
+            let symbols = strat.symbols();
+            if !symbols.is_empty() && !symbols.iter().any(|s| s.eq_ignore_ascii_case(symbol)) {
+                continue;
+            }
+            let mut ctx = StrategyContext::new(now);
+            strat.on_position_update(symbol, &position, &mut ctx);
+            self.strategy_timers.apply(idx, &mut ctx);
+        }
     }
 
     /// Convert a strategy-facing Signal into engine/internal Signal format
@@ -956,9 +1161,12 @@ impl TradingEngine {
         })
     }
 
+    /// Execute a stop / stop-limit order whose trigger fired. Failed attempts are re-armed
+    /// until `MAX_TRIGGER_ATTEMPTS`, after which the order is rejected.
     async fn process_pending_order(
-        &mut self, po: crate::utils::types::PendingOrder,
+        &mut self, mut po: crate::utils::types::PendingOrder,
     ) -> anyhow::Result<()> {
+        const MAX_TRIGGER_ATTEMPTS: u32 = 5;
         // Convert order type
         let new_order_type = match po.order_type {
             | crate::utils::types::OrderType::Stop => crate::utils::types::OrderType::Market,
@@ -971,48 +1179,240 @@ impl TradingEngine {
             }
             | other => other,
         };
-        let preferred = [
-            &po.dex_preference[..],
-            &["jupiter".to_string(), "raydium".to_string(), "photon".to_string()],
-        ]
-        .concat();
-        let mut executed = false;
-        for dex_name in preferred.iter() {
+        let order_id = self.order_manager.track_pending(&po);
+        po.id = order_id.clone();
+        po.attempts += 1;
+        let sig = Signal {
+            strategy_id: po.strategy_id.clone(),
+            pair: po.pair.clone(),
+            action: if po.is_buy { SignalAction::Buy } else { SignalAction::Sell },
+            price: po.stop_price.or(po.limit_price).unwrap_or_default(),
+            size: po.amount,
+            confidence: 1.0,
+            order_type: new_order_type,
+            limit_price: po.limit_price,
+            stop_price: None,
+            stop_loss: None,
+            take_profit: None,
+            timestamp: po.timestamp,
+            metadata: std::collections::HashMap::new(),
+        };
+
+        // Same child orders as a fresh signal; a failed piece re-arms only what is left
+        let wallet = self.next_wallet().or_else(|| self.wallet.clone());
+        let mut failure = None;
+        for piece in self.split_chunks(&sig.strategy_id, po.amount) {
+            let execution = match &wallet {
+                | Some(wallet) => {
+                    self.execute_slice(&sig, piece, wallet, &po.dex_preference).await
+                }
+                | None => Err(anyhow::anyhow!("wallet not available for live trading")),
+            };
+            match execution {
+                | Ok((venue, tx)) => {
+                    let price = self.confirmed_fill_price(&sig, &venue, &tx).await;
+                    let tx = Some(tx);
+                    if let Err(e) =
+                        self.on_order_fill(&sig, &order_id, piece, price, &venue, tx).await
+                    {
+                        log::error!("Failed to book fill for order {}: {}", order_id, e);
+                    }
+                    po.amount -= piece;
+                }
+                | Err(err) => {
+                    failure = Some(err);
+                    break;
+                }
+            }
+        }
+        match failure {
+            | None => {
+                self.pending_orders.remove(&order_id).await?;
+            }
+            | Some(err) if po.attempts < MAX_TRIGGER_ATTEMPTS => {
+                log::warn!(
+                    "Triggered order {} failed (attempt {}): {} – re-arming",
+                    order_id,
+                    po.attempts,
+                    err
+                );
+                self.pending_orders.rearm(po).await?;
+            }
+            | Some(err) => {
+                log::error!(
+                    "Giving up on triggered order {} after {} attempts",
+                    order_id,
+                    po.attempts
+                );
+                self.on_order_failed(&sig, &order_id, err);
+                self.pending_orders.remove(&order_id).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Child order sizes for `qty`: pieces of at most `split_chunk_sol` above
+    /// `split_threshold_sol`; arbitrage legs are never split
+    fn split_chunks(&self, strategy_id: &str, qty: f64) -> Vec<f64> {
+        let arbitrage = strategy_id.to_lowercase().contains("arbitrage");
+        if qty <= self.split_threshold_sol || self.split_chunk_sol <= 0.0 || arbitrage {
+            return vec![qty];
+        }
+        let mut pieces = Vec::new();
+        let mut remaining = qty;
+        while remaining > 1e-12 {
+            let piece = remaining.min(self.split_chunk_sol);
+            pieces.push(piece);
+            remaining -= piece;
+        }
+        pieces
+    }
+
+    /// Route one slice of an order through the DEX preference list, returning the executing
+    /// venue and its transaction signature.
+    async fn execute_slice(
+        &self, sig: &Signal, qty: f64, wallet: &Wallet, preferred: &[String],
+    ) -> anyhow::Result<(String, String)> {
+        let mut route: Vec<&str> = preferred.iter().map(String::as_str).collect();
+        for default in ["jupiter", "raydium", "photon"] {
+            if !route.contains(&default) {
+                route.push(default);
+            }
+        }
+        // Any other registered venue (e.g. the simulated DEX in paper mode) goes last
+        let mut others: Vec<&str> = self.dex_clients.keys().map(String::as_str).collect();
+        others.sort_unstable();
+        for name in others {
+            if !route.contains(&name) {
+                route.push(name);
+            }
+        }
+        let mut last_err = anyhow::anyhow!("no DEX client available");
+        for dex_name in route {
             if let Some(dex) = self.dex_clients.get(dex_name) {
                 match dex
                     .execute_trade(
-                        &po.pair.base,
-                        &po.pair.quote,
-                        po.amount,
-                        po.is_buy,
+                        &sig.pair.base,
+                        &sig.pair.quote,
+                        qty,
+                        sig.action == SignalAction::Buy,
                         self.slippage_bps,
                         self.max_fee_lamports,
-                        new_order_type,
-                        po.limit_price,
+                        sig.order_type,
+                        sig.limit_price,
+                        sig.stop_price,
                         None,
-                        None,
-                        self.wallet.as_ref().expect("wallet not available"),
+                        wallet,
                     )
                     .await
                 {
-                    | Ok(_) => {
-                        executed = true;
-                        break;
-                    }
+                    | Ok(tx) => return Ok((dex_name.to_string(), tx)),
                     | Err(e) => {
-                        log::warn!("Retry {} failed: {}", dex_name, e);
-                        continue;
+                        log::warn!("{} execution failed: {}", dex_name, e);
+                        last_err = e.into();
                     }
                 }
             }
         }
-        if !executed {
-            // push back to queue?
-            log::error!("Failed to execute retried order: {:?}", po);
+        Err(last_err)
+    }
+
+    /// Price a confirmed fill is booked at: the venue-reported execution price, else the live
+    /// mid when cached, else the order's own limit (for limit orders) or signal price.
+    async fn confirmed_fill_price(&self, sig: &Signal, venue: &str, tx_signature: &str) -> f64 {
+        if let Some(dex) = self.dex_clients.get(venue) {
+            if let Some(p) = dex.fill_price(tx_signature).await.filter(|p| *p > 0.0) {
+                return p;
+            }
+        }
+        match self.get_live_price(&sig.pair).await {
+            | Some(p) if p > 0.0 => p,
+            | _ => sig
+                .limit_price
+                .filter(|_| sig.order_type == crate::utils::types::OrderType::Limit)
+                .unwrap_or(sig.price),
+        }
+    }
+
+    /// Book a confirmed fill: advance the order, update the portfolio at the fill price and
+    /// notify the originating strategy.
+    async fn on_order_fill(
+        &mut self, sig: &Signal, order_id: &str, qty: f64, price: f64, venue: &str,
+        tx_signature: Option<String>,
+    ) -> anyhow::Result<()> {
+        let status = self.order_manager.record_fill(order_id, qty, price, venue, tx_signature)?;
+        let pnl = self.apply_fill(sig, qty, price);
+        let order = match self.order_manager.get(order_id) {
+            | Some(o) => o.to_order(o.fills.last()),
+            | None => return Ok(()),
+        };
+        log::info!("Order {} {:?}: {} @ {:.6} via {}", order_id, status, qty, price, venue);
+
+        let record = TradeRecord {
+            id: None,
+            timestamp: chrono::DateTime::<chrono::Utc>::from_timestamp(order.timestamp, 0)
+                .unwrap_or_else(Utc::now)
+                .naive_utc(),
+            symbol: order.symbol.clone(),
+            side: match sig.action {
+                | SignalAction::Buy => "buy".into(),
+                | SignalAction::Sell => "sell".into(),
+                | _ => "other".into(),
+            },
+            qty,
+            price,
+            pnl,
+        };
+        if !self.paper_trading {
+            let _ = self.persistence.save_trade(&record).await;
+        }
+        if let Some(mon) = self.performance_monitors.get(&sig.strategy_id) {
+            let _ = mon
+                .record_trade(&sig.strategy_id, &order, None, pnl, 0.0001, None)
+                .await;
         }
+        if let Some(strat) = self
+            .strategies
+            .iter_mut()
+            .find(|s| s.name() == sig.strategy_id)
+        {
+            strat.on_order_filled(&order);
+        }
+        self.trade_history.push(record);
         Ok(())
     }
 
+    /// Close out an order after an execution error and surface the error to its strategy.
+    /// Orders that already filled partially are canceled, untouched ones are rejected.
+    fn on_order_failed(&mut self, sig: &Signal, order_id: &str, err: anyhow::Error) {
+        let reason = err.to_string();
+        let partially_filled = self
+            .order_manager
+            .get(order_id)
+            .map(|o| o.status == crate::utils::types::OrderStatus::PartiallyFilled)
+            .unwrap_or(false);
+        let res = if partially_filled {
+            self.order_manager.cancel(order_id, Some(&reason))
+        } else {
+            self.order_manager.reject(order_id, &reason)
+        };
+        match res {
+            | Ok(status) => log::warn!("Order {} {:?}: {}", order_id, status, reason),
+            | Err(e) => log::warn!("Order {} could not be closed: {}", order_id, e),
+        }
+        let order = match self.order_manager.get(order_id) {
+            | Some(o) => o.to_order(None),
+            | None => return,
+        };
+        if let Some(strat) = self
+            .strategies
+            .iter_mut()
+            .find(|s| s.name() == sig.strategy_id)
+        {
+            strat.on_trade_error(&order, &err);
+        }
+    }
+
     #[cfg_attr(not(feature = "sidecar"), allow(unused_mut))]
     async fn handle_signals(&mut self, mut signals: Vec<Signal>) -> anyhow::Result<()> {
         #[cfg(feature = "sidecar")]
@@ -1063,177 +1463,118 @@ impl TradingEngine {
                 }
             }
         }
+        let limits = self.signal_limits();
         for sig in signals {
             // Risk checks
-            if self.open_trades >= self.max_open_trades {
-                log::warn!("Max open trades reached – signal ignored: {:?}", sig);
-                continue;
-            }
-            if self.daily_loss / self.starting_balance >= self.max_daily_loss_pct {
-                log::warn!("Daily loss limit reached – signal ignored: {:?}", sig);
+            let side = match sig.action {
+                | SignalAction::Buy => OrderSide::Buy,
+                | _ => OrderSide::Sell,
+            };
+            if let Some(reason) =
+                limits.check(side, self.open_trades, self.daily_loss, self.starting_balance)
+            {
+                log::warn!("{:?} limit reached – signal ignored: {:?}", reason, sig);
                 continue;
             }
-            // Decide amount via configurable position sizer
-            let mut chunk = self
-                .position_sizer
-                .size(self.current_balance, &sig.pair.base)
-                .await;
-            if chunk > self.max_position_abs {
-                chunk = self.max_position_abs;
-            }
-            // Determine trade splitting based on config and strategy type
-            let mut trade_chunks: Vec<f64> = Vec::new();
-            if chunk > self.split_threshold_sol
-                && !sig.strategy_id.to_lowercase().contains("arbitrage")
-            {
-                let mut remaining = chunk;
-                while remaining > 0.0 {
-                    let piece = remaining.min(self.split_chunk_sol);
-                    trade_chunks.push(piece);
-                    remaining -= piece;
-                }
+            // Risk exits carry the quantity to close; everything else goes through the sizer
+            let mut chunk = if sig.strategy_id == RISK_EXIT_STRATEGY {
+                sig.size
             } else {
-                trade_chunks.push(chunk);
+                let sized = self.position_sizer.size(self.current_balance, &sig.pair.base).await;
+                limits.cap(sized)
+            };
+            if let Some(engine) = &self.pre_trade_risk {
+                let side = match sig.action {
+                    | SignalAction::Buy => Some(OrderSide::Buy),
+                    | SignalAction::Sell => Some(OrderSide::Sell),
+                    | _ => None,
+                };
+                if let Some(side) = side {
+                    let prices: HashMap<String, f64> = match self.price_cache.try_read() {
+                        | Ok(cache) => cache.iter().map(|(p, px)| (p.to_string(), *px)).collect(),
+                        | Err(_) => HashMap::new(),
+                    };
+                    let symbol = sig.pair.to_string();
+                    let price = prices.get(&symbol).copied().unwrap_or(sig.price);
+                    let intent = OrderIntent { symbol: &symbol, side, qty: chunk, price };
+                    let (allowed, decision) =
+                        engine.approve(&intent, &self.portfolio.risk_view(&prices));
+                    if let Some(d) = decision {
+                        log::warn!(
+                            "Pre-trade risk {:?} ({:?}) – {} {} reduced to {}",
+                            d.action,
+                            d.limit,
+                            symbol,
+                            chunk,
+                            allowed
+                        );
+                    }
+                    chunk = allowed;
+                }
             }
+            let trade_chunks = self.split_chunks(&sig.strategy_id, chunk);
             if chunk == 0.0 {
                 continue;
             }
-            // Attempt trade on available DEX clients in preferred order
+            let order_id = self.order_manager.submit(&sig, chunk);
             // Determine signer wallet using rotation (falls back to trading_wallet)
-            let wallet_ref = self.next_wallet().unwrap_or_else(|| {
-                self.wallet
-                    .clone()
-                    .expect("wallet not available for live trading")
-            });
+            let wallet_ref = self.next_wallet().or_else(|| self.wallet.clone());
 
-            let mut total_pnl_chunked = 0.0;
-            for &chunk in trade_chunks.iter() {
-                if !self.paper_trading {
-                    let preferred = ["jupiter", "raydium", "photon"];
-                    let mut executed = false;
-                    let mut last_err: Option<anyhow::Error> = None;
-                    for dex_name in preferred.iter() {
-                        if let Some(dex) = self.dex_clients.get(*dex_name) {
-                            match dex.execute_trade(
-                                &sig.pair.base,
-                                &sig.pair.quote,
-                                chunk,
-                                matches!(sig.action, crate::utils::types::SignalAction::Buy),
-                                self.slippage_bps,
-                                self.max_fee_lamports,
-                                sig.order_type,
-                                sig.limit_price,
-                                sig.stop_price,
-                                None,
-                                &wallet_ref,
-                            )
-                            .await
-                            {
-                                | Ok(_) => {
-                                    // Persist trade record (pnl unknown at entry)
-                                    let rec = TradeRecord {
-                                        id: None,
-                                        timestamp: Utc::now().naive_utc(),
-                                        symbol: sig.pair.to_string(),
-                                        side: if matches!(
-                                            sig.action,
-                                            crate::utils::types::SignalAction::Buy
-                                        ) {
-                                            "buy".into()
-                                        } else {
-                                            "sell".into()
-                                        },
-                                        qty: chunk,
-                                        price: sig.price,
-                                        pnl: 0.0,
-                                    };
-                                    let _ = self.persistence.save_trade(&rec).await;
-                                    executed = true;
-                                    break;
-                                }
-                                | Err(e) => {
-                                    log::warn!("{} execution failed: {}", dex_name, e);
-                                    last_err = Some(e.into());
-                                    continue;
-                                }
-                            }
+            for &piece in trade_chunks.iter() {
+                let execution = match &wallet_ref {
+                    | Some(wallet) => self.execute_slice(&sig, piece, wallet, &[]).await,
+                    | None => Err(anyhow::anyhow!("wallet not available for live trading")),
+                };
+                match execution {
+                    | Ok((venue, tx)) => {
+                        let price = self.confirmed_fill_price(&sig, &venue, &tx).await;
+                        let tx = Some(tx);
+                        if let Err(e) =
+                            self.on_order_fill(&sig, &order_id, piece, price, &venue, tx).await
+                        {
+                            log::error!("Failed to book fill for order {}: {}", order_id, e);
                         }
                     }
-                    if !executed {
-                        #[allow(clippy::collapsible_if)]
-                        if let Some(err) = &last_err {
-                            if sig.order_type == crate::utils::types::OrderType::Stop
-                                || sig.order_type == crate::utils::types::OrderType::StopLimit
-                            {
-                                if err.to_string().contains("Stop price not triggered") {
-                                    let po = PendingOrder {
-                                        pair: sig.pair.clone(),
-                                        amount: chunk,
-                                        is_buy: matches!(
-                                            sig.action,
-                                            crate::utils::types::SignalAction::Buy
-                                        ),
-                                        order_type: sig.order_type,
-                                        limit_price: sig.limit_price,
-                                        stop_price: sig.stop_price,
-                                        wallet: String::new(),
-                                        dex_preference: Vec::new(),
-                                        timestamp: sig.timestamp,
-                                    };
-                                }
+                    | Err(err) => {
+                        let untriggered_stop = matches!(
+                            sig.order_type,
+                            crate::utils::types::OrderType::Stop
+                                | crate::utils::types::OrderType::StopLimit
+                        ) && err.to_string().contains("Stop price not triggered");
+                        if untriggered_stop {
+                            // Order stays New until the stop price is reached
+                            let remaining = self
+                                .order_manager
+                                .get(&order_id)
+                                .map(|o| o.remaining_qty())
+                                .unwrap_or(piece);
+                            let po = PendingOrder {
+                                id: order_id.clone(),
+                                strategy_id: sig.strategy_id.clone(),
+                                pair: sig.pair.clone(),
+                                amount: remaining,
+                                is_buy: matches!(
+                                    sig.action,
+                                    crate::utils::types::SignalAction::Buy
+                                ),
+                                order_type: sig.order_type,
+                                limit_price: sig.limit_price,
+                                stop_price: sig.stop_price,
+                                wallet: String::new(),
+                                dex_preference: Vec::new(),
+                                timestamp: sig.timestamp,
+                                attempts: 0,
+                            };
+                            log::info!("Order {} waiting for stop {:?}", order_id, sig.stop_price);
+                            if let Err(e) = self.pending_orders.add(po).await {
+                                log::error!("Failed to park pending order {}: {}", order_id, e);
                             }
+                        } else {
+                            self.on_order_failed(&sig, &order_id, err);
                         }
-                    } else {
-                        log::info!("[PAPER] would execute trade: {:?}", sig);
+                        break;
                     }
                 }
-                // bookkeeping per chunk
-                let pnl_chunk = self.apply_trade_effects(&sig, chunk);
-                // Build order record for this chunk
-                let order = Order {
-                    id: format!("{}-{}-{}", sig.strategy_id, sig.timestamp, rand::random::<u16>()),
-                    symbol: sig.pair.to_string(),
-                    price: sig.price,
-                    size: chunk,
-                    side: if sig.action == SignalAction::Buy {
-                        OrderSide::Buy
-                    } else {
-                        OrderSide::Sell
-                    },
-                    order_type: crate::utils::types::OrderType::Market,
-                    timestamp: sig.timestamp,
-                };
-                // Performance monitor
-                if let Some(mon) = self.performance_monitors.get(&sig.strategy_id) {
-                    let _ = mon
-                        .record_trade(&sig.strategy_id, &order, None, pnl_chunk, 0.0001, None)
-                        .await;
-                }
-                // Notify strategy
-                if let Some(strat) = self
-                    .strategies
-                    .iter_mut()
-                    .find(|s| s.name() == sig.strategy_id)
-                {
-                    strat.on_order_filled(&order);
-                }
-                // Append history
-                self.trade_history.push(TradeRecord {
-                    id: None,
-                    timestamp: chrono::DateTime::<chrono::Utc>::from_timestamp(sig.timestamp, 0)
-                        .unwrap()
-                        .naive_utc(),
-                    symbol: sig.pair.to_string(),
-                    side: match sig.action {
-                        | SignalAction::Buy => "buy".into(),
-                        | SignalAction::Sell => "sell".into(),
-                        | _ => "other".into(),
-                    },
-                    qty: chunk,
-                    price: sig.price,
-                    pnl: pnl_chunk,
-                });
-                total_pnl_chunked += pnl_chunk;
             }
         }
 
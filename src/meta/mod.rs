@@ -1,18 +1,21 @@
 //! Meta-strategy engine: selects the best-performing strategy for a given dataset
 //! by running back-tests (with caching) and ranking by Sharpe ratio & draw-down.
-//! This is an early, synchronous MVP – can be improved with async and more metrics later.
+//! Strategies are backtested in parallel with rayon on one shared copy of the data.
 
 use std::path::Path;
 
 use anyhow::Result;
 
+use crate::backtest::overfitting::{OverfittingReport, DEFAULT_CSCV_GROUPS};
 use crate::backtest::{
-    cache::BacktestCache, providers::CSVHistoricalDataProvider, Backtester, SimMode,
+    cache::BacktestCache, providers::CSVHistoricalDataProvider, Backtester,
+    HistoricalDataProvider, SimMode,
 };
 use crate::strategies::TradingStrategyClone;
 
 use crate::persistence;
 use crate::strategies::{registry::default_strategies, TradingStrategy};
+use rayon::prelude::*;
 use std::sync::Arc;
 
 /// Simple ranking result
@@ -20,6 +23,8 @@ pub struct RankedStrategy {
     pub strategy: Box<dyn TradingStrategy>,
     pub sharpe: f64,
     pub max_drawdown: f64,
+    /// Selection bias across every strategy evaluated for the pick
+    pub overfitting: Option<OverfittingReport>,
 }
 
 pub struct MetaStrategyEngine {
@@ -42,47 +47,91 @@ impl MetaStrategyEngine {
     }
 
     /// Evaluate strategies on the file & return the best-performing one (highest Sharpe, drawdown < 30%)
+    /// together with deflated Sharpe and PBO diagnostics across all of them
     pub fn select_best_strategy(&mut self, data_file: &Path) -> Result<RankedStrategy> {
         let base_provider = CSVHistoricalDataProvider::new();
-        let mut best: Option<RankedStrategy> = None;
+        // load once; every strategy backtests its own copy on a rayon worker
+        let market_data = base_provider.load_many(&[data_file.to_path_buf()])?;
+        let reports = self
+            .strategies
+            .par_iter()
+            .map(|strategy| {
+                // Backtester consumes a Vec, so wrap a fresh clone of the one strategy
+                let mut bt = Backtester {
+                    cache: Some(self.cache.clone()),
+                    sim_mode: SimMode::Bar,
+                    slippage_bps: 5,
+                    fee_bps: 3,
+                    persistence: Some(Arc::new(persistence::NullPersistence)),
+                    risk_rules: vec![
+                        Box::new(crate::risk::StopLossRule::new(0.05)),
+                        Box::new(crate::risk::TakeProfitRule::new(0.10)),
+                    ],
+                    ..Backtester::new(
+                        Box::new(base_provider.clone()),
+                        &self.timeframe,
+                        self.starting_balance,
+                        vec![strategy.box_clone() as Box<dyn TradingStrategy>],
+                    )
+                };
+                futures::executor::block_on(bt.run_with_data(&market_data))
+            })
+            .collect::<crate::Result<Vec<_>>>()?;
 
-        for strategy in &self.strategies {
-            // Obtain a fresh clone for the backtester run
-            let cloned = strategy.box_clone();
-            // Backtester consumes a Vec, so wrap the one strategy
-            let mut bt = Backtester {
-                data_provider: Box::new(base_provider.clone()),
-                timeframe: self.timeframe.clone(),
-                starting_balance: self.starting_balance,
-                strategies: vec![cloned as Box<dyn TradingStrategy>],
-                cache: Some(self.cache.clone()),
-                sim_mode: SimMode::Bar,
-                slippage_bps: 5,
-                fee_bps: 3,
-                persistence: Some(Arc::new(persistence::NullPersistence)),
-                risk_rules: vec![
-                    Box::new(crate::risk::StopLossRule::new(0.05)),
-                    Box::new(crate::risk::TakeProfitRule::new(0.10)),
-                ],
-            };
-
-            let rpt = futures::executor::block_on(bt.run(data_file))?;
+        let mut best: Option<(usize, RankedStrategy)> = None;
+        let mut trial_returns = Vec::new();
+        for (idx, (strategy, rpt)) in self.strategies.iter().zip(reports).enumerate() {
             let ranked = RankedStrategy {
                 strategy: strategy.box_clone(),
                 sharpe: rpt.sharpe,
                 max_drawdown: rpt.max_drawdown,
+                overfitting: None,
             };
+            trial_returns.push(rpt.returns);
 
             let is_better = match &best {
                 | None => true,
-                | Some(b) => ranked.sharpe > b.sharpe,
+                | Some((_, b)) => ranked.sharpe > b.sharpe,
             } && ranked.max_drawdown < 0.3; // filter high DD
 
             if is_better {
-                best = Some(ranked);
+                best = Some((idx, ranked));
             }
         }
 
-        best.ok_or_else(|| anyhow::anyhow!("No suitable strategy found"))
+        let (picked, mut best) =
+            best.ok_or_else(|| anyhow::anyhow!("No suitable strategy found"))?;
+        // diagnose the pick itself, not the highest-Sharpe trial the drawdown filter may reject
+        let diagnostics =
+            OverfittingReport::from_trials(&trial_returns, picked, DEFAULT_CSCV_GROUPS);
+        best.overfitting = match diagnostics {
+            | Ok(report) => Some(report),
+            | Err(e) => {
+                log::warn!("Overfitting diagnostics failed: {e}");
+                None
+            }
+        };
+        Ok(best)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn select_best_strategy_backtests_every_strategy() {
+        std::env::set_var("BACKTEST_NO_CACHE", "1");
+        let dir = tempfile::tempdir().unwrap();
+        let body: String = (0..300)
+            .map(|i| format!("{},{}\n", i * 3600, 100.0 + 5.0 * (i as f64 / 10.0).sin()))
+            .collect();
+        let file = dir.path().join("SOL_USDC_1h.csv");
+        std::fs::write(&file, format!("timestamp,close\n{body}")).unwrap();
+        let cache = dir.path().join("cache");
+        let mut engine = MetaStrategyEngine::new("1h", 10_000.0, cache.to_str().unwrap()).unwrap();
+        let ranked = engine.select_best_strategy(&file).unwrap();
+        assert!(ranked.sharpe.is_finite());
+        assert!(ranked.max_drawdown < 0.3);
     }
 }
@@ -114,7 +114,8 @@ impl StrategyMetrics {
         (kelly * 0.5).clamp(0.01, 0.5) // Between 1% and 50%
     }
 
-    /// Calculate risk of ruin
+    /// Calculate risk of ruin (closed-form approximation; `backtest::robustness` simulates it
+    /// from a backtest's trades instead)
     pub fn risk_of_ruin(&self) -> f64 {
         if self.winning_trades == 0 || self.losing_trades == 0 {
             return 0.5; // 50% if not enough data
@@ -9,6 +9,8 @@ use async_trait::async_trait;
 use chrono::NaiveDateTime;
 use serde::{Deserialize, Serialize};
 
+use crate::utils::types::PendingOrder;
+
 /// A minimal representation of a trade suitable for persistence.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TradeRecord {
@@ -41,6 +43,20 @@ pub struct BacktestSummary {
     pub max_drawdown: f64,
 }
 
+/// Checkpointed runtime state of one live strategy instance.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StrategyStateRecord {
+    /// Strategy name and symbols, unique per instance
+    pub key: String,
+    /// `TradingStrategy::state_version` at the time of the checkpoint
+    pub version: u32,
+    /// Params the instance ran with; state of differently configured instances is stale
+    pub params: serde_json::Value,
+    pub state: serde_json::Value,
+    /// Unix time of the checkpoint
+    pub saved_at: i64,
+}
+
 #[async_trait]
 pub trait Persistence: Send + Sync {
     /// Persist a single trade fill.
@@ -52,6 +68,24 @@ pub trait Persistence: Send + Sync {
     /// Persist a completed backtest summary.
     async fn save_backtest(&self, rpt: &BacktestSummary) -> anyhow::Result<()>;
 
+    /// Insert or replace a resting stop / stop-limit order.
+    async fn save_pending_order(&self, order: &PendingOrder) -> anyhow::Result<()>;
+
+    /// Remove a resting order once it executed or was abandoned.
+    async fn delete_pending_order(&self, id: &str) -> anyhow::Result<()>;
+
+    /// Load all resting orders, oldest first.
+    async fn load_pending_orders(&self) -> anyhow::Result<Vec<PendingOrder>>;
+
+    /// Insert or replace the checkpoint of a strategy instance.
+    async fn save_strategy_state(&self, record: &StrategyStateRecord) -> anyhow::Result<()>;
+
+    /// Load every strategy checkpoint.
+    async fn load_strategy_states(&self) -> anyhow::Result<Vec<StrategyStateRecord>>;
+
+    /// Drop the checkpoint of a strategy instance.
+    async fn delete_strategy_state(&self, key: &str) -> anyhow::Result<()>;
+
     /// Flush / close any outstanding connections.
     async fn flush(&self) -> anyhow::Result<()>;
 }
@@ -74,6 +108,24 @@ impl Persistence for NullPersistence {
     async fn save_backtest(&self, _b: &BacktestSummary) -> anyhow::Result<()> {
         Ok(())
     }
+    async fn save_pending_order(&self, _o: &PendingOrder) -> anyhow::Result<()> {
+        Ok(())
+    }
+    async fn delete_pending_order(&self, _id: &str) -> anyhow::Result<()> {
+        Ok(())
+    }
+    async fn load_pending_orders(&self) -> anyhow::Result<Vec<PendingOrder>> {
+        Ok(Vec::new())
+    }
+    async fn save_strategy_state(&self, _r: &StrategyStateRecord) -> anyhow::Result<()> {
+        Ok(())
+    }
+    async fn load_strategy_states(&self) -> anyhow::Result<Vec<StrategyStateRecord>> {
+        Ok(Vec::new())
+    }
+    async fn delete_strategy_state(&self, _key: &str) -> anyhow::Result<()> {
+        Ok(())
+    }
     async fn flush(&self) -> anyhow::Result<()> {
         Ok(())
     }
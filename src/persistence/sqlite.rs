@@ -4,10 +4,11 @@
 use async_trait::async_trait;
 use chrono::{NaiveDateTime, TimeZone, Utc};
 use rusqlite::{params, Connection};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
 
-use super::{BacktestSummary, EquitySnapshot, Persistence, TradeRecord};
+use super::{BacktestSummary, EquitySnapshot, Persistence, StrategyStateRecord, TradeRecord};
+use crate::utils::types::PendingOrder;
 
 /// Thread-safe SQLite wrapper shared across async tasks.
 #[derive(Clone)]
@@ -18,17 +19,25 @@ pub struct SqlitePersistence {
 impl SqlitePersistence {
     /// Open (or create) the DB file under the user data dir.
     pub async fn new(db_path: Option<PathBuf>) -> anyhow::Result<Self> {
-        let path = db_path.unwrap_or_else(|| {
-            let mut p = dirs::data_local_dir().unwrap_or_else(|| PathBuf::from("."));
-            p.push("algotraderv2");
-            std::fs::create_dir_all(&p).ok();
-            p.push("trades.db");
-            p
-        });
+        let path = db_path.unwrap_or_else(|| Self::default_path(false));
         let conn = tokio::task::spawn_blocking(move || Connection::open(path)).await??;
         init_schema(&conn)?;
         Ok(Self { conn: Arc::new(Mutex::new(conn)) })
     }
+
+    /// DB file of a trading mode under the user data dir
+    pub fn default_path(paper_trading: bool) -> PathBuf {
+        let mut dir = dirs::data_local_dir().unwrap_or_else(|| PathBuf::from("."));
+        dir.push("algotraderv2");
+        std::fs::create_dir_all(&dir).ok();
+        Self::path_in(&dir, paper_trading)
+    }
+
+    /// DB file of a trading mode in `dir`. Paper trading gets its own file so its trades,
+    /// resting stops and checkpoints are never picked up by a live run (and vice versa).
+    pub fn path_in(dir: &Path, paper_trading: bool) -> PathBuf {
+        dir.join(if paper_trading { "paper_trades.db" } else { "trades.db" })
+    }
 }
 
 fn init_schema(conn: &Connection) -> anyhow::Result<()> {
@@ -57,6 +66,17 @@ fn init_schema(conn: &Connection) -> anyhow::Result<()> {
              sharpe        REAL NOT NULL,
              max_drawdown  REAL NOT NULL,
              created_at    INTEGER NOT NULL
+         );
+         CREATE TABLE IF NOT EXISTS pending_orders (
+             id           TEXT PRIMARY KEY,
+             payload      TEXT NOT NULL,
+             created_at   INTEGER NOT NULL
+         );
+         CREATE TABLE IF NOT EXISTS strategy_state (
+             key          TEXT PRIMARY KEY,
+             version      INTEGER NOT NULL,
+             payload      TEXT NOT NULL,
+             saved_at     INTEGER NOT NULL
          );",
     )?;
     Ok(())
@@ -107,6 +127,106 @@ impl Persistence for SqlitePersistence {
         Ok(())
     }
 
+    async fn save_pending_order(&self, order: &PendingOrder) -> anyhow::Result<()> {
+        let conn = self.conn.clone();
+        let id = order.id.clone();
+        let payload = serde_json::to_string(order)?;
+        let created_at = order.timestamp;
+        tokio::task::spawn_blocking(move || {
+            conn.lock().unwrap().execute(
+                "INSERT OR REPLACE INTO pending_orders (id, payload, created_at) VALUES (?1, ?2, ?3)",
+                params![id, payload, created_at],
+            )?;
+            Ok::<_, rusqlite::Error>(())
+        })
+        .await??;
+        Ok(())
+    }
+
+    async fn delete_pending_order(&self, id: &str) -> anyhow::Result<()> {
+        let conn = self.conn.clone();
+        let id = id.to_string();
+        tokio::task::spawn_blocking(move || {
+            conn.lock()
+                .unwrap()
+                .execute("DELETE FROM pending_orders WHERE id = ?1", params![id])?;
+            Ok::<_, rusqlite::Error>(())
+        })
+        .await??;
+        Ok(())
+    }
+
+    async fn load_pending_orders(&self) -> anyhow::Result<Vec<PendingOrder>> {
+        let conn = self.conn.clone();
+        let payloads = tokio::task::spawn_blocking(move || {
+            let guard = conn.lock().unwrap();
+            let mut stmt =
+                guard.prepare("SELECT payload FROM pending_orders ORDER BY created_at, id")?;
+            let rows = stmt
+                .query_map([], |row| row.get::<_, String>(0))?
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok::<_, rusqlite::Error>(rows)
+        })
+        .await??;
+        let mut orders = Vec::with_capacity(payloads.len());
+        for payload in payloads {
+            match serde_json::from_str::<PendingOrder>(&payload) {
+                | Ok(o) => orders.push(o),
+                | Err(e) => log::warn!("Skipping unreadable pending order: {}", e),
+            }
+        }
+        Ok(orders)
+    }
+
+    async fn save_strategy_state(&self, record: &StrategyStateRecord) -> anyhow::Result<()> {
+        let conn = self.conn.clone();
+        let (key, version, saved_at) = (record.key.clone(), record.version, record.saved_at);
+        let payload = serde_json::to_string(record)?;
+        tokio::task::spawn_blocking(move || {
+            conn.lock().unwrap().execute(
+                "INSERT OR REPLACE INTO strategy_state (key, version, payload, saved_at) VALUES (?1, ?2, ?3, ?4)",
+                params![key, version, payload, saved_at],
+            )?;
+            Ok::<_, rusqlite::Error>(())
+        })
+        .await??;
+        Ok(())
+    }
+
+    async fn load_strategy_states(&self) -> anyhow::Result<Vec<StrategyStateRecord>> {
+        let conn = self.conn.clone();
+        let payloads = tokio::task::spawn_blocking(move || {
+            let guard = conn.lock().unwrap();
+            let mut stmt = guard.prepare("SELECT payload FROM strategy_state ORDER BY key")?;
+            let rows = stmt
+                .query_map([], |row| row.get::<_, String>(0))?
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok::<_, rusqlite::Error>(rows)
+        })
+        .await??;
+        let mut records = Vec::with_capacity(payloads.len());
+        for payload in payloads {
+            match serde_json::from_str::<StrategyStateRecord>(&payload) {
+                | Ok(r) => records.push(r),
+                | Err(e) => log::warn!("Skipping unreadable strategy checkpoint: {}", e),
+            }
+        }
+        Ok(records)
+    }
+
+    async fn delete_strategy_state(&self, key: &str) -> anyhow::Result<()> {
+        let conn = self.conn.clone();
+        let key = key.to_string();
+        tokio::task::spawn_blocking(move || {
+            conn.lock()
+                .unwrap()
+                .execute("DELETE FROM strategy_state WHERE key = ?1", params![key])?;
+            Ok::<_, rusqlite::Error>(())
+        })
+        .await??;
+        Ok(())
+    }
+
     async fn flush(&self) -> anyhow::Result<()> {
         Ok(())
     }
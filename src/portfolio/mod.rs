@@ -93,6 +93,13 @@ impl Portfolio {
             .sum()
     }
 
+    /// Snapshot for portfolio-level pre-trade risk checks
+    pub fn risk_view<'a>(
+        &'a self, prices: &'a HashMap<String, f64>,
+    ) -> crate::risk::portfolio_risk::PortfolioView<'a> {
+        crate::risk::portfolio_risk::PortfolioView::new(self.cash_usd, &self.positions, prices)
+    }
+
     pub fn total_usd_value(&self, price_lookup: &impl Fn(&TradingPair) -> Option<f64>) -> f64 {
         self.cash_usd + self.unrealized_pnl(price_lookup)
     }
@@ -1,28 +1,120 @@
-//! Basic risk management rules (stop-loss / take-profit)
+//! Basic risk management rules (stop-loss / take-profit, trailing and time stops)
 //! This module is intentionally lightweight so it can be reused by both the
 //! back-tester and the live trading engine without additional dependencies.
 
+use std::collections::{HashMap, HashSet};
+
 use crate::portfolio::Position;
+use crate::utils::types::OrderSide;
 
+pub mod portfolio_risk;
 pub mod position_sizer;
 
-/// Action requested by a risk-rule evaluation.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Action requested by a risk-rule evaluation or a pre-trade check.
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum RiskAction {
     /// Close the entire position at market.
     ClosePosition,
+    /// Close this quantity of the position at market.
+    ClosePartial(f64),
+    /// Drop the order.
+    Reject,
+    /// Send the order with this smaller quantity.
+    Resize(f64),
+    /// Only orders that reduce the existing position may go through.
+    ReduceOnly,
+}
+
+impl RiskAction {
+    /// Quantity to sell out of a position of `position_size` for exit actions
+    pub fn exit_qty(&self, position_size: f64) -> Option<f64> {
+        match self {
+            | RiskAction::ClosePosition => Some(position_size),
+            | RiskAction::ClosePartial(qty) => Some(qty.min(position_size)),
+            | _ => None,
+        }
+    }
+}
+
+/// Per-signal throttles applied before a signal becomes an order, by both the live engine
+/// and the backtester.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SignalLimits {
+    /// Largest single order, in base units
+    pub max_position_abs: f64,
+    /// New entries are refused while this many trades are open
+    pub max_open_trades: usize,
+    /// New entries are refused once losses reach this fraction of the reference balance
+    pub max_daily_loss_pct: f64,
+}
+
+impl Default for SignalLimits {
+    /// No limits
+    fn default() -> Self {
+        Self {
+            max_position_abs: f64::INFINITY,
+            max_open_trades: usize::MAX,
+            max_daily_loss_pct: f64::INFINITY,
+        }
+    }
+}
+
+/// Limit that refused a signal
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SignalRejection {
+    MaxOpenTrades,
+    DailyLoss,
+}
+
+impl SignalLimits {
+    /// Why a signal on `side` must be refused, if it must. Sells only reduce exposure and
+    /// always pass.
+    pub fn check(
+        &self, side: OrderSide, open_trades: usize, loss: f64, reference_balance: f64,
+    ) -> Option<SignalRejection> {
+        if side == OrderSide::Sell {
+            return None;
+        }
+        if open_trades >= self.max_open_trades {
+            return Some(SignalRejection::MaxOpenTrades);
+        }
+        if reference_balance > 0.0 && loss / reference_balance >= self.max_daily_loss_pct {
+            return Some(SignalRejection::DailyLoss);
+        }
+        None
+    }
+
+    /// `qty` capped at `max_position_abs`
+    pub fn cap(&self, qty: f64) -> f64 {
+        qty.min(self.max_position_abs)
+    }
 }
 
 /// Generic interface for risk-management rules.
+///
+/// Rules may keep per-position state keyed by symbol (high-water mark, entry time, ...).
+/// Callers must report flattened positions through [`RiskRule::on_position_closed`] so the
+/// next position in the same symbol starts fresh.
 pub trait RiskRule: Send + Sync {
     /// Evaluate the rule for the given position and current price.
     ///
     /// * `symbol` – instrument symbol (e.g. "SOL/USDC").
     /// * `pos` – current position (size > 0 means long; we only trade spot longs for now).
     /// * `current_price` – latest trade/mark price.
+    /// * `now` – unix timestamp (seconds) of that price; bar time in backtests.
     ///
     /// Return `Some(RiskAction)` if the rule triggers, otherwise `None`.
-    fn evaluate(&self, symbol: &str, pos: &Position, current_price: f64) -> Option<RiskAction>;
+    fn evaluate(
+        &mut self, symbol: &str, pos: &Position, current_price: f64, now: i64,
+    ) -> Option<RiskAction>;
+
+    /// The position in `symbol` was closed; drop any state kept for it.
+    fn on_position_closed(&mut self, _symbol: &str) {}
+
+    /// Rule type and parameters, without per-position state (used in backtest fingerprints).
+    fn describe(&self) -> String {
+        std::any::type_name::<Self>().to_string()
+    }
 
     /// Clone boxed trait-objects safely.
     fn box_clone(&self) -> Box<dyn RiskRule>;
@@ -47,7 +139,9 @@ impl StopLossRule {
 }
 
 impl RiskRule for StopLossRule {
-    fn evaluate(&self, _symbol: &str, pos: &Position, current_price: f64) -> Option<RiskAction> {
+    fn evaluate(
+        &mut self, _symbol: &str, pos: &Position, current_price: f64, _now: i64,
+    ) -> Option<RiskAction> {
         if pos.size > 0.0 {
             let threshold = pos.average_entry_price * (1.0 - self.pct);
             if current_price <= threshold {
@@ -57,6 +151,10 @@ impl RiskRule for StopLossRule {
         None
     }
 
+    fn describe(&self) -> String {
+        format!("stop_loss({})", self.pct)
+    }
+
     fn box_clone(&self) -> Box<dyn RiskRule> {
         Box::new(self.clone())
     }
@@ -75,7 +173,9 @@ impl TakeProfitRule {
 }
 
 impl RiskRule for TakeProfitRule {
-    fn evaluate(&self, _symbol: &str, pos: &Position, current_price: f64) -> Option<RiskAction> {
+    fn evaluate(
+        &mut self, _symbol: &str, pos: &Position, current_price: f64, _now: i64,
+    ) -> Option<RiskAction> {
         if pos.size > 0.0 {
             let threshold = pos.average_entry_price * (1.0 + self.pct);
             if current_price >= threshold {
@@ -85,7 +185,269 @@ impl RiskRule for TakeProfitRule {
         None
     }
 
+    fn describe(&self) -> String {
+        format!("take_profit({})", self.pct)
+    }
+
+    fn box_clone(&self) -> Box<dyn RiskRule> {
+        Box::new(self.clone())
+    }
+}
+
+/// How far below the high-water mark a trailing stop sits
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TrailDistance {
+    /// Fraction of the high-water mark (0.05 = 5 %)
+    Percent(f64),
+    /// Multiple of the symbol's latest ATR from `utils::atr_cache`
+    Atr(f64),
+}
+
+/// Trailing stop: close once price falls `distance` below the highest price seen since entry.
+/// ATR stops stay inactive until an ATR for the symbol has been published.
+#[derive(Debug, Clone)]
+pub struct TrailingStopRule {
+    distance: TrailDistance,
+    high_water: HashMap<String, f64>,
+}
+
+impl TrailingStopRule {
+    pub fn new(distance: TrailDistance) -> Self {
+        Self { distance, high_water: HashMap::new() }
+    }
+}
+
+impl RiskRule for TrailingStopRule {
+    fn evaluate(
+        &mut self, symbol: &str, pos: &Position, current_price: f64, _now: i64,
+    ) -> Option<RiskAction> {
+        if pos.size <= 0.0 {
+            return None;
+        }
+        let hwm = self.high_water.entry(symbol.to_string()).or_insert(pos.average_entry_price);
+        *hwm = hwm.max(current_price);
+        let stop = match self.distance {
+            | TrailDistance::Percent(pct) => *hwm * (1.0 - pct),
+            | TrailDistance::Atr(mult) => *hwm - mult * crate::utils::atr_cache::get(symbol)?,
+        };
+        (current_price <= stop).then_some(RiskAction::ClosePosition)
+    }
+
+    fn on_position_closed(&mut self, symbol: &str) {
+        self.high_water.remove(symbol);
+    }
+
+    fn describe(&self) -> String {
+        format!("trailing_stop({:?})", self.distance)
+    }
+
+    fn box_clone(&self) -> Box<dyn RiskRule> {
+        Box::new(self.clone())
+    }
+}
+
+/// Time stop: close a position held longer than `max_secs`. The holding period starts at the
+/// first evaluation that sees the position open.
+#[derive(Debug, Clone)]
+pub struct MaxHoldingRule {
+    max_secs: i64,
+    opened_at: HashMap<String, i64>,
+}
+
+impl MaxHoldingRule {
+    pub fn new(max_secs: i64) -> Self {
+        Self { max_secs, opened_at: HashMap::new() }
+    }
+}
+
+impl RiskRule for MaxHoldingRule {
+    fn evaluate(
+        &mut self, symbol: &str, pos: &Position, _current_price: f64, now: i64,
+    ) -> Option<RiskAction> {
+        if pos.size <= 0.0 {
+            return None;
+        }
+        let opened = *self.opened_at.entry(symbol.to_string()).or_insert(now);
+        (now - opened >= self.max_secs).then_some(RiskAction::ClosePosition)
+    }
+
+    fn on_position_closed(&mut self, symbol: &str) {
+        self.opened_at.remove(symbol);
+    }
+
+    fn describe(&self) -> String {
+        format!("max_holding({})", self.max_secs)
+    }
+
+    fn box_clone(&self) -> Box<dyn RiskRule> {
+        Box::new(self.clone())
+    }
+}
+
+/// Break-even stop: once price has gained `trigger_pct` over entry, close the position if it
+/// falls back to entry plus `offset_pct`.
+#[derive(Debug, Clone)]
+pub struct BreakEvenRule {
+    trigger_pct: f64,
+    offset_pct: f64,
+    armed: HashSet<String>,
+}
+
+impl BreakEvenRule {
+    pub fn new(trigger_pct: f64, offset_pct: f64) -> Self {
+        Self { trigger_pct, offset_pct, armed: HashSet::new() }
+    }
+}
+
+impl RiskRule for BreakEvenRule {
+    fn evaluate(
+        &mut self, symbol: &str, pos: &Position, current_price: f64, _now: i64,
+    ) -> Option<RiskAction> {
+        if pos.size <= 0.0 {
+            return None;
+        }
+        let entry = pos.average_entry_price;
+        if current_price >= entry * (1.0 + self.trigger_pct) {
+            self.armed.insert(symbol.to_string());
+        }
+        let hit = current_price <= entry * (1.0 + self.offset_pct);
+        (self.armed.contains(symbol) && hit).then_some(RiskAction::ClosePosition)
+    }
+
+    fn on_position_closed(&mut self, symbol: &str) {
+        self.armed.remove(symbol);
+    }
+
+    fn describe(&self) -> String {
+        format!("break_even({}, {})", self.trigger_pct, self.offset_pct)
+    }
+
+    fn box_clone(&self) -> Box<dyn RiskRule> {
+        Box::new(self.clone())
+    }
+}
+
+#[derive(Debug, Clone)]
+struct LadderState {
+    /// Position size when the ladder first saw it
+    initial_size: f64,
+    /// Index of the next rung to take
+    next: usize,
+}
+
+/// Partial take-profit ladder: at each `(gain_pct, close_fraction)` rung, sell that fraction
+/// of the initial position. Rungs fire in ascending order of gain, at most one per evaluation.
+#[derive(Debug, Clone)]
+pub struct TakeProfitLadderRule {
+    rungs: Vec<(f64, f64)>,
+    state: HashMap<String, LadderState>,
+}
+
+impl TakeProfitLadderRule {
+    pub fn new(mut rungs: Vec<(f64, f64)>) -> Self {
+        rungs.sort_by(|a, b| a.0.total_cmp(&b.0));
+        Self { rungs, state: HashMap::new() }
+    }
+}
+
+impl RiskRule for TakeProfitLadderRule {
+    fn evaluate(
+        &mut self, symbol: &str, pos: &Position, current_price: f64, _now: i64,
+    ) -> Option<RiskAction> {
+        if pos.size <= 0.0 {
+            return None;
+        }
+        let state = self
+            .state
+            .entry(symbol.to_string())
+            .or_insert(LadderState { initial_size: pos.size, next: 0 });
+        let (gain, fraction) = *self.rungs.get(state.next)?;
+        if current_price < pos.average_entry_price * (1.0 + gain) {
+            return None;
+        }
+        state.next += 1;
+        let qty = (state.initial_size * fraction).min(pos.size);
+        Some(if qty >= pos.size {
+            RiskAction::ClosePosition
+        } else {
+            RiskAction::ClosePartial(qty)
+        })
+    }
+
+    fn on_position_closed(&mut self, symbol: &str) {
+        self.state.remove(symbol);
+    }
+
+    fn describe(&self) -> String {
+        format!("take_profit_ladder({:?})", self.rungs)
+    }
+
     fn box_clone(&self) -> Box<dyn RiskRule> {
         Box::new(self.clone())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn long(size: f64, entry: f64) -> Position {
+        Position { size, average_entry_price: entry, realized_pnl: 0.0 }
+    }
+
+    #[test]
+    fn signal_limits_only_throttle_entries() {
+        let limits =
+            SignalLimits { max_open_trades: 2, max_daily_loss_pct: 0.05, ..Default::default() };
+        assert_eq!(limits.check(OrderSide::Buy, 1, 0.0, 1_000.0), None);
+        let full = limits.check(OrderSide::Buy, 2, 0.0, 1_000.0);
+        assert_eq!(full, Some(SignalRejection::MaxOpenTrades));
+        let down = limits.check(OrderSide::Buy, 0, 50.0, 1_000.0);
+        assert_eq!(down, Some(SignalRejection::DailyLoss));
+        assert_eq!(limits.check(OrderSide::Sell, 5, 500.0, 1_000.0), None);
+    }
+
+    #[test]
+    fn trailing_stop_follows_high_water_mark() {
+        let mut rule = TrailingStopRule::new(TrailDistance::Percent(0.1));
+        let pos = long(1.0, 100.0);
+        assert_eq!(rule.evaluate("SOL/USDC", &pos, 150.0, 0), None);
+        assert_eq!(rule.evaluate("SOL/USDC", &pos, 136.0, 1), None);
+        assert_eq!(rule.evaluate("SOL/USDC", &pos, 135.0, 2), Some(RiskAction::ClosePosition));
+        rule.on_position_closed("SOL/USDC");
+        // a new position starts from its own entry
+        assert_eq!(rule.evaluate("SOL/USDC", &pos, 95.0, 3), None);
+    }
+
+    #[test]
+    fn time_stop_counts_from_first_sight() {
+        let mut rule = MaxHoldingRule::new(3_600);
+        let pos = long(1.0, 100.0);
+        assert_eq!(rule.evaluate("SOL/USDC", &pos, 100.0, 1_000), None);
+        assert_eq!(
+            rule.evaluate("SOL/USDC", &pos, 100.0, 4_600),
+            Some(RiskAction::ClosePosition)
+        );
+    }
+
+    #[test]
+    fn break_even_arms_after_gain() {
+        let mut rule = BreakEvenRule::new(0.05, 0.0);
+        let pos = long(1.0, 100.0);
+        assert_eq!(rule.evaluate("SOL/USDC", &pos, 99.0, 0), None);
+        assert_eq!(rule.evaluate("SOL/USDC", &pos, 106.0, 1), None);
+        assert_eq!(rule.evaluate("SOL/USDC", &pos, 100.0, 2), Some(RiskAction::ClosePosition));
+    }
+
+    #[test]
+    fn ladder_sells_fractions_of_initial_size() {
+        let mut rule = TakeProfitLadderRule::new(vec![(0.2, 0.5), (0.1, 0.25)]);
+        let sym = "SOL/USDC";
+        let partial = |qty| Some(RiskAction::ClosePartial(qty));
+        assert_eq!(rule.evaluate(sym, &long(8.0, 100.0), 105.0, 0), None);
+        assert_eq!(rule.evaluate(sym, &long(8.0, 100.0), 111.0, 1), partial(2.0));
+        assert_eq!(rule.evaluate(sym, &long(6.0, 100.0), 111.0, 2), None);
+        assert_eq!(rule.evaluate(sym, &long(6.0, 100.0), 125.0, 3), partial(4.0));
+        assert_eq!(rule.evaluate(sym, &long(2.0, 100.0), 150.0, 4), None);
+    }
+}
@@ -0,0 +1,333 @@
+//! Pre-trade checks against the whole portfolio: gross / net exposure caps, a per-token
+//! concentration limit and a cap on combined exposure to correlated assets.
+//!
+//! Correlations are estimated from the recent returns of the prices fed to
+//! [`PreTradeRiskEngine::observe_price`], which expects one close per bar of a common timeframe
+//! so the return series of different symbols line up. `PerformanceMonitor::get_correlation_matrix`
+//! is not used: it correlates the returns of strategies, not of assets. Only filled positions
+//! count towards exposure; resting orders are not reserved.
+
+use std::collections::{HashMap, VecDeque};
+
+use super::RiskAction;
+use crate::config::PortfolioRiskConfig;
+use crate::portfolio::Position;
+use crate::utils::types::{OrderSide, TradingPair};
+
+/// Fewer overlapping returns than this and two symbols count as uncorrelated
+const MIN_CORRELATION_SAMPLES: usize = 10;
+
+/// Holdings and marks a pre-trade check runs against
+#[derive(Debug, Clone, Copy)]
+pub struct PortfolioView<'a> {
+    pub cash: f64,
+    pub positions: &'a HashMap<String, Position>,
+    /// Latest price per symbol ("BASE/QUOTE"); unpriced positions are marked at entry
+    pub prices: &'a HashMap<String, f64>,
+}
+
+impl<'a> PortfolioView<'a> {
+    pub fn new(
+        cash: f64, positions: &'a HashMap<String, Position>, prices: &'a HashMap<String, f64>,
+    ) -> Self {
+        Self { cash, positions, prices }
+    }
+
+    /// Signed market value of the position in `symbol`
+    pub fn notional(&self, symbol: &str) -> f64 {
+        self.positions.get(symbol).map_or(0.0, |p| p.size * self.mark(symbol, p))
+    }
+
+    fn mark(&self, symbol: &str, pos: &Position) -> f64 {
+        self.prices.get(symbol).copied().unwrap_or(pos.average_entry_price)
+    }
+
+    fn exposures(&self) -> impl Iterator<Item = (&'a str, f64)> + '_ {
+        self.positions.iter().map(|(sym, p)| (sym.as_str(), p.size * self.mark(sym, p)))
+    }
+
+    pub fn equity(&self) -> f64 {
+        self.cash + self.exposures().map(|(_, n)| n).sum::<f64>()
+    }
+}
+
+/// An order about to be placed
+#[derive(Debug, Clone, Copy)]
+pub struct OrderIntent<'a> {
+    pub symbol: &'a str,
+    pub side: OrderSide,
+    pub qty: f64,
+    pub price: f64,
+}
+
+impl OrderIntent<'_> {
+    fn sign(&self) -> f64 {
+        match self.side {
+            | OrderSide::Buy => 1.0,
+            | OrderSide::Sell => -1.0,
+        }
+    }
+}
+
+/// Limit behind a pre-trade decision
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PortfolioLimit {
+    GrossExposure,
+    NetExposure,
+    TokenConcentration,
+    CorrelatedExposure,
+    /// Equity is zero or negative
+    Equity,
+}
+
+/// Outcome of a pre-trade check that did not pass the order unchanged
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PreTradeDecision {
+    /// `Reject`, `Resize(qty)` or `ReduceOnly`
+    pub action: RiskAction,
+    pub limit: PortfolioLimit,
+}
+
+#[derive(Debug, Clone, Default)]
+struct PriceHistory {
+    last: Option<f64>,
+    returns: VecDeque<f64>,
+}
+
+/// Portfolio-level pre-trade risk engine shared by the live engine and the backtester
+#[derive(Debug, Clone)]
+pub struct PreTradeRiskEngine {
+    cfg: PortfolioRiskConfig,
+    history: HashMap<String, PriceHistory>,
+}
+
+impl PreTradeRiskEngine {
+    pub fn new(cfg: PortfolioRiskConfig) -> Self {
+        Self { cfg, history: HashMap::new() }
+    }
+
+    pub fn config(&self) -> &PortfolioRiskConfig {
+        &self.cfg
+    }
+
+    /// Record the close of a bar so correlations can be estimated from recent returns. Feed
+    /// every symbol the bars of the same timeframe; raw ticks give returns over unequal spans.
+    pub fn observe_price(&mut self, symbol: &str, price: f64) {
+        if price <= 0.0 {
+            return;
+        }
+        let window = self.cfg.correlation_window.max(2);
+        let h = self.history.entry(symbol.to_string()).or_default();
+        if let Some(last) = h.last {
+            if h.returns.len() == window {
+                h.returns.pop_front();
+            }
+            h.returns.push_back(price / last - 1.0);
+        }
+        h.last = Some(price);
+    }
+
+    /// Correlation of `a` and `b` estimated from their recent returns
+    pub fn correlation(&self, a: &str, b: &str) -> Option<f64> {
+        if a == b {
+            return Some(1.0);
+        }
+        let (ra, rb) = (&self.history.get(a)?.returns, &self.history.get(b)?.returns);
+        let n = ra.len().min(rb.len());
+        if n < MIN_CORRELATION_SAMPLES {
+            return None;
+        }
+        // align on the most recent returns of both series
+        let xs: Vec<f64> = ra.iter().skip(ra.len() - n).copied().collect();
+        let ys: Vec<f64> = rb.iter().skip(rb.len() - n).copied().collect();
+        pearson(&xs, &ys)
+    }
+
+    /// Check `order` against every configured limit. `None` lets the order through unchanged.
+    /// Orders that only reduce an existing position always pass.
+    pub fn check(&self, order: &OrderIntent, view: &PortfolioView) -> Option<PreTradeDecision> {
+        if order.qty <= 0.0 || order.price <= 0.0 {
+            return None;
+        }
+        let equity = view.equity();
+        let own_reducible = reducible_qty(view.notional(order.symbol), order);
+        if order.qty <= own_reducible + 1e-12 {
+            return None;
+        }
+        if equity <= 0.0 {
+            let limit = PortfolioLimit::Equity;
+            return Some(PreTradeDecision { action: RiskAction::Reject, limit });
+        }
+
+        // (limit, current exposure, cap, quantity of the order that lowers that exposure)
+        let mut limits: Vec<(PortfolioLimit, f64, f64, f64)> = Vec::new();
+        if let Some(pct) = self.cfg.max_gross_exposure_pct {
+            let gross = view.exposures().map(|(_, n)| n.abs()).sum::<f64>();
+            limits.push((PortfolioLimit::GrossExposure, gross, pct, own_reducible));
+        }
+        if let Some(pct) = self.cfg.max_net_exposure_pct {
+            let net = view.exposures().map(|(_, n)| n).sum::<f64>();
+            let reducible = reducible_qty(net, order);
+            limits.push((PortfolioLimit::NetExposure, net.abs(), pct, reducible));
+        }
+        if let (Some(pct), Some(pair)) =
+            (self.cfg.max_token_concentration_pct, TradingPair::from_str(order.symbol))
+        {
+            let same_token =
+                |sym: &str| TradingPair::from_str(sym).is_some_and(|p| p.base == pair.base);
+            let token: f64 =
+                view.exposures().filter(|(sym, _)| same_token(sym)).map(|(_, n)| n).sum();
+            let reducible = reducible_qty(token, order);
+            limits.push((PortfolioLimit::TokenConcentration, token.abs(), pct, reducible));
+        }
+        if let Some(pct) = self.cfg.max_correlated_exposure_pct {
+            let threshold = self.cfg.correlation_threshold;
+            let group: f64 = view
+                .exposures()
+                .filter(|(sym, _)| {
+                    *sym == order.symbol
+                        || self.correlation(order.symbol, sym).is_some_and(|c| c >= threshold)
+                })
+                .map(|(_, n)| n.abs())
+                .sum();
+            limits.push((PortfolioLimit::CorrelatedExposure, group, pct, own_reducible));
+        }
+
+        // largest quantity each limit admits; the tightest one binds
+        let (limit, allowed, room) = limits
+            .into_iter()
+            .map(|(limit, current, pct, reducible)| {
+                let cap = equity * pct / 100.0;
+                let room = cap - (current - reducible * order.price);
+                (limit, reducible + room.max(0.0) / order.price, room)
+            })
+            .min_by(|a, b| a.1.total_cmp(&b.1))?;
+        if allowed >= order.qty - 1e-12 {
+            return None;
+        }
+        let action = if room <= 0.0 {
+            RiskAction::ReduceOnly
+        } else if allowed < order.qty * self.cfg.min_resize_pct / 100.0 {
+            RiskAction::Reject
+        } else {
+            RiskAction::Resize(allowed)
+        };
+        Some(PreTradeDecision { action, limit })
+    }
+
+    /// Quantity of `order` that may be sent (0 drops it) and the decision behind any change
+    pub fn approve(
+        &self, order: &OrderIntent, view: &PortfolioView,
+    ) -> (f64, Option<PreTradeDecision>) {
+        match self.check(order, view) {
+            | None => (order.qty, None),
+            | Some(d) => {
+                let qty = match d.action {
+                    | RiskAction::Resize(q) => q.min(order.qty),
+                    | RiskAction::ReduceOnly => reducible_qty(view.notional(order.symbol), order),
+                    | _ => 0.0,
+                };
+                (qty, Some(d))
+            }
+        }
+    }
+}
+
+/// Part of `order` that shrinks the signed `exposure` towards zero
+fn reducible_qty(exposure: f64, order: &OrderIntent) -> f64 {
+    if exposure * order.sign() < 0.0 {
+        (exposure.abs() / order.price).min(order.qty)
+    } else {
+        0.0
+    }
+}
+
+fn pearson(xs: &[f64], ys: &[f64]) -> Option<f64> {
+    let n = xs.len() as f64;
+    let (mx, my) = (xs.iter().sum::<f64>() / n, ys.iter().sum::<f64>() / n);
+    let (mut cov, mut vx, mut vy) = (0.0, 0.0, 0.0);
+    for (x, y) in xs.iter().zip(ys) {
+        cov += (x - mx) * (y - my);
+        vx += (x - mx).powi(2);
+        vy += (y - my).powi(2);
+    }
+    (vx > 0.0 && vy > 0.0).then(|| cov / (vx * vy).sqrt())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pos(size: f64, entry: f64) -> Position {
+        Position { size, average_entry_price: entry, realized_pnl: 0.0 }
+    }
+
+    fn buy(symbol: &str, qty: f64, price: f64) -> OrderIntent<'_> {
+        OrderIntent { symbol, side: OrderSide::Buy, qty, price }
+    }
+
+    #[test]
+    fn gross_cap_resizes_then_goes_reduce_only() {
+        let cfg = PortfolioRiskConfig {
+            max_gross_exposure_pct: Some(50.0),
+            ..Default::default()
+        };
+        let engine = PreTradeRiskEngine::new(cfg);
+        let prices = HashMap::from([("SOL/USDC".to_string(), 100.0)]);
+        // equity 1000, 300 exposed: 200 of headroom
+        let positions = HashMap::from([("SOL/USDC".to_string(), pos(3.0, 100.0))]);
+        let view = PortfolioView::new(700.0, &positions, &prices);
+        let (qty, d) = engine.approve(&buy("SOL/USDC", 5.0, 100.0), &view);
+        assert!((qty - 2.0).abs() < 1e-9);
+        assert_eq!(d.unwrap().limit, PortfolioLimit::GrossExposure);
+
+        let positions = HashMap::from([("SOL/USDC".to_string(), pos(6.0, 100.0))]);
+        let view = PortfolioView::new(400.0, &positions, &prices);
+        let d = engine.check(&buy("SOL/USDC", 1.0, 100.0), &view).unwrap();
+        assert_eq!(d.action, RiskAction::ReduceOnly);
+        // selling out of the position is always allowed
+        let sell = OrderIntent { side: OrderSide::Sell, ..buy("SOL/USDC", 6.0, 100.0) };
+        assert!(engine.check(&sell, &view).is_none());
+    }
+
+    #[test]
+    fn token_concentration_spans_pairs() {
+        let cfg = PortfolioRiskConfig {
+            max_token_concentration_pct: Some(20.0),
+            ..Default::default()
+        };
+        let engine = PreTradeRiskEngine::new(cfg);
+        let prices = HashMap::new();
+        let positions = HashMap::from([("SOL/USDT".to_string(), pos(2.0, 100.0))]);
+        let view = PortfolioView::new(800.0, &positions, &prices);
+        let d = engine.check(&buy("SOL/USDC", 0.1, 100.0), &view).unwrap();
+        assert_eq!(d.limit, PortfolioLimit::TokenConcentration);
+        assert_eq!(d.action, RiskAction::ReduceOnly);
+        assert!(engine.check(&buy("BTC/USDC", 0.001, 100.0), &view).is_none());
+    }
+
+    #[test]
+    fn correlated_exposure_uses_estimated_correlation() {
+        let cfg = PortfolioRiskConfig {
+            max_correlated_exposure_pct: Some(30.0),
+            correlation_threshold: 0.8,
+            ..Default::default()
+        };
+        let mut engine = PreTradeRiskEngine::new(cfg);
+        for i in 0..30 {
+            let px = 100.0 + if i % 2 == 0 { 1.0 } else { -1.0 } * i as f64;
+            engine.observe_price("BONK/USDC", px);
+            engine.observe_price("WIF/USDC", px * 2.0);
+        }
+        assert!(engine.correlation("BONK/USDC", "WIF/USDC").unwrap() > 0.99);
+
+        let prices = HashMap::new();
+        let positions = HashMap::from([("WIF/USDC".to_string(), pos(2.5, 100.0))]);
+        let view = PortfolioView::new(750.0, &positions, &prices);
+        // 250 already in the correlated group, cap 300: 0.5 fits, but is under 10 % of 6
+        let (qty, _) = engine.approve(&buy("BONK/USDC", 0.5, 100.0), &view);
+        assert!((qty - 0.5).abs() < 1e-9);
+        let d = engine.check(&buy("BONK/USDC", 6.0, 100.0), &view).unwrap();
+        assert_eq!(d.action, RiskAction::Reject);
+    }
+}
@@ -14,6 +14,11 @@ pub trait PositionSizer: Send + Sync {
     /// * `symbol` – trading symbol (e.g. "SOL/USDC") – useful for symbol-specific sizing.
     async fn size(&self, equity: f64, symbol: &str) -> f64;
 
+    /// Sizer type and parameters (used in backtest fingerprints)
+    fn describe(&self) -> String {
+        std::any::type_name::<Self>().to_string()
+    }
+
     fn box_clone(&self) -> Box<dyn PositionSizer>;
 }
 
@@ -40,6 +45,9 @@ impl PositionSizer for FixedFractionalSizer {
     async fn size(&self, equity: f64, _symbol: &str) -> f64 {
         equity * self.pct
     }
+    fn describe(&self) -> String {
+        format!("{self:?}")
+    }
     fn box_clone(&self) -> Box<dyn PositionSizer> {
         Box::new(self.clone())
     }
@@ -78,6 +86,9 @@ impl PositionSizer for KellySizer {
         let fraction = kelly.max(0.0).min(self.cap);
         equity * fraction
     }
+    fn describe(&self) -> String {
+        format!("{self:?}")
+    }
     fn box_clone(&self) -> Box<dyn PositionSizer> {
         Box::new(self.clone())
     }
@@ -141,6 +152,9 @@ impl PositionSizer for LiveKellySizer {
         let frac = kelly.max(0.0).min(self.cap);
         equity * frac
     }
+    fn describe(&self) -> String {
+        format!("{self:?}")
+    }
     fn box_clone(&self) -> Box<dyn PositionSizer> {
         Box::new(self.clone())
     }
@@ -185,6 +199,9 @@ impl PositionSizer for VolatilitySizer {
         }
         0.0
     }
+    fn describe(&self) -> String {
+        format!("{self:?}")
+    }
     fn box_clone(&self) -> Box<dyn PositionSizer> {
         Box::new(self.clone())
     }
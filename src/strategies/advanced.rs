@@ -23,6 +23,7 @@ pub struct AdvancedStrategy {
     // Strategy configuration
     symbol: String,
     timeframe: TimeFrame,
+    params: serde_json::Value,
 
     // Technical indicators
     rsi: RelativeStrengthIndex,
@@ -51,9 +52,23 @@ impl AdvancedStrategy {
         bb_multiplier: f64, kc_period: usize, kc_multiplier: f64, mfi_period: usize,
         stoch_period: usize, atr_period: usize, window_size: usize,
     ) -> Self {
+        let params = serde_json::json!({
+            "symbol": symbol,
+            "timeframe": timeframe,
+            "rsi_period": rsi_period,
+            "bb_period": bb_period,
+            "bb_multiplier": bb_multiplier,
+            "kc_period": kc_period,
+            "kc_multiplier": kc_multiplier,
+            "mfi_period": mfi_period,
+            "stoch_period": stoch_period,
+            "atr_period": atr_period,
+            "window_size": window_size,
+        });
         Self {
             symbol: symbol.to_string(),
             timeframe,
+            params,
             rsi: RelativeStrengthIndex::new(rsi_period).unwrap(),
             bb: BollingerBands::new(bb_period, bb_multiplier).unwrap(),
             kc: KeltnerChannel::new(kc_period, kc_multiplier).unwrap(),
@@ -130,6 +145,10 @@ impl TradingStrategy for AdvancedStrategy {
         vec![self.symbol.clone()]
     }
 
+    fn params(&self) -> serde_json::Value {
+        self.params.clone()
+    }
+
     async fn generate_signals(&mut self, market_data: &MarketData) -> Vec<Signal> {
         // Update market regime detection
         self.market_regime = self.detect_market_regime(market_data.close);
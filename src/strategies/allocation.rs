@@ -6,7 +6,7 @@
 use async_trait::async_trait;
 use futures::future::join_all;
 
-use super::{TimeFrame, TradingStrategy};
+use super::{StrategyContext, TimeFrame, TradingStrategy};
 use crate::trading::{MarketData, Order, Position, Signal};
 
 pub struct AllocationStrategy {
@@ -54,6 +54,13 @@ impl TradingStrategy for AllocationStrategy {
         set.into_iter().collect()
     }
 
+    fn params(&self) -> serde_json::Value {
+        serde_json::json!({
+            "weights": self.weights,
+            "strategies": super::sub_params(&self.sub_strategies),
+        })
+    }
+
     async fn generate_signals(&mut self, market_data: &MarketData) -> Vec<Signal> {
         let futs = self
             .sub_strategies
@@ -83,6 +90,50 @@ impl TradingStrategy for AllocationStrategy {
         }
     }
 
+    fn on_start(&mut self, ctx: &mut StrategyContext) {
+        for s in &mut self.sub_strategies {
+            s.on_start(ctx);
+        }
+    }
+
+    fn on_stop(&mut self, ctx: &mut StrategyContext) {
+        for s in &mut self.sub_strategies {
+            s.on_stop(ctx);
+        }
+    }
+
+    /// Sub-strategies share one timer id space
+    fn on_timer(&mut self, id: u64, ctx: &mut StrategyContext) -> Vec<Signal> {
+        let mut out = Vec::new();
+        for (s, w) in self.sub_strategies.iter_mut().zip(&self.weights) {
+            for mut sig in s.on_timer(id, ctx) {
+                sig.size *= w;
+                out.push(sig);
+            }
+        }
+        out
+    }
+
+    fn on_position_update(
+        &mut self, symbol: &str, position: &crate::portfolio::Position, ctx: &mut StrategyContext,
+    ) {
+        for s in &mut self.sub_strategies {
+            s.on_position_update(symbol, position, ctx);
+        }
+    }
+
+    fn state_version(&self) -> u32 {
+        1
+    }
+
+    fn snapshot_state(&self) -> Option<serde_json::Value> {
+        super::snapshot_subs(&self.sub_strategies)
+    }
+
+    fn restore_state(&mut self, state: serde_json::Value) -> crate::Result<()> {
+        super::restore_subs(&mut self.sub_strategies, state)
+    }
+
     fn get_positions(&self) -> Vec<&Position> {
         let mut v = Vec::new();
         for s in &self.sub_strategies {
@@ -39,6 +39,10 @@ impl TradingStrategy for BundleSniperStrategy {
         self.symbols.clone()
     }
 
+    fn params(&self) -> serde_json::Value {
+        serde_json::json!({ "symbols": self.symbols })
+    }
+
     async fn generate_signals(&mut self, _market_data: &MarketData) -> Vec<Signal> {
         // TODO: implement real bundle detection logic
         Vec::new()
@@ -1,77 +1,188 @@
 //! Conversions from generic `StrategyConfig` to concrete strategy instances.
-//! Currently this provides basic default parameter mappings. In future we
-//! should parse `config.params` for fine-grained tuning.
+//! Every key in `config.params` is optional; missing keys fall back to the defaults the
+//! strategies have always been built with.
 
 use super::trend_following::TrendFollowingConfig;
 use super::*;
 use std::convert::TryFrom;
 
-mod _removed_brace {}
+type ParamResult<T> = Result<T, Box<dyn std::error::Error>>;
+
+/// Typed, defaulted lookups into `StrategyConfig::params`
+struct Params<'a>(&'a serde_json::Value);
+
+impl<'a> Params<'a> {
+    fn new(cfg: &'a StrategyConfig) -> Self {
+        Self(&cfg.params)
+    }
+
+    fn get(&self, key: &str) -> Option<&'a serde_json::Value> {
+        self.0.get(key).filter(|v| !v.is_null())
+    }
+
+    fn str(&self, key: &str, default: &str) -> ParamResult<String> {
+        match self.get(key) {
+            | None => Ok(default.to_string()),
+            | Some(v) => v
+                .as_str()
+                .map(str::to_string)
+                .ok_or_else(|| format!("param '{key}' must be a string").into()),
+        }
+    }
+
+    fn f64(&self, key: &str, default: f64) -> ParamResult<f64> {
+        match self.get(key) {
+            | None => Ok(default),
+            | Some(v) => v.as_f64().ok_or_else(|| format!("param '{key}' must be a number").into()),
+        }
+    }
+
+    /// Positive integer (periods, counts)
+    fn int<T: TryFrom<u64>>(&self, key: &str, default: T) -> ParamResult<T> {
+        let v = match self.get(key) {
+            | Some(v) => v,
+            | None => return Ok(default),
+        };
+        // Optimizers may hand over whole numbers as floats (e.g. 20.0)
+        let n = v
+            .as_u64()
+            .or_else(|| v.as_f64().filter(|f| f.fract() == 0.0 && *f >= 0.0).map(|f| f as u64))
+            .filter(|n| *n > 0)
+            .ok_or_else(|| format!("param '{key}' must be a positive integer"))?;
+        T::try_from(n).map_err(|_| format!("param '{key}' is out of range: {n}").into())
+    }
+
+    fn timeframe(&self, default: TimeFrame) -> ParamResult<TimeFrame> {
+        match self.get("timeframe") {
+            | None => Ok(default),
+            | Some(v) => {
+                let tf = v.as_str().ok_or("param 'timeframe' must be a string")?;
+                Ok(parse_timeframe(tf)?)
+            }
+        }
+    }
+}
 
 impl TryFrom<&StrategyConfig> for AdvancedStrategy {
     type Error = Box<dyn std::error::Error>;
-    fn try_from(_cfg: &StrategyConfig) -> Result<Self, Self::Error> {
+    fn try_from(cfg: &StrategyConfig) -> Result<Self, Self::Error> {
+        let p = Params::new(cfg);
         Ok(AdvancedStrategy::new(
-            "SOL/USDC",
-            TimeFrame::OneHour,
-            14,
-            20,
-            2.0,
-            20,
-            1.5,
-            14,
-            14,
-            14,
-            100,
+            &p.str("symbol", "SOL/USDC")?,
+            p.timeframe(TimeFrame::OneHour)?,
+            p.int("rsi_period", 14)?,
+            p.int("bb_period", 20)?,
+            p.f64("bb_multiplier", 2.0)?,
+            p.int("kc_period", 20)?,
+            p.f64("kc_multiplier", 1.5)?,
+            p.int("mfi_period", 14)?,
+            p.int("stoch_period", 14)?,
+            p.int("atr_period", 14)?,
+            p.int("window_size", 100)?,
         ))
     }
 }
 
 impl TryFrom<&StrategyConfig> for MeanReversionStrategy {
     type Error = Box<dyn std::error::Error>;
-    fn try_from(_cfg: &StrategyConfig) -> Result<Self, Self::Error> {
-        Ok(MeanReversionStrategy::new("SOL/USDC", TimeFrame::OneHour, 20, 2.0, 1.0, 1.0))
+    fn try_from(cfg: &StrategyConfig) -> Result<Self, Self::Error> {
+        let p = Params::new(cfg);
+        Ok(MeanReversionStrategy::new(
+            &p.str("symbol", "SOL/USDC")?,
+            p.timeframe(TimeFrame::OneHour)?,
+            p.int("lookback_period", 20)?,
+            p.f64("zscore_threshold", 2.0)?,
+            p.f64("take_profit_pct", 1.0)?,
+            p.f64("stop_loss_pct", 1.0)?,
+        ))
     }
 }
 
 impl TryFrom<&StrategyConfig> for TrendFollowingStrategy {
     type Error = Box<dyn std::error::Error>;
-    fn try_from(_cfg: &StrategyConfig) -> Result<Self, Self::Error> {
+    fn try_from(cfg: &StrategyConfig) -> Result<Self, Self::Error> {
+        let p = Params::new(cfg);
         Ok(TrendFollowingStrategy::new(TrendFollowingConfig::new(
-            "SOL/USDC",
-            TimeFrame::OneHour,
-            9,
-            21,
-            50,
-            12,
-            26,
-            9,
-            14,
-            14,
-            1.0,
-            10.0,
-            1.0,
+            &p.str("symbol", "SOL/USDC")?,
+            p.timeframe(TimeFrame::OneHour)?,
+            p.int("fast_ema_period", 9)?,
+            p.int("medium_ema_period", 21)?,
+            p.int("slow_ema_period", 50)?,
+            p.int("macd_fast", 12)?,
+            p.int("macd_slow", 26)?,
+            p.int("macd_signal", 9)?,
+            p.int("adx_period", 14)?,
+            p.int("atr_period", 14)?,
+            p.f64("trailing_stop_pct", 1.0)?,
+            p.f64("max_drawdown_pct", 10.0)?,
+            p.f64("position_size_pct", 1.0)?,
         )))
     }
 }
 
 impl TryFrom<&StrategyConfig> for OrderFlowStrategy {
     type Error = Box<dyn std::error::Error>;
-    fn try_from(_cfg: &StrategyConfig) -> Result<Self, Self::Error> {
-        Ok(OrderFlowStrategy::new("SOL/USDC", TimeFrame::OneHour, 20, 0.5, 50, 1.0, 0.5))
+    fn try_from(cfg: &StrategyConfig) -> Result<Self, Self::Error> {
+        let p = Params::new(cfg);
+        Ok(OrderFlowStrategy::new(
+            &p.str("symbol", "SOL/USDC")?,
+            p.timeframe(TimeFrame::OneHour)?,
+            p.int("order_book_depth", 20)?,
+            p.f64("imbalance_threshold", 0.5)?,
+            p.int("window_size", 50)?,
+            p.f64("position_size_pct", 1.0)?,
+            p.f64("max_slippage_pct", 0.5)?,
+        ))
     }
 }
 
 impl TryFrom<&StrategyConfig> for MemeArbitrageStrategy {
     type Error = Box<dyn std::error::Error>;
-    fn try_from(_cfg: &StrategyConfig) -> Result<Self, Self::Error> {
-        Ok(MemeArbitrageStrategy::new("SOL/USDC", TimeFrame::OneHour, 1.0, 0.5, 3))
+    fn try_from(cfg: &StrategyConfig) -> Result<Self, Self::Error> {
+        let p = Params::new(cfg);
+        Ok(MemeArbitrageStrategy::new(
+            &p.str("symbol", "SOL/USDC")?,
+            p.timeframe(TimeFrame::OneHour)?,
+            p.f64("max_position_size", 1.0)?,
+            p.f64("max_slippage_pct", 0.5)?,
+            p.int("max_consecutive_losses", 3)?,
+        ))
     }
 }
 
 impl TryFrom<&StrategyConfig> for MomentumStrategy {
     type Error = Box<dyn std::error::Error>;
-    fn try_from(_cfg: &StrategyConfig) -> Result<Self, Self::Error> {
-        Ok(MomentumStrategy::new("SOL/USDC"))
+    fn try_from(cfg: &StrategyConfig) -> Result<Self, Self::Error> {
+        let symbol = Params::new(cfg).str("symbol", "SOL/USDC")?;
+        if crate::utils::types::TradingPair::from_str(&symbol).is_none() {
+            return Err(format!("invalid symbol '{symbol}'").into());
+        }
+        Ok(MomentumStrategy::new(&symbol))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn cfg(params: serde_json::Value) -> StrategyConfig {
+        StrategyConfig { name: "mean_reversion".into(), enabled: true, params, performance: None }
+    }
+
+    #[test]
+    fn params_override_defaults() {
+        let s = MeanReversionStrategy::try_from(&cfg(
+            json!({ "symbol": "BONK/USDC", "timeframe": "5m", "lookback_period": 30.0 }),
+        ))
+        .unwrap();
+        assert_eq!(s.symbols(), vec!["BONK/USDC".to_string()]);
+        assert_eq!(s.timeframe(), TimeFrame::FiveMinutes);
+    }
+
+    #[test]
+    fn invalid_periods_are_rejected() {
+        assert!(MeanReversionStrategy::try_from(&cfg(json!({ "lookback_period": 0 }))).is_err());
+        assert!(MeanReversionStrategy::try_from(&cfg(json!({ "lookback_period": 2.5 }))).is_err());
     }
 }
@@ -0,0 +1,160 @@
+//! Context handed to the strategy lifecycle hooks and the timers strategies register through it.
+//!
+//! The same types drive live trading (wall-clock time) and backtests (simulated time), so a
+//! strategy cannot tell the two apart through its hooks.
+
+use std::collections::HashMap;
+
+/// Clock and timer registry passed to lifecycle hooks. Timer requests are collected here and
+/// applied by the driver once the hook returns.
+#[derive(Debug, Clone, Default)]
+pub struct StrategyContext {
+    now: i64,
+    requests: Vec<TimerRequest>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TimerRequest {
+    Set { id: u64, at: i64, every: Option<i64> },
+    Cancel { id: u64 },
+}
+
+impl StrategyContext {
+    pub fn new(now: i64) -> Self {
+        Self { now, requests: Vec::new() }
+    }
+
+    /// Current unix time in seconds; simulated time in backtests
+    pub fn now(&self) -> i64 {
+        self.now
+    }
+
+    /// Fire timer `id` once at unix time `at`; replaces any timer with the same id
+    pub fn set_timer(&mut self, id: u64, at: i64) {
+        self.requests.push(TimerRequest::Set { id, at, every: None });
+    }
+
+    /// Fire timer `id` every `every_secs` seconds, starting one period from now
+    pub fn set_interval(&mut self, id: u64, every_secs: i64) {
+        if every_secs <= 0 {
+            log::warn!("Ignoring timer {id} with non-positive interval {every_secs}s");
+            return;
+        }
+        let at = self.now + every_secs;
+        self.requests.push(TimerRequest::Set { id, at, every: Some(every_secs) });
+    }
+
+    pub fn cancel_timer(&mut self, id: u64) {
+        self.requests.push(TimerRequest::Cancel { id });
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Timer {
+    at: i64,
+    every: Option<i64>,
+}
+
+/// Timers of all strategies, keyed by strategy index and timer id
+#[derive(Debug, Clone, Default)]
+pub struct TimerBook {
+    timers: HashMap<(usize, u64), Timer>,
+}
+
+impl TimerBook {
+    /// Apply the requests collected in `ctx` for strategy `strategy` and return the
+    /// `(at, id)` of every timer that was (re)scheduled
+    pub fn apply(&mut self, strategy: usize, ctx: &mut StrategyContext) -> Vec<(i64, u64)> {
+        let mut scheduled = Vec::new();
+        for req in ctx.requests.drain(..) {
+            match req {
+                | TimerRequest::Set { id, at, every } => {
+                    self.timers.insert((strategy, id), Timer { at, every });
+                    scheduled.push((at, id));
+                }
+                | TimerRequest::Cancel { id } => {
+                    self.timers.remove(&(strategy, id));
+                }
+            }
+        }
+        scheduled
+    }
+
+    /// Consume timer `id` of `strategy` due at `at`. `None` means the entry is stale (the timer
+    /// was cancelled, re-set or already fired); otherwise the inner value is the next tick of
+    /// an interval, which stays scheduled.
+    pub fn fire(&mut self, strategy: usize, id: u64, at: i64) -> Option<Option<i64>> {
+        let timer = self.timers.get_mut(&(strategy, id)).filter(|t| t.at == at)?;
+        match timer.every {
+            | Some(every) => {
+                timer.at += every;
+                Some(Some(timer.at))
+            }
+            | None => {
+                self.timers.remove(&(strategy, id));
+                Some(None)
+            }
+        }
+    }
+
+    /// Fire every timer due at or before `now` as `(strategy, id, at)`, earliest first.
+    /// Intervals that fell behind fire once and skip to their next future tick.
+    pub fn due(&mut self, now: i64) -> Vec<(usize, u64, i64)> {
+        let mut fired: Vec<(usize, u64, i64)> = self
+            .timers
+            .iter()
+            .filter(|(_, t)| t.at <= now)
+            .map(|(&(strategy, id), t)| (strategy, id, t.at))
+            .collect();
+        fired.sort_by_key(|&(strategy, id, at)| (at, strategy, id));
+        for &(strategy, id, at) in &fired {
+            if let Some(Some(_)) = self.fire(strategy, id, at) {
+                if let Some(t) = self.timers.get_mut(&(strategy, id)) {
+                    let every = t.every.unwrap_or(1);
+                    while t.at <= now {
+                        t.at += every;
+                    }
+                }
+            }
+        }
+        fired
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.timers.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn intervals_repeat_and_cancelled_timers_go_stale() {
+        let mut book = TimerBook::default();
+        let mut ctx = StrategyContext::new(100);
+        ctx.set_interval(1, 10);
+        ctx.set_timer(2, 105);
+        assert_eq!(book.apply(0, &mut ctx), vec![(110, 1), (105, 2)]);
+
+        assert_eq!(book.fire(0, 2, 105), Some(None));
+        assert_eq!(book.fire(0, 2, 105), None);
+        assert_eq!(book.fire(0, 1, 110), Some(Some(120)));
+
+        ctx.cancel_timer(1);
+        book.apply(0, &mut ctx);
+        assert_eq!(book.fire(0, 1, 120), None);
+        assert!(book.is_empty());
+    }
+
+    #[test]
+    fn due_catches_up_once() {
+        let mut book = TimerBook::default();
+        let mut ctx = StrategyContext::new(0);
+        ctx.set_interval(7, 10);
+        book.apply(3, &mut ctx);
+        assert_eq!(book.due(35), vec![(3, 7, 10)]);
+        assert!(book.due(39).is_empty());
+        assert_eq!(book.due(40), vec![(3, 7, 40)]);
+    }
+}
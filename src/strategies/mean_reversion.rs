@@ -1,6 +1,7 @@
 use std::collections::VecDeque;
 
 use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
 use ta::{
     indicators::{
         BollingerBands, ExponentialMovingAverage, RelativeStrengthIndex, StandardDeviation,
@@ -38,6 +39,17 @@ pub struct MeanReversionStrategy {
     stop_loss_pct: f64,
 }
 
+/// Checkpointed part of [`MeanReversionStrategy`]
+#[derive(Serialize, Deserialize)]
+struct MeanReversionState {
+    rsi: CachedIndicator<RelativeStrengthIndex>,
+    bb: BollingerBands,
+    ema: CachedIndicator<ExponentialMovingAverage>,
+    std_dev: CachedIndicator<StandardDeviation>,
+    position: Option<Position>,
+    recent_prices: VecDeque<f64>,
+}
+
 impl MeanReversionStrategy {
     /// Create a new instance of MeanReversionStrategy
     pub fn new(
@@ -110,6 +122,17 @@ impl TradingStrategy for MeanReversionStrategy {
         vec![self.symbol.clone()]
     }
 
+    fn params(&self) -> serde_json::Value {
+        serde_json::json!({
+            "symbol": self.symbol,
+            "timeframe": self.timeframe,
+            "lookback_period": self.lookback_period,
+            "zscore_threshold": self.zscore_threshold,
+            "take_profit_pct": self.take_profit_pct * 100.0,
+            "stop_loss_pct": self.stop_loss_pct * 100.0,
+        })
+    }
+
     async fn generate_signals(&mut self, market_data: &MarketData) -> Vec<Signal> {
         // Update indicators
         let _ = self.rsi.next(market_data.close);
@@ -234,6 +257,33 @@ impl TradingStrategy for MeanReversionStrategy {
         }
     }
 
+    fn state_version(&self) -> u32 {
+        1
+    }
+
+    fn snapshot_state(&self) -> Option<serde_json::Value> {
+        let state = MeanReversionState {
+            rsi: self.rsi.clone(),
+            bb: self.bb.clone(),
+            ema: self.ema.clone(),
+            std_dev: self.std_dev.clone(),
+            position: self.position.clone(),
+            recent_prices: self.recent_prices.clone(),
+        };
+        serde_json::to_value(state).ok()
+    }
+
+    fn restore_state(&mut self, state: serde_json::Value) -> crate::Result<()> {
+        let state: MeanReversionState = serde_json::from_value(state)?;
+        self.rsi = state.rsi;
+        self.bb = state.bb;
+        self.ema = state.ema;
+        self.std_dev = state.std_dev;
+        self.position = state.position;
+        self.recent_prices = state.recent_prices;
+        Ok(())
+    }
+
     fn get_positions(&self) -> Vec<&Position> {
         self.position.iter().collect()
     }
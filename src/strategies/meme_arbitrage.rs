@@ -229,6 +229,16 @@ impl TradingStrategy for MemeArbitrageStrategy {
         vec![self.symbol.clone()]
     }
 
+    fn params(&self) -> serde_json::Value {
+        serde_json::json!({
+            "symbol": self.symbol,
+            "timeframe": self.timeframe,
+            "max_position_size": self.max_position_size,
+            "max_slippage_pct": self.max_slippage_pct * 100.0,
+            "max_consecutive_losses": self.max_consecutive_losses,
+        })
+    }
+
     async fn generate_signals(&mut self, market_data: &MarketData) -> Vec<Signal> {
         let mut signals = Vec::new();
 
@@ -3,7 +3,7 @@
 use async_trait::async_trait;
 use futures::future::join_all;
 
-use super::{TimeFrame, TradingStrategy, TradingStrategyClone};
+use super::{StrategyContext, TimeFrame, TradingStrategy, TradingStrategyClone};
 use crate::trading::{MarketData, Order, Position, Signal};
 
 /// A simple ensemble strategy that aggregates signals from multiple
@@ -47,6 +47,10 @@ impl TradingStrategy for EnsembleStrategy {
         set.into_iter().collect()
     }
 
+    fn params(&self) -> serde_json::Value {
+        serde_json::json!({ "strategies": super::sub_params(&self.sub_strategies) })
+    }
+
     async fn generate_signals(&mut self, market_data: &MarketData) -> Vec<Signal> {
         // Gather futures of each sub-strategy.
         let mut futs = Vec::with_capacity(self.sub_strategies.len());
@@ -90,6 +94,43 @@ impl TradingStrategy for EnsembleStrategy {
         }
     }
 
+    fn on_start(&mut self, ctx: &mut StrategyContext) {
+        for s in &mut self.sub_strategies {
+            s.on_start(ctx);
+        }
+    }
+
+    fn on_stop(&mut self, ctx: &mut StrategyContext) {
+        for s in &mut self.sub_strategies {
+            s.on_stop(ctx);
+        }
+    }
+
+    /// Sub-strategies share one timer id space; timer signals bypass the vote
+    fn on_timer(&mut self, id: u64, ctx: &mut StrategyContext) -> Vec<Signal> {
+        self.sub_strategies.iter_mut().flat_map(|s| s.on_timer(id, ctx)).collect()
+    }
+
+    fn on_position_update(
+        &mut self, symbol: &str, position: &crate::portfolio::Position, ctx: &mut StrategyContext,
+    ) {
+        for s in &mut self.sub_strategies {
+            s.on_position_update(symbol, position, ctx);
+        }
+    }
+
+    fn state_version(&self) -> u32 {
+        1
+    }
+
+    fn snapshot_state(&self) -> Option<serde_json::Value> {
+        super::snapshot_subs(&self.sub_strategies)
+    }
+
+    fn restore_state(&mut self, state: serde_json::Value) -> crate::Result<()> {
+        super::restore_subs(&mut self.sub_strategies, state)
+    }
+
     fn get_positions(&self) -> Vec<&Position> {
         let mut out = Vec::new();
         for s in &self.sub_strategies {
@@ -4,6 +4,7 @@ mod advanced;
 mod allocation;
 mod bundle_sniper;
 mod config_impls;
+pub mod context;
 mod mean_reversion;
 mod meme_arbitrage;
 mod meta;
@@ -19,6 +20,7 @@ mod trend_following;
 pub use advanced::AdvancedStrategy;
 pub use allocation::AllocationStrategy;
 pub use bundle_sniper::BundleSniperStrategy;
+pub use context::{StrategyContext, TimerBook};
 pub use mean_reversion::MeanReversionStrategy;
 pub use meme_arbitrage::MemeArbitrageStrategy;
 pub use meta::EnsembleStrategy;
@@ -67,6 +69,49 @@ pub trait TradingStrategy: Send + Sync {
     /// Update parameters at runtime (default no-op).
     fn update_params(&mut self, _params: &serde_json::Value) {}
 
+    /// Called once before the first market data; register timers or warm up here
+    fn on_start(&mut self, _ctx: &mut StrategyContext) {}
+
+    /// Called once after the last market data; flush state here
+    fn on_stop(&mut self, _ctx: &mut StrategyContext) {}
+
+    /// A timer registered through [`StrategyContext`] fired. Returned signals are handled like
+    /// those of [`generate_signals`](Self::generate_signals).
+    fn on_timer(&mut self, _id: u64, _ctx: &mut StrategyContext) -> Vec<Signal> {
+        Vec::new()
+    }
+
+    /// The portfolio position in `symbol` changed; `size` is 0 once it is closed
+    fn on_position_update(
+        &mut self, _symbol: &str, _position: &crate::portfolio::Position,
+        _ctx: &mut StrategyContext,
+    ) {
+    }
+
+    /// Parameters this instance runs with, part of backtest cache fingerprints.
+    /// `Null` (the default) leaves only name, timeframe and symbols to tell instances apart.
+    fn params(&self) -> serde_json::Value {
+        serde_json::Value::Null
+    }
+
+    /// Layout version of [`snapshot_state`](Self::snapshot_state). Bump it whenever the layout
+    /// changes; checkpoints written with another version are discarded on restore.
+    fn state_version(&self) -> u32 {
+        0
+    }
+
+    /// Runtime state worth keeping across restarts (indicator windows, positions, history).
+    /// `None` (the default) means there is nothing to checkpoint.
+    fn snapshot_state(&self) -> Option<serde_json::Value> {
+        None
+    }
+
+    /// Restore a checkpoint taken by [`snapshot_state`](Self::snapshot_state) with the same
+    /// [`state_version`](Self::state_version) and [`params`](Self::params)
+    fn restore_state(&mut self, _state: serde_json::Value) -> crate::Result<()> {
+        Ok(())
+    }
+
     /// Get current positions
     fn get_positions(&self) -> Vec<&Position>;
     /// Downcast helper for dynamic typing
@@ -78,6 +123,50 @@ pub trait TradingStrategy: Send + Sync {
     }
 }
 
+/// Parameters of a composite strategy: one `{name, params}` entry per sub-strategy
+pub(crate) fn sub_params(subs: &[Box<dyn TradingStrategy>]) -> serde_json::Value {
+    subs.iter().map(|s| serde_json::json!({ "name": s.name(), "params": s.params() })).collect()
+}
+
+/// Checkpoint of a composite strategy: one `{version, state}` entry per sub-strategy, `null`
+/// for those without state
+pub(crate) fn snapshot_subs(subs: &[Box<dyn TradingStrategy>]) -> Option<serde_json::Value> {
+    let entries: Vec<serde_json::Value> = subs
+        .iter()
+        .map(|s| match s.snapshot_state() {
+            | Some(state) => serde_json::json!({ "version": s.state_version(), "state": state }),
+            | None => serde_json::Value::Null,
+        })
+        .collect();
+    entries.iter().any(|e| !e.is_null()).then_some(serde_json::Value::Array(entries))
+}
+
+/// Restore the sub-strategies of a composite from [`snapshot_subs`]. Entries written with
+/// another state version are skipped, leaving that sub-strategy cold.
+pub(crate) fn restore_subs(
+    subs: &mut [Box<dyn TradingStrategy>], state: serde_json::Value,
+) -> crate::Result<()> {
+    let entries: Vec<serde_json::Value> = serde_json::from_value(state)?;
+    if entries.len() != subs.len() {
+        return Err(crate::Error::StrategyError(format!(
+            "checkpoint holds {} sub-strategies, expected {}",
+            entries.len(),
+            subs.len()
+        )));
+    }
+    for (sub, mut entry) in subs.iter_mut().zip(entries) {
+        if entry.is_null() {
+            continue;
+        }
+        if entry["version"].as_u64() != Some(u64::from(sub.state_version())) {
+            log::info!("Discarding checkpoint of {} with an old state version", sub.name());
+            continue;
+        }
+        sub.restore_state(entry["state"].take())?;
+    }
+    Ok(())
+}
+
 // Trait enabling cloning of boxed strategies
 #[async_trait]
 pub trait TradingStrategyClone: TradingStrategy {
@@ -121,6 +210,18 @@ impl TimeFrame {
             | TimeFrame::OneWeek => 604800,
         }
     }
+    /// Short label as used by data files and providers ("1m", "1h", ...)
+    pub fn label(&self) -> &'static str {
+        match self {
+            | TimeFrame::OneMinute => "1m",
+            | TimeFrame::FiveMinutes => "5m",
+            | TimeFrame::FifteenMinutes => "15m",
+            | TimeFrame::OneHour => "1h",
+            | TimeFrame::FourHours => "4h",
+            | TimeFrame::OneDay => "1d",
+            | TimeFrame::OneWeek => "1w",
+        }
+    }
 }
 
 /// Strategy configuration
@@ -151,10 +252,43 @@ pub struct PerformanceConfig {
     pub lookback_days: u32,
 }
 
+/// Config of one sub-strategy of a composite: a bare factory name gets default params, an
+/// object `{ "name": ..., "params": {...} }` its own
+fn sub_strategy_config(entry: &serde_json::Value) -> Result<StrategyConfig, Box<dyn Error>> {
+    let (name, params) = match entry {
+        | serde_json::Value::String(name) => (name.clone(), serde_json::json!({})),
+        | serde_json::Value::Object(obj) => {
+            let name =
+                obj.get("name").and_then(|v| v.as_str()).ok_or("sub-strategy name missing")?;
+            (name.to_string(), obj.get("params").cloned().unwrap_or(serde_json::json!({})))
+        }
+        | other => return Err(format!("invalid sub-strategy entry {other}").into()),
+    };
+    Ok(StrategyConfig { name, enabled: true, params, performance: None })
+}
+
 /// Strategy factory
 pub struct StrategyFactory;
 
 impl StrategyFactory {
+    /// Every name [`create_strategy`](Self::create_strategy) accepts
+    pub fn available_strategies() -> &'static [&'static str] {
+        &[
+            "advanced",
+            "allocation",
+            "ensemble",
+            "meta",
+            "mean_reversion",
+            "trend_following",
+            "order_flow",
+            "momentum",
+            "meme_arbitrage",
+            "bundle_sniper",
+            #[cfg(feature = "ml")]
+            "ml",
+        ]
+    }
+
     /// Create a new strategy instance from configuration with performance monitoring
     pub fn create_strategy(
         name: &str, config: &StrategyConfig,
@@ -170,9 +304,10 @@ impl StrategyFactory {
         let strategy: Box<dyn TradingStrategy> = match name {
             | "advanced" => Box::new(AdvancedStrategy::try_from(config)?),
             | "allocation" => {
-                // params: { "subs": ["s1","s2"], "weights": [0.6,0.4] }
+                // params: { "subs": ["s1","s2"], "weights": [0.6,0.4] }; a sub may also be
+                // { "name": "s1", "params": {...} }
                 let val = config.params.clone();
-                let subs: Vec<String> = val
+                let subs: Vec<serde_json::Value> = val
                     .get("subs")
                     .and_then(|v| serde_json::from_value(v.clone()).ok())
                     .ok_or("allocation subs missing")?;
@@ -184,37 +319,26 @@ impl StrategyFactory {
                     return Err("weights length mismatch".into());
                 }
                 let mut sub_boxes = Vec::new();
-                use serde_json::json;
-                for n in &subs {
-                    let dummy = StrategyConfig {
-                        name: n.clone(),
-                        enabled: true,
-                        params: json!({}),
-                        performance: None,
-                    };
-                    sub_boxes.push(StrategyFactory::create_strategy(n, &dummy)?);
+                for sub in &subs {
+                    let sub_cfg = sub_strategy_config(sub)?;
+                    sub_boxes.push(StrategyFactory::create_strategy(&sub_cfg.name, &sub_cfg)?);
                 }
                 Box::new(AllocationStrategy::new(name, sub_boxes, weights))
             }
             | "ensemble" | "meta" => {
-                // Expected params: list of strategy names to include
-                let names: Vec<String> = serde_json::from_value(config.params.clone())
-                    .map_err(|e| format!("Invalid ensemble params: {}", e))?;
+                // Expected params: list of strategy names, or { "name", "params" } objects
+                let entries: Vec<serde_json::Value> =
+                    serde_json::from_value(config.params.clone())
+                        .map_err(|e| format!("Invalid ensemble params: {}", e))?;
                 // Recursively create sub strategies using factory
                 let mut subs: Vec<Box<dyn TradingStrategy>> = Vec::new();
-                use serde_json::json;
-                for n in names {
+                for entry in &entries {
+                    let sub_cfg = sub_strategy_config(entry)?;
+                    let n = &sub_cfg.name;
                     if n.eq_ignore_ascii_case("ensemble") || n.eq_ignore_ascii_case("meta") {
                         return Err("Nested ensemble strategies are not supported".into());
                     }
-                    // Build a minimal StrategyConfig with empty params; users should pass full configs in TOML for production.
-                    let dummy_cfg = StrategyConfig {
-                        name: n.clone(),
-                        enabled: true,
-                        params: json!({}),
-                        performance: None,
-                    };
-                    match StrategyFactory::create_strategy(&n, &dummy_cfg) {
+                    match StrategyFactory::create_strategy(n, &sub_cfg) {
                         | Ok(s) => subs.push(s),
                         | Err(e) => {
                             return Err(format!("Failed to create sub-strategy {}: {}", n, e).into())
@@ -305,4 +429,11 @@ mod tests {
         assert_eq!(parse_timeframe("1week").unwrap(), TimeFrame::OneWeek);
         assert!(parse_timeframe("invalid").is_err());
     }
+
+    #[test]
+    fn labels_parse_back() {
+        for tf in [TimeFrame::OneMinute, TimeFrame::FourHours, TimeFrame::OneWeek] {
+            assert_eq!(parse_timeframe(tf.label()).unwrap(), tf);
+        }
+    }
 }
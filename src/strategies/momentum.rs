@@ -2,6 +2,7 @@
 
 use crate::utils::indicators::CachedIndicator;
 use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
 use ta::indicators::{ExponentialMovingAverage, RelativeStrengthIndex};
 use ta::Next;
 
@@ -27,6 +28,15 @@ pub struct MomentumStrategy {
     positions: Vec<Position>,
 }
 
+/// Checkpointed part of [`MomentumStrategy`]; indicators are `None` until warmed up
+#[derive(Serialize, Deserialize)]
+struct MomentumState {
+    ema_short: Option<CachedIndicator<ExponentialMovingAverage>>,
+    ema_long: Option<CachedIndicator<ExponentialMovingAverage>>,
+    rsi: Option<CachedIndicator<RelativeStrengthIndex>>,
+    positions: Vec<Position>,
+}
+
 impl MomentumStrategy {
     pub fn new(pair: &str) -> Self {
         Self {
@@ -76,13 +86,27 @@ impl TradingStrategy for MomentumStrategy {
         vec![self.pair.to_string()]
     }
 
+    fn params(&self) -> serde_json::Value {
+        serde_json::json!({
+            "symbol": self.pair.to_string(),
+            "ema_short_period": self.ema_short_period,
+            "ema_long_period": self.ema_long_period,
+            "rsi_period": self.rsi_period,
+            "rsi_overbought": self.rsi_overbought,
+            "rsi_oversold": self.rsi_oversold,
+        })
+    }
+
     async fn generate_signals(&mut self, market_data: &MarketData) -> Vec<Signal> {
-        if market_data.pair != self.pair || market_data.candles.is_empty() {
+        // only candles up to the current bar; anything later would be lookahead
+        let candles: Vec<_> =
+            market_data.candles.iter().filter(|c| c.timestamp <= market_data.timestamp).collect();
+        if market_data.pair != self.pair || candles.is_empty() {
             return Vec::new();
         }
-        let latest = market_data.candles.last().unwrap();
+        let latest = candles.last().unwrap();
         let current_price = latest.close;
-        let close_prices: Vec<f64> = market_data.candles.iter().map(|c| c.close).collect();
+        let close_prices: Vec<f64> = candles.iter().map(|c| c.close).collect();
         if self.ema_short.is_none() {
             self.warm_up(&close_prices);
         }
@@ -128,6 +152,29 @@ impl TradingStrategy for MomentumStrategy {
         // simplistic, no state tracking yet
     }
 
+    fn state_version(&self) -> u32 {
+        1
+    }
+
+    fn snapshot_state(&self) -> Option<serde_json::Value> {
+        let state = MomentumState {
+            ema_short: self.ema_short.clone(),
+            ema_long: self.ema_long.clone(),
+            rsi: self.rsi.clone(),
+            positions: self.positions.clone(),
+        };
+        serde_json::to_value(state).ok()
+    }
+
+    fn restore_state(&mut self, state: serde_json::Value) -> crate::Result<()> {
+        let state: MomentumState = serde_json::from_value(state)?;
+        self.ema_short = state.ema_short;
+        self.ema_long = state.ema_long;
+        self.rsi = state.rsi;
+        self.positions = state.positions;
+        Ok(())
+    }
+
     fn get_positions(&self) -> Vec<&Position> {
         self.positions.iter().collect()
     }
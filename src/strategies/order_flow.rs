@@ -47,6 +47,16 @@ struct TradeRecord {
     metadata: serde_json::Value,
 }
 
+/// Checkpointed part of [`OrderFlowStrategy`]
+#[derive(Serialize, Deserialize)]
+struct OrderFlowState {
+    volume_profile: HashMap<u64, f64>,
+    vpoc_levels: Vec<f64>,
+    position: Option<Position>,
+    recent_imbalances: VecDeque<f64>,
+    trade_history: Vec<TradeRecord>,
+}
+
 impl OrderFlowStrategy {
     /// Create a new instance of OrderFlowStrategy
     pub fn new(
@@ -151,6 +161,18 @@ impl TradingStrategy for OrderFlowStrategy {
         vec![self.symbol.clone()]
     }
 
+    fn params(&self) -> serde_json::Value {
+        serde_json::json!({
+            "symbol": self.symbol,
+            "timeframe": self.timeframe,
+            "order_book_depth": self.order_book_depth,
+            "imbalance_threshold": self.imbalance_threshold,
+            "window_size": self.window_size,
+            "position_size_pct": self.position_size_pct * 100.0,
+            "max_slippage_pct": self.max_slippage_pct * 100.0,
+        })
+    }
+
     async fn generate_signals(&mut self, market_data: &MarketData) -> Vec<Signal> {
         // Update volume profile with new trade data
         self.update_volume_profile(market_data.close, market_data.volume.unwrap_or(0.0));
@@ -301,6 +323,31 @@ impl TradingStrategy for OrderFlowStrategy {
         }
     }
 
+    fn state_version(&self) -> u32 {
+        1
+    }
+
+    fn snapshot_state(&self) -> Option<serde_json::Value> {
+        let state = OrderFlowState {
+            volume_profile: self.volume_profile.clone(),
+            vpoc_levels: self.vpoc_levels.clone(),
+            position: self.position.clone(),
+            recent_imbalances: self.recent_imbalances.clone(),
+            trade_history: self.trade_history.clone(),
+        };
+        serde_json::to_value(state).ok()
+    }
+
+    fn restore_state(&mut self, state: serde_json::Value) -> crate::Result<()> {
+        let state: OrderFlowState = serde_json::from_value(state)?;
+        self.volume_profile = state.volume_profile;
+        self.vpoc_levels = state.vpoc_levels;
+        self.position = state.position;
+        self.recent_imbalances = state.recent_imbalances;
+        self.trade_history = state.trade_history;
+        Ok(())
+    }
+
     fn get_positions(&self) -> Vec<&Position> {
         self.position.iter().collect()
     }
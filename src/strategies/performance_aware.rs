@@ -5,7 +5,7 @@ use std::collections::HashMap;
 use std::time::Duration;
 use tokio::sync::Mutex;
 
-use super::{TimeFrame, TradingStrategy};
+use super::{StrategyContext, TimeFrame, TradingStrategy};
 use crate::performance::{PerformanceMonitor, StrategyAnalyzer};
 use crate::trading::{MarketData, Order, Position, Signal};
 
@@ -115,6 +115,10 @@ impl<T: TradingStrategy + AdaptiveStrategy + Send + Sync + 'static> TradingStrat
         self.inner.symbols()
     }
 
+    fn params(&self) -> serde_json::Value {
+        self.inner.params()
+    }
+
     async fn generate_signals(&mut self, market_data: &MarketData) -> Vec<Signal> {
         // Run analysis and apply any suggested parameter updates once per call
         if let Some(updates) = self.check_and_analyze().await {
@@ -172,6 +176,36 @@ impl<T: TradingStrategy + AdaptiveStrategy + Send + Sync + 'static> TradingStrat
         self.inner.on_order_filled(order);
     }
 
+    fn on_start(&mut self, ctx: &mut StrategyContext) {
+        self.inner.on_start(ctx);
+    }
+
+    fn on_stop(&mut self, ctx: &mut StrategyContext) {
+        self.inner.on_stop(ctx);
+    }
+
+    fn on_timer(&mut self, id: u64, ctx: &mut StrategyContext) -> Vec<Signal> {
+        self.inner.on_timer(id, ctx)
+    }
+
+    fn on_position_update(
+        &mut self, symbol: &str, position: &crate::portfolio::Position, ctx: &mut StrategyContext,
+    ) {
+        self.inner.on_position_update(symbol, position, ctx);
+    }
+
+    fn state_version(&self) -> u32 {
+        self.inner.state_version()
+    }
+
+    fn snapshot_state(&self) -> Option<serde_json::Value> {
+        self.inner.snapshot_state()
+    }
+
+    fn restore_state(&mut self, state: serde_json::Value) -> crate::Result<()> {
+        self.inner.restore_state(state)
+    }
+
     fn get_positions(&self) -> Vec<&Position> {
         self.inner.get_positions()
     }
@@ -20,6 +20,7 @@ pub struct TrendFollowingStrategy {
     // Strategy configuration
     symbol: String,
     timeframe: TimeFrame,
+    params: serde_json::Value,
 
     // Technical indicators
     fast_ema: CachedIndicator<ExponentialMovingAverage>,
@@ -51,6 +52,21 @@ enum TrendDirection {
     Sideways,
 }
 
+/// Checkpointed part of [`TrendFollowingStrategy`]
+#[derive(Serialize, Deserialize)]
+struct TrendFollowingState {
+    fast_ema: CachedIndicator<ExponentialMovingAverage>,
+    medium_ema: CachedIndicator<ExponentialMovingAverage>,
+    slow_ema: CachedIndicator<ExponentialMovingAverage>,
+    macd: MovingAverageConvergenceDivergence,
+    ppc: PercentagePriceOscillator,
+    atr: SimpleMovingAverage,
+    position: Option<Position>,
+    trend_direction: TrendDirection,
+    peak_equity: f64,
+    current_drawdown: f64,
+}
+
 #[derive(Debug, Clone)]
 pub struct TrendFollowingConfig {
     pub symbol: String,
@@ -97,9 +113,25 @@ impl TrendFollowingConfig {
 impl TrendFollowingStrategy {
     /// Create a new instance of TrendFollowingStrategy
     pub fn new(cfg: TrendFollowingConfig) -> Self {
+        let params = serde_json::json!({
+            "symbol": cfg.symbol,
+            "timeframe": cfg.timeframe,
+            "fast_ema_period": cfg.fast_ema_period,
+            "medium_ema_period": cfg.medium_ema_period,
+            "slow_ema_period": cfg.slow_ema_period,
+            "macd_fast": cfg.macd_fast,
+            "macd_slow": cfg.macd_slow,
+            "macd_signal": cfg.macd_signal,
+            "adx_period": cfg.adx_period,
+            "atr_period": cfg.atr_period,
+            "trailing_stop_pct": cfg.trailing_stop_pct,
+            "max_drawdown_pct": cfg.max_drawdown_pct,
+            "position_size_pct": cfg.position_size_pct,
+        });
         Self {
             symbol: cfg.symbol,
             timeframe: cfg.timeframe,
+            params,
             fast_ema: CachedIndicator::new(
                 ExponentialMovingAverage::new(cfg.fast_ema_period).unwrap(),
             ),
@@ -210,6 +242,10 @@ impl TradingStrategy for TrendFollowingStrategy {
         vec![self.symbol.clone()]
     }
 
+    fn params(&self) -> serde_json::Value {
+        self.params.clone()
+    }
+
     async fn generate_signals(&mut self, market_data: &MarketData) -> Vec<Signal> {
         // Update indicators
         let _ = self.fast_ema.next(market_data.close);
@@ -363,6 +399,41 @@ impl TradingStrategy for TrendFollowingStrategy {
         }
     }
 
+    fn state_version(&self) -> u32 {
+        1
+    }
+
+    fn snapshot_state(&self) -> Option<serde_json::Value> {
+        let state = TrendFollowingState {
+            fast_ema: self.fast_ema.clone(),
+            medium_ema: self.medium_ema.clone(),
+            slow_ema: self.slow_ema.clone(),
+            macd: self.macd.clone(),
+            ppc: self.ppc.clone(),
+            atr: self.atr.clone(),
+            position: self.position.clone(),
+            trend_direction: self.trend_direction,
+            peak_equity: self.peak_equity,
+            current_drawdown: self.current_drawdown,
+        };
+        serde_json::to_value(state).ok()
+    }
+
+    fn restore_state(&mut self, state: serde_json::Value) -> crate::Result<()> {
+        let state: TrendFollowingState = serde_json::from_value(state)?;
+        self.fast_ema = state.fast_ema;
+        self.medium_ema = state.medium_ema;
+        self.slow_ema = state.slow_ema;
+        self.macd = state.macd;
+        self.ppc = state.ppc;
+        self.atr = state.atr;
+        self.position = state.position;
+        self.trend_direction = state.trend_direction;
+        self.peak_equity = state.peak_equity;
+        self.current_drawdown = state.current_drawdown;
+        Ok(())
+    }
+
     fn get_positions(&self) -> Vec<&Position> {
         self.position.iter().collect()
     }
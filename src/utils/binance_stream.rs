@@ -1,4 +1,9 @@
 //! Binance WebSocket market data stream integration
+//!
+//! Trades come from `<symbol>@trade`. Depth follows Binance's local order book procedure: the
+//! `<symbol>@depth@100ms` diff stream is opened first, then a REST snapshot is sent as an
+//! `OrderBook` snapshot carrying its `lastUpdateId`, then the diffs are forwarded with their
+//! `U`..`u` update id range so the order book manager can drop stale diffs and detect gaps.
 
 use crate::engine::market_router::ChannelMarketDataStream;
 use crate::utils::market_stream::MarketEvent;
@@ -7,8 +12,14 @@ use serde_json::Value;
 use tokio::sync::mpsc::Sender;
 use tokio_tungstenite::{connect_async, tungstenite::protocol::Message};
 
+/// Levels requested for the REST depth snapshot
+const SNAPSHOT_LIMIT: usize = 1000;
+
 pub struct BinanceStream {
     pub url: String,
+    /// REST depth endpoint the snapshots are fetched from
+    pub depth_url: String,
+    symbols: Vec<String>,
 }
 
 impl BinanceStream {
@@ -16,66 +27,121 @@ impl BinanceStream {
         // Binance expects lowercase and concatenated symbols, e.g. "btcusdt"
         let streams = symbols
             .iter()
-            .map(|s| format!("{}@trade", s.to_lowercase()))
+            .flat_map(|s| {
+                let s = s.to_lowercase();
+                [format!("{s}@trade"), format!("{s}@depth@100ms")]
+            })
             .collect::<Vec<_>>()
             .join("/");
         let url = format!("wss://stream.binance.com:9443/stream?streams={}", streams);
-        Self { url }
+        Self {
+            url,
+            depth_url: "https://api.binance.com/api/v3/depth".to_string(),
+            symbols: symbols.iter().map(|s| s.to_uppercase()).collect(),
+        }
+    }
+
+    async fn depth_snapshot(&self, symbol: &str) -> anyhow::Result<MarketEvent> {
+        let url = format!("{}?symbol={symbol}&limit={SNAPSHOT_LIMIT}", self.depth_url);
+        let json: Value = reqwest::get(&url).await?.error_for_status()?.json().await?;
+        let timestamp = chrono::Utc::now().timestamp_millis();
+        parse_depth_snapshot(symbol, &json, timestamp)
+            .ok_or_else(|| anyhow::anyhow!("malformed Binance depth snapshot for {symbol}"))
+    }
+}
+
+fn number(v: &Value) -> Option<f64> {
+    v.as_str().and_then(|s| s.parse().ok())
+}
+
+/// `[["price", "qty"], ...]` levels
+fn levels(v: Option<&Value>) -> Vec<(f64, f64)> {
+    v.and_then(|v| v.as_array())
+        .map(|arr| {
+            arr.iter()
+                .filter_map(|l| Some((number(l.get(0)?)?, number(l.get(1)?)?)))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// REST `/api/v3/depth` response as a snapshot sequenced at its `lastUpdateId`
+fn parse_depth_snapshot(symbol: &str, json: &Value, timestamp: i64) -> Option<MarketEvent> {
+    Some(MarketEvent::OrderBook {
+        exchange: "binance".to_string(),
+        symbol: symbol.to_string(),
+        bids: levels(json.get("bids")),
+        asks: levels(json.get("asks")),
+        timestamp,
+        is_snapshot: true,
+        sequence: Some(json.get("lastUpdateId")?.as_u64()?),
+        first_sequence: None,
+    })
+}
+
+/// Payload of a combined-stream message
+fn parse_event(data: &Value) -> Option<MarketEvent> {
+    let symbol = data.get("s").and_then(|v| v.as_str()).unwrap_or("").to_string();
+    match data.get("e")?.as_str()? {
+        | "trade" => {
+            let price = data.get("p").and_then(number).unwrap_or(0.0);
+            let qty = data.get("q").and_then(number).unwrap_or(0.0);
+            let side = if data.get("m").and_then(|v| v.as_bool()).unwrap_or(false) {
+                "sell"
+            } else {
+                "buy"
+            };
+            let timestamp = data.get("T").and_then(|v| v.as_i64()).unwrap_or(0);
+            Some(MarketEvent::Trade {
+                exchange: "binance".to_string(),
+                symbol,
+                price,
+                qty,
+                side: side.to_string(),
+                timestamp,
+            })
+        }
+        | "depthUpdate" => Some(MarketEvent::OrderBook {
+            exchange: "binance".to_string(),
+            symbol,
+            bids: levels(data.get("b")),
+            asks: levels(data.get("a")),
+            timestamp: data.get("E").and_then(|v| v.as_i64()).unwrap_or(0),
+            is_snapshot: false,
+            sequence: Some(data.get("u")?.as_u64()?),
+            first_sequence: Some(data.get("U")?.as_u64()?),
+        }),
+        | _ => None,
     }
 }
 
 #[async_trait::async_trait]
 impl ChannelMarketDataStream for BinanceStream {
+    fn name(&self) -> &str {
+        "binance"
+    }
+
     async fn connect_and_stream_channel(
         &mut self, _symbols: Vec<String>, sender: Sender<MarketEvent>,
     ) -> anyhow::Result<()> {
         let (ws_stream, _) = connect_async(&self.url).await?;
+        // Diffs received meanwhile wait in the socket until the snapshots are out
+        for symbol in &self.symbols {
+            let snapshot = self.depth_snapshot(symbol).await?;
+            if sender.send(snapshot).await.is_err() {
+                return Ok(());
+            }
+        }
         let (_, mut read) = ws_stream.split();
         while let Some(msg) = read.next().await {
             let msg = msg?;
             if let Message::Text(txt) = msg {
-                if let Ok(json) = serde_json::from_str::<Value>(&txt) {
-                    if let Some(_stream) = json.get("stream") {
-                        if let Some(data) = json.get("data") {
-                            // Handle trade events
-                            if let Some(event_type) = data.get("e") {
-                                if event_type == "trade" {
-                                    let symbol = data
-                                        .get("s")
-                                        .and_then(|v| v.as_str())
-                                        .unwrap_or("")
-                                        .to_string();
-                                    let price = data
-                                        .get("p")
-                                        .and_then(|v| v.as_str())
-                                        .and_then(|s| s.parse().ok())
-                                        .unwrap_or(0.0);
-                                    let qty = data
-                                        .get("q")
-                                        .and_then(|v| v.as_str())
-                                        .and_then(|s| s.parse().ok())
-                                        .unwrap_or(0.0);
-                                    let side =
-                                        if data.get("m").and_then(|v| v.as_bool()).unwrap_or(false)
-                                        {
-                                            "sell"
-                                        } else {
-                                            "buy"
-                                        };
-                                    let timestamp =
-                                        data.get("T").and_then(|v| v.as_i64()).unwrap_or(0);
-                                    let event = MarketEvent::Trade {
-                                        exchange: "binance".to_string(),
-                                        symbol,
-                                        price,
-                                        qty,
-                                        side: side.to_string(),
-                                        timestamp,
-                                    };
-                                    let _ = sender.send(event).await;
-                                }
-                            }
-                        }
+                let Ok(json) = serde_json::from_str::<Value>(&txt) else {
+                    continue;
+                };
+                if let Some(event) = json.get("data").and_then(parse_event) {
+                    if sender.send(event).await.is_err() {
+                        break;
                     }
                 }
             }
@@ -83,3 +149,53 @@ impl ChannelMarketDataStream for BinanceStream {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn parses_snapshots_and_diffs_with_their_update_ids() {
+        let snap = json!({
+            "lastUpdateId": 160,
+            "bids": [["99.5", "2.0"]],
+            "asks": [["100.5", "1.5"], ["101.0", "3"]],
+        });
+        let MarketEvent::OrderBook { bids, asks, is_snapshot, sequence, .. } =
+            parse_depth_snapshot("SOLUSDC", &snap, 0).unwrap()
+        else {
+            panic!("expected a book event");
+        };
+        assert_eq!(bids, vec![(99.5, 2.0)]);
+        assert_eq!(asks, vec![(100.5, 1.5), (101.0, 3.0)]);
+        assert!(is_snapshot);
+        assert_eq!(sequence, Some(160));
+
+        let diff = json!({
+            "e": "depthUpdate", "E": 123, "s": "SOLUSDC", "U": 157, "u": 162,
+            "b": [["99.5", "0.000"]], "a": [["100.0", "1.0"]],
+        });
+        let MarketEvent::OrderBook { symbol, bids, is_snapshot, sequence, first_sequence, .. } =
+            parse_event(&diff).unwrap()
+        else {
+            panic!("expected a book event");
+        };
+        assert_eq!(symbol, "SOLUSDC");
+        assert_eq!(bids, vec![(99.5, 0.0)]);
+        assert!(!is_snapshot);
+        assert_eq!((first_sequence, sequence), (Some(157), Some(162)));
+    }
+
+    #[test]
+    fn parses_trades() {
+        let trade = json!({
+            "e": "trade", "s": "SOLUSDC", "p": "101.25", "q": "0.5", "m": true, "T": 42,
+        });
+        let Some(MarketEvent::Trade { price, qty, side, timestamp, .. }) = parse_event(&trade)
+        else {
+            panic!("expected a trade");
+        };
+        assert_eq!((price, qty, side.as_str(), timestamp), (101.25, 0.5, "sell", 42));
+    }
+}
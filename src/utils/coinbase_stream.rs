@@ -20,6 +20,10 @@ impl CoinbaseStream {
 
 #[async_trait::async_trait]
 impl ChannelMarketDataStream for CoinbaseStream {
+    fn name(&self) -> &str {
+        "coinbase"
+    }
+
     async fn connect_and_stream_channel(
         &mut self, symbols: Vec<String>, sender: Sender<MarketEvent>,
     ) -> anyhow::Result<()> {
@@ -23,6 +23,10 @@ impl HeliusStream {
 
 #[async_trait::async_trait]
 impl ChannelMarketDataStream for HeliusStream {
+    fn name(&self) -> &str {
+        "helius"
+    }
+
     async fn connect_and_stream_channel(
         &mut self, _symbols: Vec<String>, sender: Sender<MarketEvent>,
     ) -> anyhow::Result<()> {
@@ -4,6 +4,7 @@
 //! `use crate::utils::indicators::*` and access both wrappers from the `ta` crate as
 //! well as a few bespoke indicators that are not included upstream.
 
+use serde::{Deserialize, Serialize};
 use ta::Next;
 use ta::{Period, Reset};
 
@@ -30,7 +31,7 @@ pub trait IndicatorValue {
 
 /// Generic wrapper that caches the last output of any `ta::Next` indicator so
 /// the most-recent value can be queried cheaply.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct CachedIndicator<I> {
     inner: I,
     last: f64,
@@ -28,6 +28,10 @@ impl Default for KrakenStream {
 
 #[async_trait::async_trait]
 impl ChannelMarketDataStream for KrakenStream {
+    fn name(&self) -> &str {
+        "kraken"
+    }
+
     async fn connect_and_stream_channel(
         &mut self, symbols: Vec<String>, sender: Sender<MarketEvent>,
     ) -> anyhow::Result<()> {
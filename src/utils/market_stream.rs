@@ -14,12 +14,23 @@ pub enum MarketEvent {
         side: String,
         timestamp: i64,
     },
+    /// `(price, size)` levels. A snapshot replaces the venue's book; otherwise the levels are
+    /// deltas where size 0 removes the level.
     OrderBook {
         exchange: String,
         symbol: String,
         bids: Vec<(f64, f64)>,
         asks: Vec<(f64, f64)>,
         timestamp: i64,
+        #[serde(default)]
+        is_snapshot: bool,
+        /// Venue sequence number, used to detect missed deltas
+        #[serde(default)]
+        sequence: Option<u64>,
+        /// First sequence number of a delta covering several updates (Binance `U`, with
+        /// `sequence` as `u`); `sequence` when unset
+        #[serde(default)]
+        first_sequence: Option<u64>,
     },
     Ticker {
         exchange: String,
@@ -24,6 +24,10 @@ impl SerumStream {
 
 #[async_trait::async_trait]
 impl ChannelMarketDataStream for SerumStream {
+    fn name(&self) -> &str {
+        "serum"
+    }
+
     async fn connect_and_stream_channel(
         &mut self, _symbols: Vec<String>, sender: Sender<MarketEvent>,
     ) -> anyhow::Result<()> {
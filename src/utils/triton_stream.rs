@@ -24,6 +24,10 @@ impl TritonStream {
 
 #[async_trait]
 impl ChannelMarketDataStream for TritonStream {
+    fn name(&self) -> &str {
+        "triton"
+    }
+
     async fn connect_and_stream_channel(
         &mut self, _symbols: Vec<String>, sender: Sender<MarketEvent>,
     ) -> anyhow::Result<()> {
@@ -101,10 +101,15 @@ pub enum OrderType {
     StopLimit,
 }
 
-/// Status of an order
 /// Represents a pending order waiting for stop/limit trigger
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PendingOrder {
+    /// Id of the engine order this trigger belongs to
+    #[serde(default)]
+    pub id: String,
+    /// Strategy that originated the order
+    #[serde(default)]
+    pub strategy_id: String,
     pub pair: TradingPair,
     pub amount: f64,
     pub is_buy: bool,
@@ -114,8 +119,13 @@ pub struct PendingOrder {
     pub wallet: String,
     pub dex_preference: Vec<String>,
     pub timestamp: i64,
+    /// Execution attempts made after the trigger fired
+    #[serde(default)]
+    pub attempts: u32,
 }
 
+/// Status of an order
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
 pub enum OrderStatus {
     New,
     PartiallyFilled,
@@ -125,6 +135,31 @@ pub enum OrderStatus {
     Expired,
 }
 
+impl OrderStatus {
+    /// True once the order can no longer change state
+    pub fn is_terminal(&self) -> bool {
+        matches!(
+            self,
+            OrderStatus::Filled
+                | OrderStatus::Canceled
+                | OrderStatus::Rejected
+                | OrderStatus::Expired
+        )
+    }
+
+    /// Whether the order lifecycle allows moving from `self` to `next`
+    pub fn can_transition_to(&self, next: OrderStatus) -> bool {
+        match (self, next) {
+            | (OrderStatus::New, OrderStatus::New) => false,
+            | (OrderStatus::New, _) => true,
+            // Once something has filled the remainder can only complete, be canceled or expire
+            | (OrderStatus::PartiallyFilled, OrderStatus::New | OrderStatus::Rejected) => false,
+            | (OrderStatus::PartiallyFilled, _) => true,
+            | _ => false,
+        }
+    }
+}
+
 /// Represents a trade that was executed
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Trade {
@@ -245,6 +280,20 @@ impl Default for MarketData {
     }
 }
 
+impl MarketData {
+    /// This bar as a candle; missing OHLC fields fall back to the close, volume to 0
+    pub fn candle(&self) -> Candle {
+        Candle {
+            timestamp: self.timestamp,
+            open: self.open.unwrap_or(self.close),
+            high: self.high.unwrap_or(self.close),
+            low: self.low.unwrap_or(self.close),
+            close: self.close,
+            volume: self.volume.unwrap_or(0.0),
+        }
+    }
+}
+
 /// Market regime for analytics and strategy adaptation
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
 pub enum MarketRegime {
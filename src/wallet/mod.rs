@@ -10,6 +10,9 @@ use solana_sdk::{
 use spl_token::state::Account as TokenAccount;
 use tokio::sync::RwLock;
 
+/// Mint of wrapped SOL; native SOL balance changes are reported under it
+pub const WSOL_MINT: &str = "So11111111111111111111111111111111111111112";
+
 /// Convenience wrapper around an on-chain Solana wallet (keypair + RPC)
 #[derive(Clone)]
 pub struct Wallet {
@@ -104,6 +107,44 @@ impl Wallet {
         Ok(sig)
     }
 
+    /// Net change of this wallet's balances in a confirmed transaction, in UI units keyed by
+    /// mint. Native SOL, with the fee added back, is booked under [`WSOL_MINT`].
+    pub async fn balance_changes(
+        &self, signature: &solana_sdk::signature::Signature,
+    ) -> Result<std::collections::HashMap<String, f64>> {
+        use solana_client::rpc_config::RpcTransactionConfig;
+        use solana_sdk::commitment_config::CommitmentConfig;
+        use solana_transaction_status::{UiTransactionEncoding, UiTransactionTokenBalance};
+
+        let config = RpcTransactionConfig {
+            encoding: Some(UiTransactionEncoding::JsonParsed),
+            commitment: Some(CommitmentConfig::confirmed()),
+            max_supported_transaction_version: Some(0),
+        };
+        let tx = self.rpc.get_transaction_with_config(signature, config).await?;
+        let meta = tx.transaction.meta.context("transaction has no status meta")?;
+        let owner = self.pubkey().to_string();
+        let pre: Option<Vec<UiTransactionTokenBalance>> = meta.pre_token_balances.into();
+        let post: Option<Vec<UiTransactionTokenBalance>> = meta.post_token_balances.into();
+        let (pre, post) = (pre.unwrap_or_default(), post.unwrap_or_default());
+
+        let mut changes = std::collections::HashMap::new();
+        for (balances, sign) in [(pre, -1.0), (post, 1.0)] {
+            for balance in balances {
+                let owned = Option::<String>::from(balance.owner).is_some_and(|o| o == owner);
+                if let (true, Some(ui)) = (owned, balance.ui_token_amount.ui_amount) {
+                    *changes.entry(balance.mint).or_insert(0.0) += sign * ui;
+                }
+            }
+        }
+        // we pay the fee, so we are account 0
+        if let (Some(pre), Some(post)) = (meta.pre_balances.first(), meta.post_balances.first()) {
+            let lamports = *post as f64 + meta.fee as f64 - *pre as f64;
+            *changes.entry(WSOL_MINT.to_string()).or_insert(0.0) += lamports / 1e9;
+        }
+        Ok(changes)
+    }
+
     /// Sign and send a base64-encoded VersionedTransaction produced by Jupiter swap API
     pub async fn sign_and_send_serialized_tx(
         &self, tx_b64: &str,
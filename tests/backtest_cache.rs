@@ -0,0 +1,90 @@
+//! Report cache keys must change with anything that changes the backtest outcome
+
+mod common;
+
+use algotraderv2::backtest::cache::BacktestCache;
+use algotraderv2::backtest::providers::CSVHistoricalDataProvider;
+use algotraderv2::backtest::Backtester;
+use algotraderv2::risk::StopLossRule;
+use algotraderv2::strategies::{
+    AllocationStrategy, MeanReversionStrategy, StrategyConfig, TimeFrame, TradingStrategy,
+    TrendFollowingStrategy,
+};
+use common::sol_bars;
+
+fn backtester(lookback: usize) -> Backtester {
+    let strategy: Box<dyn TradingStrategy> = Box::new(MeanReversionStrategy::new(
+        "SOL/USDC",
+        TimeFrame::OneHour,
+        lookback,
+        2.0,
+        2.0,
+        1.0,
+    ));
+    Backtester::new(Box::new(CSVHistoricalDataProvider::new()), "1h", 10_000.0, vec![strategy])
+}
+
+#[test]
+fn fingerprint_covers_data_params_costs_and_rules() {
+    std::env::set_var("BACKTEST_NO_CACHE", "1");
+    let dir = tempfile::tempdir().unwrap();
+    let data = sol_bars(dir.path(), &[100.0, 101.0, 102.0]);
+    let base = backtester(20).fingerprint(&data);
+    assert_eq!(base, backtester(20).fingerprint(&data));
+
+    let edited = sol_bars(dir.path(), &[100.0, 101.0, 103.0]);
+    assert_ne!(base, backtester(20).fingerprint(&edited));
+    assert_ne!(base, backtester(30).fingerprint(&data));
+    let fees = Backtester { fee_bps: 5, ..backtester(20) };
+    assert_ne!(base, fees.fingerprint(&data));
+    let slippage = Backtester { slippage_bps: 5, ..backtester(20) };
+    assert_ne!(base, slippage.fingerprint(&data));
+    let stop = Backtester { risk_rules: vec![Box::new(StopLossRule::new(0.05))], ..backtester(20) };
+    let wider = Backtester { risk_rules: vec![Box::new(StopLossRule::new(0.1))], ..backtester(20) };
+    assert_ne!(stop.fingerprint(&data), wider.fingerprint(&data));
+}
+
+fn trend_following(fast_ema: u64) -> Box<dyn TradingStrategy> {
+    let cfg = StrategyConfig {
+        name: "trend_following".into(),
+        enabled: true,
+        params: serde_json::json!({ "fast_ema_period": fast_ema }),
+        performance: None,
+    };
+    Box::new(TrendFollowingStrategy::try_from(&cfg).unwrap())
+}
+
+#[test]
+fn fingerprint_covers_params_of_every_strategy() {
+    std::env::set_var("BACKTEST_NO_CACHE", "1");
+    let dir = tempfile::tempdir().unwrap();
+    let data = sol_bars(dir.path(), &[100.0, 101.0, 102.0]);
+    let run = |s: Box<dyn TradingStrategy>| {
+        Backtester::new(Box::new(CSVHistoricalDataProvider::new()), "1h", 10_000.0, vec![s])
+            .fingerprint(&data)
+    };
+    assert_eq!(run(trend_following(9)), run(trend_following(9)));
+    assert_ne!(run(trend_following(9)), run(trend_following(12)));
+    let wrapped = |fast_ema| -> Box<dyn TradingStrategy> {
+        Box::new(AllocationStrategy::new("alloc", vec![trend_following(fast_ema)], vec![1.0]))
+    };
+    assert_ne!(run(wrapped(9)), run(wrapped(12)));
+}
+
+#[tokio::test]
+async fn cached_report_is_reused_only_for_the_same_run() {
+    std::env::set_var("BACKTEST_NO_CACHE", "1");
+    let dir = tempfile::tempdir().unwrap();
+    let data = sol_bars(dir.path(), &[100.0, 101.0, 102.0]);
+    let cache = BacktestCache::open(dir.path().join("db").to_str().unwrap()).unwrap();
+
+    let mut bt = Backtester { cache: Some(cache.clone()), ..backtester(20) };
+    bt.run_with_data(&data).await.unwrap();
+    bt.run_with_data(&data).await.unwrap();
+    let mut costly = Backtester { cache: Some(cache.clone()), fee_bps: 10, ..backtester(20) };
+    costly.run_with_data(&data).await.unwrap();
+
+    let entries = cache.entries().unwrap();
+    assert_eq!(entries.len(), 2);
+    assert!(entries.iter().all(|e| e.symbols == "SOL/USDC" && e.timeframe == "1h"));
+}
@@ -0,0 +1,66 @@
+//! Trades must reach the portfolio at the simulated time they happen
+
+mod common;
+
+use algotraderv2::backtest::providers::CSVHistoricalDataProvider;
+use algotraderv2::backtest::Backtester;
+use algotraderv2::risk::StopLossRule;
+use algotraderv2::utils::types::OrderSide;
+use common::{sol_csv, Scripted};
+
+fn backtester() -> Backtester {
+    Backtester::new(
+        Box::new(CSVHistoricalDataProvider::new()),
+        "1h",
+        10_000.0,
+        vec![Box::new(Scripted::buy_on_bar(1, None))],
+    )
+}
+
+#[tokio::test]
+async fn buy_on_bar_n_shows_in_equity_on_bar_n_plus_one() {
+    std::env::set_var("BACKTEST_NO_CACHE", "1");
+    let dir = tempfile::tempdir().unwrap();
+    let data = sol_csv(dir.path(), &[100.0, 100.0, 110.0, 120.0]);
+
+    let report = backtester().run(&data).await.unwrap();
+    assert_eq!(report.equity_curve, vec![10_000.0, 10_000.0, 10_100.0, 10_200.0]);
+    assert_eq!(report.trades[0].timestamp, 3600);
+}
+
+#[tokio::test]
+async fn stop_loss_exits_before_later_bars() {
+    std::env::set_var("BACKTEST_NO_CACHE", "1");
+    let dir = tempfile::tempdir().unwrap();
+    let data = sol_csv(dir.path(), &[100.0, 100.0, 90.0, 50.0, 50.0]);
+
+    let mut bt = Backtester { risk_rules: vec![Box::new(StopLossRule::new(0.05))], ..backtester() };
+    let report = bt.run(&data).await.unwrap();
+    // stopped out at 90 on bar 2, so the crash to 50 no longer hits equity
+    assert_eq!(report.trades.len(), 2);
+    assert_eq!(report.trades[1].timestamp, 7200);
+    assert!((report.ending_balance - 9_900.0).abs() < 1e-9);
+    assert!((report.max_drawdown - 0.01).abs() < 1e-9);
+}
+
+#[tokio::test]
+async fn strategy_exit_and_stop_on_one_bar_sell_the_position_once() {
+    std::env::set_var("BACKTEST_NO_CACHE", "1");
+    let dir = tempfile::tempdir().unwrap();
+    let data = sol_csv(dir.path(), &[100.0, 100.0, 90.0, 90.0]);
+
+    let strategy = Scripted::buy_on_bar(1, Some(2));
+    let mut bt = Backtester {
+        strategies: vec![Box::new(strategy)],
+        risk_rules: vec![Box::new(StopLossRule::new(0.05))],
+        fee_bps: 10,
+        ..backtester()
+    };
+    let report = bt.run(&data).await.unwrap();
+    let sold: f64 =
+        report.trades.iter().filter(|t| t.side == OrderSide::Sell).map(|t| t.qty).sum();
+    assert_eq!(sold, 10.0, "the second exit has nothing left to sell");
+    assert_eq!(report.trades.len(), 2);
+    // bought 10 @ 100 and sold 10 @ 90, each leg paying 10 bps
+    assert!((report.ending_balance - (9_900.0 - 1.0 - 0.9)).abs() < 1e-9);
+}
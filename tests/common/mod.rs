@@ -0,0 +1,102 @@
+//! Fixtures shared by the backtest integration tests
+#![allow(dead_code)] // every test crate uses a different subset
+
+use std::path::{Path, PathBuf};
+
+use algotraderv2::backtest::providers::CSVHistoricalDataProvider;
+use algotraderv2::backtest::HistoricalDataProvider;
+use algotraderv2::strategies::{TimeFrame, TradingStrategy};
+use algotraderv2::trading::{MarketData, OrderType, Position, Signal, SignalType};
+use async_trait::async_trait;
+
+pub fn write_csv(dir: &Path, name: &str, body: &str) -> PathBuf {
+    let path = dir.join(name);
+    std::fs::write(&path, body).unwrap();
+    path
+}
+
+/// Hourly close-only SOL/USDC bars from t = 0, in a file named after the pair
+pub fn sol_csv(dir: &Path, closes: &[f64]) -> PathBuf {
+    let mut body = String::from("timestamp,close\n");
+    for (i, c) in closes.iter().enumerate() {
+        body.push_str(&format!("{},{}\n", i * 3600, c));
+    }
+    write_csv(dir, "SOL_USDC_1h.csv", &body)
+}
+
+/// The bars of [`sol_csv`], loaded
+pub fn sol_bars(dir: &Path, closes: &[f64]) -> Vec<MarketData> {
+    CSVHistoricalDataProvider::new().load(&sol_csv(dir, closes)).unwrap()
+}
+
+/// Buys `size` units of `symbol` on the `buy_at`-th bar it sees (from 0) and sells them on the
+/// `sell_at`-th
+#[derive(Clone)]
+pub struct Scripted {
+    pub symbol: String,
+    pub size: f64,
+    pub buy_at: usize,
+    pub sell_at: Option<usize>,
+    /// Send the buy as a limit order at this price instead of at market
+    pub limit: Option<f64>,
+    seen: usize,
+}
+
+impl Scripted {
+    /// Buys `size` units of `symbol` at market on the first bar and never trades again
+    pub fn buy_first(symbol: &str, size: f64) -> Self {
+        Self { symbol: symbol.into(), size, buy_at: 0, sell_at: None, limit: None, seen: 0 }
+    }
+
+    /// Buys 10 SOL/USDC on bar `at` and sells them on bar `sell_at`
+    pub fn buy_on_bar(at: usize, sell_at: Option<usize>) -> Self {
+        Self { buy_at: at, sell_at, ..Self::buy_first("SOL/USDC", 10.0) }
+    }
+
+    pub fn with_limit(self, price: f64) -> Self {
+        Self { limit: Some(price), ..self }
+    }
+}
+
+#[async_trait]
+impl TradingStrategy for Scripted {
+    fn name(&self) -> &str {
+        "scripted"
+    }
+
+    fn timeframe(&self) -> TimeFrame {
+        TimeFrame::OneHour
+    }
+
+    fn symbols(&self) -> Vec<String> {
+        vec![self.symbol.clone()]
+    }
+
+    async fn generate_signals(&mut self, md: &MarketData) -> Vec<Signal> {
+        self.seen += 1;
+        let bar = self.seen - 1;
+        let (signal_type, limit) = if bar == self.buy_at {
+            (SignalType::Buy, self.limit)
+        } else if Some(bar) == self.sell_at {
+            (SignalType::Sell, None)
+        } else {
+            return Vec::new();
+        };
+        vec![Signal {
+            symbol: self.symbol.clone(),
+            signal_type,
+            price: md.close,
+            size: self.size,
+            timestamp: md.timestamp,
+            confidence: 1.0,
+            order_type: if limit.is_some() { OrderType::Limit } else { OrderType::Market },
+            limit_price: limit,
+            stop_price: None,
+            metadata: None,
+        }]
+    }
+
+    fn get_positions(&self) -> Vec<&Position> {
+        Vec::new()
+    }
+}
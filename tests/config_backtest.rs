@@ -0,0 +1,135 @@
+//! Backtests built from the live trading configuration
+
+mod common;
+
+use algotraderv2::backtest::providers::CSVHistoricalDataProvider;
+use algotraderv2::backtest::{Backtester, OrderSplit};
+use algotraderv2::config::Config;
+use algotraderv2::risk::position_sizer::FixedFractionalSizer;
+use algotraderv2::risk::SignalLimits;
+use algotraderv2::strategies::StrategyConfig;
+use common::{sol_csv, write_csv, Scripted};
+
+#[tokio::test]
+async fn sizer_and_split_replace_signal_size() {
+    std::env::set_var("BACKTEST_NO_CACHE", "1");
+    let dir = tempfile::tempdir().unwrap();
+    let data = sol_csv(dir.path(), &[100.0; 3]);
+
+    let mut bt = Backtester {
+        // 0.05% of 10k cash = 5 units, sent as 2 + 2 + 1
+        position_sizer: Some(Box::new(FixedFractionalSizer::new(0.0005))),
+        order_split: Some(OrderSplit { threshold: 1.0, chunk: 2.0 }),
+        ..Backtester::new(
+            Box::new(CSVHistoricalDataProvider::new()),
+            "1h",
+            10_000.0,
+            vec![Box::new(Scripted::buy_first("SOL/USDC", 100.0))],
+        )
+    };
+    let report = bt.run(&data).await.unwrap();
+    let qtys: Vec<f64> = report.trades.iter().map(|t| t.qty).collect();
+    assert_eq!(qtys, vec![2.0, 2.0, 1.0]);
+}
+
+fn limited(limits: SignalLimits) -> Backtester {
+    Backtester {
+        limits: Some(limits),
+        ..Backtester::new(
+            Box::new(CSVHistoricalDataProvider::new()),
+            "1h",
+            10_000.0,
+            vec![Box::new(Scripted::buy_first("SOL/USDC", 100.0))],
+        )
+    }
+}
+
+#[tokio::test]
+async fn signal_limits_cap_and_count_refusals() {
+    std::env::set_var("BACKTEST_NO_CACHE", "1");
+    let dir = tempfile::tempdir().unwrap();
+    let data = sol_csv(dir.path(), &[100.0; 3]);
+    let mut bt = limited(SignalLimits { max_position_abs: 3.0, ..Default::default() });
+    let capped = bt.run(&data).await.unwrap();
+    assert_eq!(capped.trades[0].qty, 3.0);
+    assert_eq!(capped.rejected_signals, 0);
+
+    let mut bt = limited(SignalLimits { max_open_trades: 0, ..Default::default() });
+    let refused = bt.run(&data).await.unwrap();
+    assert!(refused.trades.is_empty());
+    assert_eq!(refused.rejected_signals, 1);
+}
+
+#[test]
+fn from_config_mirrors_live_settings() {
+    let mut config = Config::default();
+    config.trading.starting_balance_usd = 2_500.0;
+    config.trading.slippage_bps = 40;
+    config.trading.strategies.push(StrategyConfig {
+        name: "mean_reversion".into(),
+        enabled: true,
+        params: serde_json::json!({ "symbol": "SOL/USDC" }),
+        performance: None,
+    });
+    config.trading.strategies.push(StrategyConfig {
+        name: "momentum".into(),
+        enabled: false,
+        params: serde_json::json!({}),
+        performance: None,
+    });
+
+    let bt =
+        Backtester::from_config(&config, Box::new(CSVHistoricalDataProvider::new()), "1h").unwrap();
+    assert_eq!(bt.strategies.len(), 1);
+    assert_eq!(bt.starting_balance, 2_500.0);
+    assert_eq!(bt.slippage_bps, 40);
+    assert_eq!(bt.fee_bps, config.trading.paper.fee_bps);
+    assert_eq!(bt.risk_rules.len(), config.risk.build_rules().len());
+    assert!(bt.position_sizer.is_some());
+    assert!(bt.order_split.is_some());
+    assert_eq!(bt.limits, Some(config.signal_limits()));
+    assert!(bt.fill_model.describe().contains("InstantFillModel"));
+}
+
+#[tokio::test]
+async fn configured_fill_model_partially_fills_a_limit_order() {
+    std::env::set_var("BACKTEST_NO_CACHE", "1");
+    let dir = tempfile::tempdir().unwrap();
+    // The buy limit at 95 is crossed on the next two bars, each only deep enough for part of it
+    let data = write_csv(
+        dir.path(),
+        "SOL_USDC_1h.csv",
+        "timestamp,open,high,low,close,volume\n\
+         0,100,100,100,100,1000\n\
+         3600,99,99,94,96,500\n\
+         7200,96,97,94,96,300\n",
+    );
+
+    let mut config = Config::default();
+    config.trading.starting_balance_usd = 10_000.0;
+    config.backtest = toml::from_str(
+        "[fill_model]\ntype = \"realistic\"\nparticipation_rate = 0.1\nimpact_coef = 0.0\n",
+    )
+    .unwrap();
+    config.trading.strategies.push(StrategyConfig {
+        name: "mean_reversion".into(),
+        enabled: true,
+        params: serde_json::json!({ "symbol": "SOL/USDC" }),
+        performance: None,
+    });
+    let mut bt =
+        Backtester::from_config(&config, Box::new(CSVHistoricalDataProvider::new()), "1h").unwrap();
+    bt.strategies = vec![Box::new(Scripted::buy_first("SOL/USDC", 100.0).with_limit(95.0))];
+    bt.position_sizer = None;
+    bt.order_split = None;
+
+    let report = bt.run(&data).await.unwrap();
+    let fills: Vec<(f64, f64)> = report.trades.iter().map(|t| (t.qty, t.price)).collect();
+    assert_eq!(fills, vec![(50.0, 95.0), (30.0, 95.0)]);
+}
+
+#[test]
+fn from_config_needs_an_enabled_strategy() {
+    let provider = Box::new(CSVHistoricalDataProvider::new());
+    assert!(Backtester::from_config(&Config::default(), provider, "1h").is_err());
+}
@@ -24,3 +24,29 @@ async fn trading_engine_smoke_test() {
     // assert removed
     // assert removed
 }
+
+#[tokio::test]
+async fn trailing_stop_fires_on_price_updates_alone() {
+    use algotraderv2_rust::config::risk_rules::TrailingStopConfig;
+    use algotraderv2_rust::utils::types::{OrderSide, OrderStatus, TradingPair};
+
+    let mut config = Config::default();
+    config.risk.trailing_stop = Some(TrailingStopConfig::Percent { pct: 5.0 });
+    // leave the trailing stop as the only exit a 20% run-up can hit
+    config.risk.default_take_profit_pct = 0.0;
+    let mut engine = TradingEngine::with_config_async(config, true).await;
+    // a pair the background price feed does not touch
+    let pair = TradingPair::new("JUP", "USDC");
+    engine.portfolio.update_on_buy("JUP/USDC", 10.0, 1.0);
+
+    engine.on_price_update(&pair, 1.2).await.unwrap();
+    assert!(engine.order_manager.orders_with_status(OrderStatus::Filled).is_empty());
+    // 5% under the 1.2 high-water mark, no fill or signal in between
+    engine.on_price_update(&pair, 1.13).await.unwrap();
+    let filled = engine.order_manager.orders_with_status(OrderStatus::Filled);
+    assert_eq!(filled.len(), 1, "the exit must go out as an order");
+    assert_eq!(filled[0].side, OrderSide::Sell);
+    assert_eq!(filled[0].quantity, 10.0);
+    // the ledger follows the confirmed fill
+    assert!(!engine.portfolio.positions.contains_key("JUP/USDC"));
+}
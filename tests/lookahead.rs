@@ -0,0 +1,98 @@
+//! No factory strategy may change its signals when future candles are visible
+
+use algotraderv2::backtest::lookahead::{
+    assert_no_lookahead, default_params, detect, strategy_names,
+};
+use algotraderv2::strategies::{StrategyConfig, StrategyFactory, TimeFrame, TradingStrategy};
+use algotraderv2::trading::{MarketData, OrderType, Position, Signal, SignalType};
+use algotraderv2::utils::types::TradingPair;
+use async_trait::async_trait;
+
+fn bars() -> Vec<MarketData> {
+    (0..300)
+        .map(|i| {
+            let close = 100.0 + (i as f64 / 7.0).sin() * 8.0 + (i as f64 / 31.0).cos() * 4.0;
+            MarketData {
+                symbol: "SOL/USDC".into(),
+                pair: TradingPair::new("SOL", "USDC"),
+                timestamp: i * 3600,
+                open: Some(close - 0.5),
+                high: Some(close + 1.0),
+                low: Some(close - 1.0),
+                close,
+                last_price: close,
+                volume: Some(1_000.0),
+                ..Default::default()
+            }
+        })
+        .collect()
+}
+
+/// Buys whenever the last candle it is handed closes above the current bar
+#[derive(Clone)]
+struct PeeksAtLastCandle;
+
+#[async_trait]
+impl TradingStrategy for PeeksAtLastCandle {
+    fn name(&self) -> &str {
+        "peeks"
+    }
+
+    fn timeframe(&self) -> TimeFrame {
+        TimeFrame::OneHour
+    }
+
+    fn symbols(&self) -> Vec<String> {
+        vec!["SOL/USDC".into()]
+    }
+
+    async fn generate_signals(&mut self, md: &MarketData) -> Vec<Signal> {
+        match md.candles.last() {
+            | Some(c) if c.close > md.close => vec![Signal {
+                symbol: md.symbol.clone(),
+                signal_type: SignalType::Buy,
+                price: md.close,
+                size: 1.0,
+                timestamp: md.timestamp,
+                confidence: 1.0,
+                order_type: OrderType::Market,
+                limit_price: None,
+                stop_price: None,
+                metadata: None,
+            }],
+            | _ => Vec::new(),
+        }
+    }
+
+    fn get_positions(&self) -> Vec<&Position> {
+        Vec::new()
+    }
+}
+
+#[tokio::test]
+async fn factory_strategies_do_not_peek() {
+    let data = bars();
+    for name in strategy_names() {
+        let params = default_params(name, "SOL/USDC", &serde_json::Value::Null);
+        assert_no_lookahead(name, &params, &data).await;
+    }
+}
+
+#[test]
+fn composite_params_trade_the_requested_symbol() {
+    for name in ["ensemble", "allocation"] {
+        let params = default_params(name, "BONK/USDC", &serde_json::Value::Null);
+        let config =
+            StrategyConfig { name: name.into(), enabled: true, params, performance: None };
+        let strategy = StrategyFactory::create_strategy(name, &config).unwrap();
+        assert_eq!(strategy.symbols(), vec!["BONK/USDC".to_string()], "{name}");
+    }
+}
+
+#[tokio::test]
+async fn peeking_strategy_is_flagged() {
+    let data = bars();
+    let report = detect(Box::new(PeeksAtLastCandle), Box::new(PeeksAtLastCandle), &data).await;
+    assert!(!report.is_clean());
+    assert_eq!(report.bars, data.len());
+}
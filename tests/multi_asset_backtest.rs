@@ -0,0 +1,95 @@
+//! Multi-symbol data loading and timestamp-interleaved backtests
+
+mod common;
+
+use algotraderv2::backtest::providers::CSVHistoricalDataProvider;
+use algotraderv2::backtest::{Backtester, HistoricalDataProvider};
+use common::{write_csv, Scripted};
+
+#[test]
+fn files_merge_by_timestamp_with_inferred_pairs() {
+    std::env::set_var("BACKTEST_NO_CACHE", "1");
+    let dir = tempfile::tempdir().unwrap();
+    let sol = write_csv(dir.path(), "SOL_USDC_1h.csv", "timestamp,close\n0,100\n3600,101\n");
+    let btc = write_csv(dir.path(), "BTC_USDC_1h.csv", "timestamp,close\n1800,1000\n5400,1001\n");
+
+    let data = CSVHistoricalDataProvider::new().load_many(&[sol, btc]).unwrap();
+    let seen: Vec<(i64, &str)> = data.iter().map(|d| (d.timestamp, d.symbol.as_str())).collect();
+    assert_eq!(
+        seen,
+        vec![(0, "SOL/USDC"), (1800, "BTC/USDC"), (3600, "SOL/USDC"), (5400, "BTC/USDC")]
+    );
+}
+
+#[test]
+fn symbol_column_overrides_file_name() {
+    std::env::set_var("BACKTEST_NO_CACHE", "1");
+    let dir = tempfile::tempdir().unwrap();
+    let file = write_csv(
+        dir.path(),
+        "mixed.csv",
+        "timestamp,symbol,close\n60,ETH-USDC,3000\n0,sol/usdc,100\n",
+    );
+    let data = CSVHistoricalDataProvider::new().load(&file).unwrap();
+    assert_eq!(data[0].symbol, "SOL/USDC");
+    assert_eq!(data[1].pair.base, "ETH");
+}
+
+#[test]
+fn file_names_only_name_pairs_with_a_known_quote() {
+    std::env::set_var("BACKTEST_NO_CACHE", "1");
+    let dir = tempfile::tempdir().unwrap();
+    let body = "timestamp,close\n0,100\n";
+    for name in ["sample_data.csv", "btc_1h.csv"] {
+        let file = write_csv(dir.path(), name, body);
+        let err = CSVHistoricalDataProvider::new().load(&file).unwrap_err();
+        assert!(err.to_string().contains("cannot tell the pair"), "{name}: {err}");
+    }
+    let file = write_csv(dir.path(), "bonk_sol_15m.csv", body);
+    let data = CSVHistoricalDataProvider::new().load(&file).unwrap();
+    assert_eq!(data[0].symbol, "BONK/SOL");
+}
+
+#[tokio::test]
+async fn strategies_only_see_their_own_pair() {
+    std::env::set_var("BACKTEST_NO_CACHE", "1");
+    let dir = tempfile::tempdir().unwrap();
+    // BTC prints first; a mis-routed SOL strategy would buy at the BTC price
+    let btc = write_csv(dir.path(), "BTC_USDC_1h.csv", "timestamp,close\n0,1000\n3600,1000\n");
+    let sol = write_csv(dir.path(), "SOL_USDC_1h.csv", "timestamp,close\n1800,100\n5400,110\n");
+
+    let mut bt = Backtester::new(
+        Box::new(CSVHistoricalDataProvider::new()),
+        "1h",
+        10_000.0,
+        vec![Box::new(Scripted::buy_first("SOL/USDC", 1.0))],
+    );
+    let report = bt.run_many(&[btc, sol]).await.unwrap();
+    assert_eq!(report.total_trades, 1);
+    assert_eq!(report.equity_curve.len(), 4);
+    assert!((report.ending_balance - 10_010.0).abs() < 1e-9);
+}
+
+#[tokio::test]
+async fn pre_trade_risk_resizes_orders() {
+    use algotraderv2::config::PortfolioRiskConfig;
+    use algotraderv2::risk::portfolio_risk::PreTradeRiskEngine;
+
+    std::env::set_var("BACKTEST_NO_CACHE", "1");
+    let dir = tempfile::tempdir().unwrap();
+    let sol = write_csv(dir.path(), "SOL_USDC_1h.csv", "timestamp,close\n0,100\n3600,100\n");
+    let cfg = PortfolioRiskConfig { max_gross_exposure_pct: Some(50.0), ..Default::default() };
+    let strategy = Scripted::buy_first("SOL/USDC", 80.0);
+    let mut bt = Backtester {
+        pre_trade_risk: Some(PreTradeRiskEngine::new(cfg)),
+        ..Backtester::new(
+            Box::new(CSVHistoricalDataProvider::new()),
+            "1h",
+            10_000.0,
+            vec![Box::new(strategy)],
+        )
+    };
+    let report = bt.run_many(&[sol]).await.unwrap();
+    assert_eq!(report.trades.len(), 1);
+    assert!((report.trades[0].qty - 50.0).abs() < 1e-9);
+}
@@ -1,7 +1,10 @@
 use algotraderv2::backtest::{
-    harness::{run_walk_forward, WalkForwardConfig},
+    harness::{run_cpcv, run_walk_forward, CpcvConfig, WalkForwardConfig},
+    optimizer::{Objective, Optimizer, ParamSpace, SearchMethod},
     SimMode,
 };
+use algotraderv2::strategies::StrategyConfig;
+use algotraderv2::trading::{MarketData, TradingPair};
 use std::io::Write;
 
 /// Smoke test that runs the walk-forward harness on a small synthetic dataset.
@@ -21,9 +24,40 @@ async fn walk_forward_smoke() -> anyhow::Result<()> {
     }
 
     // Run harness
-    let cfg = WalkForwardConfig { train_days: 90, test_days: 30, step_days: 30 };
-    let reports = run_walk_forward(tmp.path(), "1h", SimMode::Bar, cfg).await?;
+    let cfg =
+        WalkForwardConfig { train_days: 90, test_days: 30, step_days: 30, ..Default::default() };
+    let pair = TradingPair::new("SOL", "USDC");
+    let reports = run_walk_forward(tmp.path(), Some(&pair), "1h", SimMode::Bar, cfg).await?;
     assert!(!reports.is_empty(), "no reports generated");
     println!("walk_forward_smoke: generated {} reports", reports.len());
     Ok(())
 }
+
+/// CPCV with 6 groups and 2 test groups yields C(6,2) = 15 splits and 5 full paths
+#[test]
+fn cpcv_builds_every_path() -> anyhow::Result<()> {
+    let data: Vec<MarketData> = (0..600)
+        .map(|i| MarketData {
+            symbol: "SOL/USDC".into(),
+            timestamp: i * 3600,
+            close: 100.0 + (i as f64 * 0.3).sin() * 5.0,
+            ..Default::default()
+        })
+        .collect();
+    let cfg = StrategyConfig {
+        name: "mean_reversion".into(),
+        enabled: true,
+        params: serde_json::json!({ "symbol": "SOL/USDC", "lookback_period": [10, 20] }),
+        performance: None,
+    };
+    let optimizer = Optimizer {
+        timeframe: "1h".into(),
+        ..Optimizer::new(ParamSpace::from_config(&cfg)?, Objective::Sharpe, SearchMethod::Grid)
+    };
+    let cpcv = CpcvConfig { groups: 6, test_groups: 2, purge_secs: 3600, embargo_secs: 3600 };
+    let result = run_cpcv(&data, &cpcv, &optimizer)?;
+    assert_eq!(result.splits.len(), 15);
+    assert_eq!(result.paths.len(), 5);
+    assert!(result.paths.iter().all(|p| p.total_return.is_finite()));
+    Ok(())
+}
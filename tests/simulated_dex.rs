@@ -0,0 +1,94 @@
+//! Offline execution tests against the simulated constant-product DEX
+
+use algotraderv2::dex::{DexClient, SimulatedDex};
+use algotraderv2::utils::types::OrderType;
+use algotraderv2::wallet::Wallet;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::signature::Keypair;
+
+fn paper_wallet() -> Wallet {
+    Wallet::new(RpcClient::new("http://127.0.0.1:8899".to_string()), Keypair::new())
+}
+
+fn dex(seed: u64) -> SimulatedDex {
+    // 10k SOL against 1M USDC -> spot 100
+    SimulatedDex::new(seed).with_pool("SOL", "USDC", 10_000.0, 1_000_000.0)
+}
+
+async fn market(
+    dex: &SimulatedDex, qty: f64, is_buy: bool, slippage_bps: u16, wallet: &Wallet,
+) -> algotraderv2::Result<String> {
+    dex.execute_trade(
+        "SOL",
+        "USDC",
+        qty,
+        is_buy,
+        slippage_bps,
+        5_000,
+        OrderType::Market,
+        None,
+        None,
+        None,
+        wallet,
+    )
+    .await
+}
+
+#[tokio::test]
+async fn market_buy_moves_price_and_reports_fill() {
+    let dex = dex(7);
+    let wallet = paper_wallet();
+    let tx = market(&dex, 50.0, true, 100, &wallet).await.expect("market buy");
+
+    let fill = dex.fill(&tx).expect("fill recorded");
+    assert!(fill.avg_price > 100.0, "buyer pays above spot: {}", fill.avg_price);
+    assert!(dex.get_price("SOL", "USDC").await.unwrap() > 100.0);
+    assert_eq!(dex.fill_price(&tx).await, Some(fill.avg_price));
+    assert!(dex.fill(&tx).is_none(), "the record is dropped once the price is read");
+    assert!((dex.get_balance("SOL").await.unwrap() - 50.0).abs() < 1e-9);
+}
+
+#[tokio::test]
+async fn same_seed_same_signatures() {
+    let wallet = paper_wallet();
+    let (a, b) = (dex(42), dex(42));
+    for _ in 0..3 {
+        let ta = market(&a, 1.0, false, 100, &wallet).await.unwrap();
+        let tb = market(&b, 1.0, false, 100, &wallet).await.unwrap();
+        assert_eq!(ta, tb);
+    }
+}
+
+#[tokio::test]
+async fn large_order_exceeds_slippage_tolerance() {
+    let dex = dex(1);
+    let wallet = paper_wallet();
+    // 10% of the pool -> ~11% impact, far above 50 bps
+    let res = market(&dex, 1_000.0, true, 50, &wallet).await;
+    assert!(res.is_err());
+    // Rejected swap leaves the pool untouched
+    assert!((dex.get_price("SOL", "USDC").await.unwrap() - 100.0).abs() < 1e-9);
+}
+
+#[tokio::test]
+async fn stop_orders_wait_for_trigger() {
+    let dex = dex(3);
+    let wallet = paper_wallet();
+    let err = dex
+        .execute_trade(
+            "SOL",
+            "USDC",
+            1.0,
+            false,
+            100,
+            5_000,
+            OrderType::Stop,
+            None,
+            Some(95.0),
+            None,
+            &wallet,
+        )
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("Stop price not triggered"));
+}
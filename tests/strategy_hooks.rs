@@ -0,0 +1,108 @@
+//! Lifecycle hooks and timers run on simulated time in backtests
+
+use std::sync::{Arc, Mutex};
+
+use algotraderv2::backtest::providers::CSVHistoricalDataProvider;
+use algotraderv2::backtest::Backtester;
+use algotraderv2::portfolio::Position as PortfolioPosition;
+use algotraderv2::strategies::{StrategyContext, TimeFrame, TradingStrategy};
+use algotraderv2::trading::{MarketData, OrderType, Position, Signal, SignalType};
+use async_trait::async_trait;
+
+/// Buys one unit on its first two-hourly timer and logs every hook
+#[derive(Clone, Default)]
+struct Clockwork {
+    log: Arc<Mutex<Vec<String>>>,
+    fired: usize,
+}
+
+impl Clockwork {
+    fn record(&self, entry: String) {
+        self.log.lock().unwrap().push(entry);
+    }
+}
+
+#[async_trait]
+impl TradingStrategy for Clockwork {
+    fn name(&self) -> &str {
+        "clockwork"
+    }
+
+    fn timeframe(&self) -> TimeFrame {
+        TimeFrame::OneHour
+    }
+
+    fn symbols(&self) -> Vec<String> {
+        vec!["SOL/USDC".into()]
+    }
+
+    async fn generate_signals(&mut self, _md: &MarketData) -> Vec<Signal> {
+        Vec::new()
+    }
+
+    fn on_start(&mut self, ctx: &mut StrategyContext) {
+        self.record(format!("start@{}", ctx.now()));
+        ctx.set_interval(1, 7200);
+    }
+
+    fn on_timer(&mut self, id: u64, ctx: &mut StrategyContext) -> Vec<Signal> {
+        self.record(format!("timer{id}@{}", ctx.now()));
+        self.fired += 1;
+        if self.fired > 1 {
+            return Vec::new();
+        }
+        vec![Signal {
+            symbol: "SOL/USDC".into(),
+            signal_type: SignalType::Buy,
+            price: 100.0,
+            size: 1.0,
+            timestamp: ctx.now(),
+            confidence: 1.0,
+            order_type: OrderType::Market,
+            limit_price: None,
+            stop_price: None,
+            metadata: None,
+        }]
+    }
+
+    fn on_position_update(
+        &mut self, symbol: &str, position: &PortfolioPosition, ctx: &mut StrategyContext,
+    ) {
+        self.record(format!("{symbol}={}@{}", position.size, ctx.now()));
+    }
+
+    fn on_stop(&mut self, ctx: &mut StrategyContext) {
+        self.record(format!("stop@{}", ctx.now()));
+    }
+
+    fn get_positions(&self) -> Vec<&Position> {
+        Vec::new()
+    }
+}
+
+#[tokio::test]
+async fn hooks_follow_simulated_time() {
+    std::env::set_var("BACKTEST_NO_CACHE", "1");
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("SOL_USDC_1h.csv");
+    let body: String = (0..6).map(|i| format!("{},100\n", i * 3600)).collect();
+    std::fs::write(&path, format!("timestamp,close\n{body}")).unwrap();
+
+    let strategy = Clockwork::default();
+    let log = strategy.log.clone();
+    let mut bt = Backtester::new(
+        Box::new(CSVHistoricalDataProvider::new()),
+        "1h",
+        10_000.0,
+        vec![Box::new(strategy)],
+    );
+    let report = bt.run(&path).await.unwrap();
+
+    // the timer order rests until the next bar; the 21600 tick lies past the data
+    assert_eq!(report.trades.len(), 1);
+    assert_eq!(report.trades[0].timestamp, 10_800);
+    assert_eq!(
+        *log.lock().unwrap(),
+        vec!["start@0", "timer1@7200", "SOL/USDC=1@10800", "timer1@14400", "stop@18000"]
+    );
+}